@@ -22,4 +22,14 @@ pub struct Cli {
     /// Output machine code to this file path. Leave blank to output to stdout.
     #[clap(short, long)]
     pub output: Option<Utf8PathBuf>,
+
+    /// The symbols flag. Set to write a symbol map of all labels and variables next to the output
+    /// file, at `<output>.sym`.
+    #[clap(short, long, requires = "output")]
+    pub symbols: bool,
+
+    /// The lines flag. Set to write a table of the source file and line of every assembled byte
+    /// next to the output file, at `<output>.lines`.
+    #[clap(short, long, requires = "output")]
+    pub lines: bool,
 }
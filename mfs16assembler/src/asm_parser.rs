@@ -1,10 +1,11 @@
-use std::{collections::HashMap, fmt::Display};
+use std::{collections::HashMap, fmt::Display, ops::Range};
 
 use color_eyre::{
     eyre::{self, eyre, OptionExt},
     owo_colors::OwoColorize,
     Section, SectionExt,
 };
+use mfs16core::SymbolMap;
 
 mod instruction_parser;
 
@@ -28,13 +29,26 @@ macro_rules! get_next_expected {
     }};
 }
 
-/// Parse a valid list of MFS-16 assembly [Token]s into machine code for the MFS-16 architecture.
+/// Everything produced by [parse].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseOutput {
+    /// The machine code for the MFS-16 architecture.
+    pub machine_code: Vec<u8>,
+    /// The labels and variables defined by the source.
+    pub symbols: SymbolMap,
+    /// The addresses of the bytes of each statement, paired with the offset of the statement
+    /// within the source.
+    pub statements: Vec<(Range<u32>, usize)>,
+}
+
+/// Parse a valid list of MFS-16 assembly [Token]s into machine code for the MFS-16 architecture,
+/// along with the labels, variables and statement locations needed to debug it.
 pub fn parse(
     tokens: Vec<Token>,
     data: &str,
     bytes_offset: usize,
     debug: bool,
-) -> eyre::Result<Vec<u8>> {
+) -> eyre::Result<ParseOutput> {
     let mut parser = Parser::new(tokens, data, bytes_offset, true, debug);
     // First pass to get labels
     while parser.parse_next()?.is_some() {}
@@ -49,10 +63,22 @@ pub fn parse(
     parser.substitute_labels();
 
     let mut output_bytes: Vec<u8> = Vec::new();
-    while let Some(bytes) = parser.parse_next()? {
+    let mut statements = Vec::new();
+    while let Some(source_offset) = parser.current_index() {
+        let start = parser.bytes_parsed as u32;
+        let Some(bytes) = parser.parse_next()? else {
+            break;
+        };
+        if !bytes.is_empty() {
+            statements.push((start..(parser.bytes_parsed as u32), source_offset));
+        }
         output_bytes.extend(bytes);
     }
-    Ok(output_bytes)
+    Ok(ParseOutput {
+        machine_code: output_bytes,
+        symbols: parser.symbol_map(),
+        statements,
+    })
 }
 
 /// MFS-16 parser. Parses a list of [Token]s into machine code for the MFS-16 architecture.
@@ -111,6 +137,27 @@ impl<'a> Parser<'a> {
         }
     }
 
+    /// Collect the [Label]s and final variable values of this [Parser] into a [SymbolMap].
+    fn symbol_map(&self) -> SymbolMap {
+        let mut symbols = SymbolMap::default();
+        let mut labels: Vec<(&String, &u32)> = self
+            .labels
+            .iter()
+            .filter_map(|(name, address)| Some((name, address.as_ref()?)))
+            .collect();
+        labels.sort();
+        for (name, address) in labels {
+            symbols.add_label(name.as_str(), *address);
+        }
+        let mut variables: Vec<(&String, &Variable)> = self.variables.iter().collect();
+        variables.sort();
+        for (name, value) in variables {
+            symbols.add_variable(name.as_str(), value);
+        }
+        symbols.set_end(self.bytes_parsed as u32);
+        symbols
+    }
+
     /// Parse the next statement. Return the bytes parsed from the statement, or [Option::None] if
     /// the end of the list of [Token]s has been reached.
     fn parse_next(&mut self) -> eyre::Result<Option<Vec<u8>>> {
@@ -17,7 +17,7 @@ macro_rules! parser_test {
         #[test]
         fn $test_name() {
             let tokens = lex($data).unwrap();
-            let machine_code = parse(tokens, $data, 0, true).unwrap();
+            let machine_code = parse(tokens, $data, 0, true).unwrap().machine_code;
             assert_eq!(machine_code, $expected);
         }
     };
@@ -88,7 +88,7 @@ parser_test!(
     FULL: abslabelzero,
     "// abslabelzero test.\n0:d:"
     =>
-    vec![]
+    Vec::<u8>::new()
 );
 parser_test!(
     FULL FAIL: abslabeltoosmall,
@@ -129,6 +129,35 @@ parser_test!(
     "jnz loop;"
 );
 
+#[test]
+fn symbolmap() {
+    let data =
+        "my_num = 0:w;\nloop:\npss L0;\njpz is_zero;\nmy_num = 2:w;\n0x20:d:\nis_zero:\nhalt;";
+    let tokens = lex(data).unwrap();
+    let symbols = parse(tokens, data, 0, true).unwrap().symbols;
+    assert_eq!(
+        symbols.labels(),
+        &[
+            (0x00, String::from("loop")),
+            (0x20, String::from("is_zero"))
+        ]
+    );
+    assert_eq!(
+        symbols.variables(),
+        &[(String::from("my_num"), String::from("0x0002:w"))]
+    );
+    assert_eq!(symbols.format_addr(0x04), "loop+0x4");
+    assert_eq!(symbols.format_addr(0x22), "0x00000022");
+}
+
+#[test]
+fn statements() {
+    let data = "inc B;\nloop: [0x01, 0x02]\n  x = 1;\n0x8:d:\njp loop;";
+    let tokens = lex(data).unwrap();
+    let statements = parse(tokens, data, 0, true).unwrap().statements;
+    assert_eq!(statements, [(0..2, 0), (2..4, 13), (4..8, 35), (8..14, 42)]);
+}
+
 parser_test!(varbyteassign, "my_byte = 0xFE:b;" => "my_byte", &Variable::Byte(0xFE));
 parser_test!(varwordassign, "my_word = 0xFE:w;" => "my_word", &Variable::Word(0x00FE));
 parser_test!(vardwordassign, "my_dword = 0o0123_4567:d;" => "my_dword", &Variable::DWord(0o0123_4567));
@@ -2,11 +2,13 @@
 use std::{
     fs::{File, OpenOptions},
     io::{self, Read, Write},
+    ops::Range,
 };
 
 use camino::Utf8Path;
 use clap::Parser;
 use color_eyre::eyre::{self, eyre};
+use mfs16core::{LineTable, SymbolMap};
 
 mod arg_parser;
 mod asm_lexer;
@@ -25,19 +27,39 @@ fn main() -> eyre::Result<()> {
     }
 
     let mut files_contents = String::new();
+    // The offset of the start of each file within the concatenated contents
+    let mut file_starts: Vec<(&Utf8Path, usize)> = Vec::with_capacity(args.files.len());
 
-    for path in args.files {
-        files_contents.push_str(&read_file(&path)?);
+    for path in &args.files {
+        file_starts.push((path, files_contents.len()));
+        files_contents.push_str(&read_file(path)?);
     }
 
     let tokens = lex(&files_contents)?;
 
-    let machine_code: Vec<u8> = parse(tokens, &files_contents, 0, args.debug)?;
+    let output = parse(tokens, &files_contents, 0, args.debug)?;
 
     if let Some(output_path) = &args.output {
-        file_output(output_path, machine_code, args.replace)?;
+        file_output(output_path, output.machine_code, args.replace)?;
+        if args.symbols {
+            debug_info_output(
+                &SymbolMap::path_for(output_path),
+                output.symbols.to_string(),
+                "symbol map",
+                args.replace,
+            )?;
+        }
+        if args.lines {
+            let line_table = line_table(&output.statements, &files_contents, &file_starts);
+            debug_info_output(
+                &LineTable::path_for(output_path),
+                line_table.to_string(),
+                "line table",
+                args.replace,
+            )?;
+        }
     } else {
-        stdout_output(machine_code)?;
+        stdout_output(output.machine_code)?;
     }
 
     Ok(())
@@ -59,6 +81,46 @@ fn file_output(path: &Utf8Path, machine_code: Vec<u8>, replace: bool) -> eyre::R
     Ok(())
 }
 
+fn debug_info_output(
+    path: &Utf8Path,
+    contents: String,
+    description: &str,
+    replace: bool,
+) -> eyre::Result<()> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create(replace)
+        .truncate(replace)
+        .create_new(!replace)
+        .open(path)?;
+    file.write_all(contents.as_bytes())?;
+    println!("Wrote {} to `{}`.", description, path);
+    Ok(())
+}
+
+/// Build a [LineTable] from the statement locations of the concatenated contents of the given
+/// files.
+fn line_table(
+    statements: &[(Range<u32>, usize)],
+    files_contents: &str,
+    file_starts: &[(&Utf8Path, usize)],
+) -> LineTable {
+    let newlines: Vec<usize> = files_contents
+        .match_indices('\n')
+        .map(|(index, _)| index)
+        .collect();
+    let line_of = |offset: usize| newlines.partition_point(|&newline| newline < offset);
+
+    let mut line_table = LineTable::default();
+    for (addrs, offset) in statements {
+        let (path, file_start) =
+            file_starts[file_starts.partition_point(|(_, start)| start <= offset) - 1];
+        let line = line_of(*offset) - line_of(file_start) + 1;
+        line_table.add(addrs.clone(), path.as_str(), line as u32);
+    }
+    line_table
+}
+
 fn stdout_output(machine_code: Vec<u8>) -> eyre::Result<()> {
     let mut stdout = io::stdout().lock();
     stdout.write_all(&machine_code)?;
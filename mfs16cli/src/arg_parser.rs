@@ -1,6 +1,10 @@
 use camino::Utf8PathBuf;
 use clap::Parser;
 
+use mfs16core::{TraceRange, FRAME_CYCLES};
+
+use crate::terminal::ColourMode;
+
 /// The CLI parser.
 #[derive(Parser, Debug)]
 #[command(name = "mfs16")]
@@ -13,4 +17,64 @@ pub struct Cli {
     /// The debug flag. Set to print debug messages.
     #[clap(short, long)]
     pub debug: bool,
+
+    /// Replay the inputs recorded in this file.
+    #[clap(short, long)]
+    pub replay: Option<Utf8PathBuf>,
+
+    /// Stop after this many cycles.
+    #[clap(short, long)]
+    pub cycles: Option<u128>,
+
+    /// Record every frame to this file. The format is chosen by the extension: .gif, .y4m or .ppm.
+    #[clap(short, long)]
+    pub video: Option<Utf8PathBuf>,
+
+    /// Write a record of every executed instruction to this file. The format is chosen by the
+    /// extension: .jsonl or .csv.
+    #[clap(long, conflicts_with = "terminal")]
+    pub trace: Option<Utf8PathBuf>,
+
+    /// Only trace the instructions in this range of addresses, e.g. `0x100..0x200`. Can be given
+    /// more than once.
+    #[clap(long, value_name = "START..END", requires = "trace")]
+    pub trace_range: Vec<TraceRange>,
+
+    /// Draw the screen in the terminal and forward key presses to the computer. Press Ctrl+C to
+    /// quit.
+    #[clap(short, long)]
+    pub terminal: bool,
+
+    /// Run the computer in the interactive debugger. Type `help` at the prompt for a list of
+    /// commands.
+    #[clap(short = 'g', long = "debugger", conflicts_with = "terminal")]
+    pub debugger: bool,
+
+    /// Wait for GDB to connect on this port of localhost, then let it control the computer.
+    #[clap(long, value_name = "PORT", conflicts_with_all = ["terminal", "debugger"])]
+    pub gdb: Option<u16>,
+
+    /// Keep this many snapshots of the computer, so that the debugger or GDB can run it backwards.
+    /// Each snapshot takes about 17 MiB.
+    #[clap(long, value_name = "SNAPSHOTS", conflicts_with = "terminal")]
+    pub rewind: Option<usize>,
+
+    /// The number of cycles between snapshots. Running backwards re-executes up to this many
+    /// cycles from the nearest snapshot. Defaults to one frame.
+    #[clap(long, value_name = "CYCLES", default_value_t = FRAME_CYCLES as u128, requires = "rewind")]
+    pub rewind_interval: u128,
+
+    /// Label addresses in the debugger using this symbol map. Defaults to `<bin>.sym` if it
+    /// exists.
+    #[clap(short = 's', long = "symbols")]
+    pub symbols: Option<Utf8PathBuf>,
+
+    /// Show source lines in the debugger using this line table. Defaults to `<bin>.lines` if it
+    /// exists.
+    #[clap(short = 'l', long = "lines")]
+    pub lines: Option<Utf8PathBuf>,
+
+    /// The kind of colours used to draw the screen in the terminal.
+    #[clap(long, value_enum, default_value_t)]
+    pub colour_mode: ColourMode,
 }
@@ -0,0 +1,953 @@
+//! Interactive debugger which runs a [Computer] one instruction at a time.
+use std::io::{self, BufRead, Write};
+
+use color_eyre::eyre::{self, eyre};
+use mfs16core::{
+    AccessKind, Computer, Instruction, Interrupt, LineTable, MemAccess, SymbolMap, WatchKind,
+    Watchpoint,
+};
+
+use crate::runner::{decode, peek, poke, Runner};
+
+use Instruction::*;
+
+const HELP: &str = "\
+Commands:
+  s, step [n]               Execute the next n instructions. (Default: 1)
+  n, next                   Execute the next instruction, stepping over calls.
+  c, continue               Run until a breakpoint is hit or the computer stops.
+  rs, rstep [n]             Step back n instructions. (Default: 1)
+  rc, rcontinue             Run backwards until a breakpoint or watchpoint is hit, e.g. to the
+                            last write to a watched address.
+  rf, rframe [n]            Run backwards to just before the nth last frame was presented.
+                            (Default: 1)
+  b, break <addr>           Set a breakpoint at an address or label, e.g. 0x1234 or main+0x10.
+  d, delete [addr]          Delete the breakpoint at an address, or all breakpoints.
+  wa, watch <addr> [n] [kind]
+                            Stop after n bytes of memory at an address are accessed. The kind
+                            is read, write, change or access. (Default: 1 byte, write)
+  uw, unwatch [addr]        Delete the watchpoints at an address, or all watchpoints.
+  bl, breakpoints           List the breakpoints and watchpoints.
+  r, regs                   Print the registers and flags.
+  x, examine <addr> [n]     Print n bytes of memory starting at an address. (Default: 64)
+  w, write <addr> <byte>... Write bytes to memory starting at an address.
+  l, list [addr] [n]        Disassemble n instructions around the PC or from an address.
+                            (Default: 10)
+  i, interrupts             Show the interrupt registers.
+  h, help                   Print this message.
+  q, quit                   Quit the debugger.
+An empty line repeats the last command.";
+
+/// The number of bytes printed per line by the examine command.
+const EXAMINE_BYTES_PER_LINE: u32 = 16;
+/// The furthest a label can be behind the PC for the list command to disassemble from it.
+const LIST_MAX_LOOKBEHIND: u32 = 0x40;
+
+/// Run the interactive debugger on the given [Computer], reading commands from stdin.
+pub fn run(
+    computer: &mut Computer,
+    runner: Runner,
+    symbols: SymbolMap,
+    lines: LineTable,
+) -> eyre::Result<()> {
+    let mut debugger = Debugger {
+        runner,
+        breakpoints: Vec::new(),
+        watchpoints: Vec::new(),
+        symbols,
+        lines,
+        last_command: String::new(),
+    };
+    let mut stdout = io::stdout();
+
+    writeln!(stdout, "Type `help` for a list of commands.")?;
+    debugger.print_location(computer, &mut stdout)?;
+    let mut lines = io::stdin().lock().lines();
+    loop {
+        write!(stdout, "(mfs16) ")?;
+        stdout.flush()?;
+        let Some(line) = lines.next() else {
+            // Quit on EOF
+            writeln!(stdout)?;
+            return Ok(());
+        };
+        if debugger.execute(computer, &line?, &mut stdout)? {
+            return Ok(());
+        }
+    }
+}
+
+/// The state of the interactive debugger.
+struct Debugger<'a> {
+    /// Executes the instructions.
+    runner: Runner<'a>,
+    /// The addresses to stop at.
+    breakpoints: Vec<u32>,
+    /// The memory accesses to stop after.
+    watchpoints: Vec<Watchpoint>,
+    /// The labels of the program.
+    symbols: SymbolMap,
+    /// The source line of every byte of the program.
+    lines: LineTable,
+    /// The last non-empty command, repeated when an empty line is entered.
+    last_command: String,
+}
+impl Debugger<'_> {
+    /// Execute a single command, returning `true` iff the debugger should quit. Invalid commands
+    /// are reported to `out` rather than returned as errors.
+    fn execute<W: Write>(
+        &mut self,
+        computer: &mut Computer,
+        line: &str,
+        out: &mut W,
+    ) -> io::Result<bool> {
+        let line = match line.trim() {
+            "" => self.last_command.clone(),
+            line => {
+                self.last_command = line.to_owned();
+                line.to_owned()
+            }
+        };
+        let mut words = line.split_whitespace();
+        let Some(command) = words.next() else {
+            return Ok(false);
+        };
+        let args: Vec<&str> = words.collect();
+
+        let result = match command {
+            "s" | "step" => self.step(computer, &args, out),
+            "n" | "next" => self.step_over(computer, out),
+            "c" | "continue" => self.run_until(computer, out, |_| false),
+            "rs" | "rstep" => self.step_back(computer, &args, out),
+            "rc" | "rcontinue" => self.continue_back(computer, out),
+            "rf" | "rframe" => self.frame_back(computer, &args, out),
+            "b" | "break" => self.set_breakpoint(&args, out),
+            "d" | "delete" => self.delete_breakpoint(&args, out),
+            "wa" | "watch" => self.set_watchpoint(computer, &args, out),
+            "uw" | "unwatch" => self.delete_watchpoint(computer, &args, out),
+            "bl" | "breakpoints" => self.list_breakpoints(out),
+            "r" | "regs" => self.print_regs(computer, out),
+            "x" | "examine" => self.examine(computer, &args, out),
+            "w" | "write" => self.write(computer, &args),
+            "l" | "list" => self.list(computer, &args, out),
+            "i" | "interrupts" => self.print_interrupts(computer, out),
+            "h" | "help" => writeln!(out, "{HELP}").map_err(eyre::Report::from),
+            "q" | "quit" => return Ok(true),
+            _ => Err(eyre!(
+                "Unknown command `{command}`. Type `help` for a list of commands."
+            )),
+        };
+
+        if let Err(e) = result {
+            writeln!(out, "{e}")?;
+        }
+        Ok(false)
+    }
+
+    // ------- EXECUTION -------
+
+    /// Execute instructions until the given condition is met after an instruction, a breakpoint
+    /// is hit or the computer can't run any further. At least one instruction is always executed.
+    fn run_until<W: Write, F: FnMut(&Computer) -> bool>(
+        &mut self,
+        computer: &mut Computer,
+        out: &mut W,
+        mut is_done: F,
+    ) -> eyre::Result<()> {
+        loop {
+            if !self.runner.step_instruction(computer)? {
+                if computer.cpu.is_stopped {
+                    writeln!(out, "The computer has stopped.")?;
+                } else {
+                    writeln!(out, "Reached the cycle limit.")?;
+                }
+                break;
+            }
+            if let Some((watchpoint, access)) =
+                triggered_watchpoint(&self.watchpoints, self.runner.accesses())
+            {
+                writeln!(
+                    out,
+                    "Hit watchpoint on {}: {}.",
+                    self.format_range(watchpoint.start, watchpoint.end),
+                    describe_access(&access)
+                )?;
+                break;
+            }
+            let pc = computer.cpu.pc.address();
+            if self.breakpoints.contains(&pc) {
+                writeln!(out, "Hit breakpoint at {}.", self.symbols.format_addr(pc))?;
+                break;
+            }
+            if is_done(computer) {
+                break;
+            }
+        }
+        self.print_location(computer, out)
+    }
+
+    /// Execute the given number of instructions.
+    fn step<W: Write>(
+        &mut self,
+        computer: &mut Computer,
+        args: &[&str],
+        out: &mut W,
+    ) -> eyre::Result<()> {
+        let mut remaining = args.first().map(|n| parse_num(n)).transpose()?.unwrap_or(1);
+        self.run_until(computer, out, |_| {
+            remaining = remaining.saturating_sub(1);
+            remaining == 0
+        })
+    }
+
+    /// Execute the next instruction. If it's a call, keep going until it returns.
+    fn step_over<W: Write>(&mut self, computer: &mut Computer, out: &mut W) -> eyre::Result<()> {
+        let pc = computer.cpu.pc.address();
+        match decode(computer, pc) {
+            Some(decoded) if is_call(decoded.instruction) => {
+                let return_addr = pc + decoded.size() as u32;
+                let sp = computer.cpu.sp.address();
+                // Also check the stack pointer so recursive calls don't stop too early
+                self.run_until(computer, out, |computer| {
+                    computer.cpu.pc.address() == return_addr && computer.cpu.sp.address() >= sp
+                })
+            }
+            _ => self.run_until(computer, out, |_| true),
+        }
+    }
+
+    // ------- REVERSE EXECUTION -------
+
+    /// Step back the given number of instructions.
+    fn step_back<W: Write>(
+        &mut self,
+        computer: &mut Computer,
+        args: &[&str],
+        out: &mut W,
+    ) -> eyre::Result<()> {
+        let count = args.first().map(|n| parse_num(n)).transpose()?.unwrap_or(1);
+        let found = self
+            .runner
+            .rewind(computer, count as usize, |_, _, _| Some(()))?;
+        if found.is_none() {
+            writeln!(out, "Reached the oldest snapshot.")?;
+        }
+        self.print_location(computer, out)
+    }
+
+    /// Run backwards to the start of the last instruction which hit a breakpoint or watchpoint.
+    fn continue_back<W: Write>(
+        &mut self,
+        computer: &mut Computer,
+        out: &mut W,
+    ) -> eyre::Result<()> {
+        let (breakpoints, watchpoints) = (&self.breakpoints, &self.watchpoints);
+        // Remember which watchpoint was hit, if it wasn't a breakpoint
+        let found = self.runner.rewind(computer, 1, |computer, accesses, _| {
+            let hit_watchpoint = triggered_watchpoint(watchpoints, accesses);
+            let hit_breakpoint = breakpoints.contains(&computer.cpu.instr_addr);
+            (hit_watchpoint.is_some() || hit_breakpoint).then_some(hit_watchpoint)
+        })?;
+        match found {
+            Some(Some((watchpoint, access))) => writeln!(
+                out,
+                "Hit watchpoint on {}: {}.",
+                self.format_range(watchpoint.start, watchpoint.end),
+                describe_access(&access)
+            )?,
+            Some(None) => {
+                let pc = computer.cpu.pc.address();
+                writeln!(out, "Hit breakpoint at {}.", self.symbols.format_addr(pc))?;
+            }
+            None => writeln!(out, "Reached the oldest snapshot.")?,
+        }
+        self.print_location(computer, out)
+    }
+
+    /// Run backwards to just before the given number of frames were presented.
+    fn frame_back<W: Write>(
+        &mut self,
+        computer: &mut Computer,
+        args: &[&str],
+        out: &mut W,
+    ) -> eyre::Result<()> {
+        let count = args.first().map(|n| parse_num(n)).transpose()?.unwrap_or(1);
+        let found = self
+            .runner
+            .rewind(computer, count as usize, |_, _, frame_presented| {
+                frame_presented.then_some(())
+            })?;
+        if found.is_none() {
+            writeln!(out, "Reached the oldest snapshot.")?;
+        }
+        self.print_location(computer, out)
+    }
+
+    // ------- BREAKPOINTS -------
+
+    fn set_breakpoint<W: Write>(&mut self, args: &[&str], out: &mut W) -> eyre::Result<()> {
+        let address =
+            self.parse_addr(args.first().ok_or_else(|| eyre!("Expected an address."))?)?;
+        if !self.breakpoints.contains(&address) {
+            self.breakpoints.push(address);
+        }
+        writeln!(
+            out,
+            "Breakpoint set at {address:#010X}{}.",
+            self.label_of(address)
+        )?;
+        Ok(())
+    }
+
+    fn delete_breakpoint<W: Write>(&mut self, args: &[&str], out: &mut W) -> eyre::Result<()> {
+        match args.first() {
+            Some(arg) => {
+                let address = self.parse_addr(arg)?;
+                let len = self.breakpoints.len();
+                self.breakpoints.retain(|&bp| bp != address);
+                if self.breakpoints.len() == len {
+                    return Err(eyre!("No breakpoint at {address:#010X}."));
+                }
+            }
+            None => self.breakpoints.clear(),
+        }
+        self.list_breakpoints(out)
+    }
+
+    fn list_breakpoints<W: Write>(&self, out: &mut W) -> eyre::Result<()> {
+        if self.breakpoints.is_empty() {
+            writeln!(out, "No breakpoints.")?;
+        }
+        for &address in &self.breakpoints {
+            writeln!(out, "{address:#010X}{}", self.label_of(address))?;
+        }
+        for watchpoint in &self.watchpoints {
+            writeln!(
+                out,
+                "Watch {} {}",
+                kind_name(watchpoint.kind),
+                self.format_range(watchpoint.start, watchpoint.end)
+            )?;
+        }
+        Ok(())
+    }
+
+    // ------- WATCHPOINTS -------
+
+    fn set_watchpoint<W: Write>(
+        &mut self,
+        computer: &mut Computer,
+        args: &[&str],
+        out: &mut W,
+    ) -> eyre::Result<()> {
+        let start = self.parse_addr(args.first().ok_or_else(|| eyre!("Expected an address."))?)?;
+        let len = args.get(1).map(|n| parse_num(n)).transpose()?.unwrap_or(1);
+        if len == 0 {
+            return Err(eyre!("Can't watch 0 bytes."));
+        }
+        let kind = match args.get(2).copied().unwrap_or("write") {
+            "r" | "read" => WatchKind::Read,
+            "w" | "write" => WatchKind::Write,
+            "c" | "change" => WatchKind::Change,
+            "a" | "access" => WatchKind::Access,
+            kind => return Err(eyre!("Unknown watchpoint kind `{kind}`.")),
+        };
+
+        let watchpoint = Watchpoint {
+            kind,
+            start,
+            end: start.saturating_add(len),
+        };
+        if self.watchpoints.is_empty() {
+            self.runner.log_accesses(computer, true);
+        }
+        if !self.watchpoints.contains(&watchpoint) {
+            self.watchpoints.push(watchpoint);
+        }
+        writeln!(
+            out,
+            "Watching {} {}.",
+            kind_name(kind),
+            self.format_range(watchpoint.start, watchpoint.end)
+        )?;
+        Ok(())
+    }
+
+    fn delete_watchpoint<W: Write>(
+        &mut self,
+        computer: &mut Computer,
+        args: &[&str],
+        out: &mut W,
+    ) -> eyre::Result<()> {
+        match args.first() {
+            Some(arg) => {
+                let address = self.parse_addr(arg)?;
+                let len = self.watchpoints.len();
+                self.watchpoints.retain(|wp| wp.start != address);
+                if self.watchpoints.len() == len {
+                    return Err(eyre!("No watchpoint at {address:#010X}."));
+                }
+            }
+            None => self.watchpoints.clear(),
+        }
+        if self.watchpoints.is_empty() {
+            self.runner.log_accesses(computer, false);
+        }
+        self.list_breakpoints(out)
+    }
+
+    // ------- INSPECTION -------
+
+    /// Print the PC, its source line and the next instruction to be executed.
+    fn print_location<W: Write>(&self, computer: &mut Computer, out: &mut W) -> eyre::Result<()> {
+        let pc = computer.cpu.pc.address();
+        self.print_instr(computer, pc, true, out)?;
+        Ok(())
+    }
+
+    /// Print the instruction at the given address on a single line.
+    fn print_instr<W: Write>(
+        &self,
+        computer: &mut Computer,
+        address: u32,
+        is_pc: bool,
+        out: &mut W,
+    ) -> io::Result<u32> {
+        let marker = if is_pc { "=>" } else { "  " };
+        let (size, asm) = match decode(computer, address) {
+            Some(decoded) => (
+                decoded.size() as u32,
+                decoded.to_string_with_symbols(&self.symbols),
+            ),
+            None => (2, String::from("(invalid)")),
+        };
+        let bytes = (address..address.saturating_add(size))
+            .map(|a| format!("{:02X}", peek(computer, a)))
+            .collect::<Vec<String>>()
+            .join(" ");
+        let source_line = self
+            .lines
+            .lookup(address)
+            .map(|source_line| format!("  // {source_line}"))
+            .unwrap_or_default();
+        writeln!(
+            out,
+            "{marker} {address:#010X}{}: {bytes:<17} {asm}{source_line}",
+            self.label_of(address)
+        )?;
+        Ok(size)
+    }
+
+    fn print_regs<W: Write>(&self, computer: &mut Computer, out: &mut W) -> eyre::Result<()> {
+        let cpu = &computer.cpu;
+        let mut status = Vec::new();
+        if cpu.is_halted {
+            status.push("halted");
+        }
+        if cpu.is_stopped {
+            status.push("stopped");
+        }
+        writeln!(
+            out,
+            "PC: {}{}\nSP: {}\n{}\nFlags: {}\nCycles: {}{}{}",
+            cpu.pc,
+            self.label_of(cpu.pc.address()),
+            cpu.sp,
+            cpu.regs,
+            cpu.flags,
+            computer.cycles,
+            if status.is_empty() { "" } else { " " },
+            status.join(", ")
+        )?;
+        Ok(())
+    }
+
+    fn print_interrupts<W: Write>(&self, computer: &mut Computer, out: &mut W) -> eyre::Result<()> {
+        writeln!(
+            out,
+            "Interrupts {}. IE: {:#010b} IF: {:#010b}",
+            if computer.cpu.interrupts_enabled {
+                "enabled"
+            } else {
+                "disabled"
+            },
+            computer.mmu.ie_register,
+            computer.mmu.interrupt_register
+        )?;
+        writeln!(out, "{:<12} IE IF", "")?;
+        for n in 0..=Interrupt::Blitter.into_byte() {
+            writeln!(
+                out,
+                "{:<12} {}  {}",
+                Interrupt::from_byte(n).to_string(),
+                (computer.mmu.ie_register >> n) & 1,
+                (computer.mmu.interrupt_register >> n) & 1
+            )?;
+        }
+        Ok(())
+    }
+
+    fn examine<W: Write>(
+        &self,
+        computer: &mut Computer,
+        args: &[&str],
+        out: &mut W,
+    ) -> eyre::Result<()> {
+        let start = self.parse_addr(args.first().ok_or_else(|| eyre!("Expected an address."))?)?;
+        let len = args.get(1).map(|n| parse_num(n)).transpose()?.unwrap_or(64);
+        for offset in (0..len).step_by(EXAMINE_BYTES_PER_LINE as usize) {
+            let line_start = start.wrapping_add(offset);
+            let line_len = (len - offset).min(EXAMINE_BYTES_PER_LINE);
+            let bytes = (0..line_len)
+                .map(|i| format!("{:02X}", peek(computer, line_start.wrapping_add(i))))
+                .collect::<Vec<String>>()
+                .join(" ");
+            writeln!(out, "{line_start:#010X}: {bytes}")?;
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, computer: &mut Computer, args: &[&str]) -> eyre::Result<()> {
+        let start = self.parse_addr(args.first().ok_or_else(|| eyre!("Expected an address."))?)?;
+        if args.len() < 2 {
+            return Err(eyre!("Expected at least one byte."));
+        }
+        let bytes = args[1..]
+            .iter()
+            .map(|arg| u8::try_from(parse_num(arg)?).map_err(|_| eyre!("`{arg}` is not a byte.")))
+            .collect::<eyre::Result<Vec<u8>>>()?;
+
+        for (i, &byte) in bytes.iter().enumerate() {
+            poke(computer, start.wrapping_add(i as u32), byte);
+        }
+        self.runner.branch(computer);
+        Ok(())
+    }
+
+    fn list<W: Write>(
+        &self,
+        computer: &mut Computer,
+        args: &[&str],
+        out: &mut W,
+    ) -> eyre::Result<()> {
+        let pc = computer.cpu.pc.address();
+        let start = args.first().map(|arg| self.parse_addr(arg)).transpose()?;
+        let count = args.get(1).map(|n| parse_num(n)).transpose()?.unwrap_or(10);
+
+        let mut address = match start {
+            Some(start) => start,
+            None => self.list_start(computer, pc, count / 2),
+        };
+        for _ in 0..count {
+            address =
+                address.wrapping_add(self.print_instr(computer, address, address == pc, out)?);
+        }
+        Ok(())
+    }
+
+    /// Find the address to disassemble from so that up to the given number of instructions before
+    /// the PC are shown. Decoding backwards is ambiguous, so this decodes forwards from the
+    /// closest label before the PC.
+    fn list_start(&self, computer: &mut Computer, pc: u32, num_before: u32) -> u32 {
+        let Some((_, offset)) = self
+            .symbols
+            .nearest(pc)
+            .filter(|&(_, offset)| offset <= LIST_MAX_LOOKBEHIND)
+        else {
+            return pc;
+        };
+
+        let mut addrs = Vec::new();
+        let mut address = pc - offset;
+        while address < pc {
+            addrs.push(address);
+            match decode(computer, address) {
+                Some(decoded) => address += decoded.size() as u32,
+                None => return pc,
+            }
+        }
+        if address != pc {
+            return pc;
+        }
+        let skip = addrs.len().saturating_sub(num_before as usize);
+        addrs.get(skip).copied().unwrap_or(pc)
+    }
+
+    /// Format the given address relative to its nearest label, e.g. ` <main+0x12>`, or return an
+    /// empty string if there is no such label.
+    fn label_of(&self, address: u32) -> String {
+        match self.symbols.nearest(address) {
+            Some(_) => format!(" <{}>", self.symbols.format_addr(address)),
+            None => String::new(),
+        }
+    }
+
+    /// Format the given range start..end of memory, e.g. `0x00800000..0x00800004`.
+    fn format_range(&self, start: u32, end: u32) -> String {
+        format!("{start:#010X}..{end:#010X}{}", self.label_of(start))
+    }
+
+    /// Parse an address, which is either a number or a label with an optional offset.
+    fn parse_addr(&self, s: &str) -> eyre::Result<u32> {
+        let (base, offset) = match s.split_once('+') {
+            Some((base, offset)) => (base, parse_num(offset)?),
+            None => (s, 0),
+        };
+        let base = match self.symbols.address_of(base) {
+            Some(address) => address,
+            None => parse_num(base).map_err(|_| eyre!("Unknown address or label `{s}`."))?,
+        };
+        Ok(base.wrapping_add(offset))
+    }
+}
+
+/// Parse a number given in decimal, or in hexadecimal or binary with a `0x` or `0b` prefix.
+fn parse_num(s: &str) -> eyre::Result<u32> {
+    let cleaned = s.replace('_', "");
+    if let Some(hex) = cleaned.strip_prefix("0x") {
+        u32::from_str_radix(hex, 16)
+    } else if let Some(bin) = cleaned.strip_prefix("0b") {
+        u32::from_str_radix(bin, 2)
+    } else {
+        cleaned.parse()
+    }
+    .map_err(|e| eyre!("Invalid number `{s}`: {e}"))
+}
+
+/// Find the first of the given watchpoints triggered by the given accesses, and the access which
+/// triggered it.
+fn triggered_watchpoint(
+    watchpoints: &[Watchpoint],
+    accesses: &[MemAccess],
+) -> Option<(Watchpoint, MemAccess)> {
+    accesses.iter().find_map(|&access| {
+        watchpoints
+            .iter()
+            .find(|wp| wp.is_triggered_by(&access))
+            .map(|&wp| (wp, access))
+    })
+}
+
+/// Describe a read or write, e.g. `write of 0x0012 to 0x00800000 (was 0x0000)`.
+fn describe_access(access: &MemAccess) -> String {
+    // Each byte takes two hex digits, plus two for the prefix
+    let digits = 2 + 2 * access.width as usize;
+    match access.kind {
+        AccessKind::Read => format!(
+            "read of {:#0digits$X} from {:#010X}",
+            access.value, access.address
+        ),
+        AccessKind::Write => {
+            let mut description = format!(
+                "write of {:#0digits$X} to {:#010X}",
+                access.value, access.address
+            );
+            if let Some(old_value) = access.old_value {
+                description.push_str(&format!(" (was {old_value:#0digits$X})"));
+            }
+            description
+        }
+    }
+}
+
+fn kind_name(kind: WatchKind) -> &'static str {
+    match kind {
+        WatchKind::Read => "reads of",
+        WatchKind::Write => "writes to",
+        WatchKind::Change => "changes to",
+        WatchKind::Access => "accesses to",
+    }
+}
+
+fn is_call(instruction: Instruction) -> bool {
+    matches!(
+        instruction,
+        CallImm32
+            | ClzImm32
+            | CnzImm32
+            | ClcImm32
+            | CncImm32
+            | CloImm32
+            | CnoImm32
+            | ClpImm32
+            | CnpImm32
+            | ClnImm32
+            | CnnImm32
+            | CallBra(_)
+            | ClzBra(_)
+            | CnzBra(_)
+            | ClcBra(_)
+            | CncBra(_)
+            | CloBra(_)
+            | CnoBra(_)
+            | ClpBra(_)
+            | CnpBra(_)
+            | ClnBra(_)
+            | CnnBra(_)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use mfs16core::{
+        test_utils::load_test_program, Reg16, RewindBuffer, FRAME_CYCLES, VBLANK_CYCLES,
+    };
+
+    use super::*;
+
+    /// Load the test program, then run the debugger commands and return their output.
+    fn run_commands(computer: &mut Computer, commands: &[&str]) -> String {
+        let mut video_recorder = None;
+        let mut tracer = None;
+        let runner = Runner::new(None, None, &mut video_recorder, &mut tracer, None);
+        run_commands_with(computer, runner, commands)
+    }
+
+    /// Load the test program, then run the debugger commands using the given [Runner] and return
+    /// their output.
+    fn run_commands_with(computer: &mut Computer, runner: Runner, commands: &[&str]) -> String {
+        let symbols = load_test_program(computer);
+        let mut debugger = Debugger {
+            runner,
+            breakpoints: Vec::new(),
+            watchpoints: Vec::new(),
+            symbols,
+            lines: LineTable::default(),
+            last_command: String::new(),
+        };
+
+        let mut out = Vec::new();
+        for command in commands {
+            debugger.execute(computer, command, &mut out).unwrap();
+        }
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_step() {
+        let mut computer = Computer::new(false);
+        let out = run_commands(&mut computer, &["step", "s", "", ""]);
+        assert_eq!(computer.cpu.pc.address(), 0x0A);
+        assert_eq!(computer.cpu.reg(Reg16::A), 0x0002);
+        assert!(out.contains("=> 0x00000010 <sub>: 30 1D             INC A;"));
+
+        let mut computer = Computer::new(false);
+        run_commands(&mut computer, &["step 3"]);
+        assert_eq!(computer.cpu.pc.address(), 0x12);
+    }
+
+    #[test]
+    fn test_next() {
+        let mut computer = Computer::new(false);
+        let out = run_commands(&mut computer, &["n", "n", "n"]);
+        assert_eq!(computer.cpu.pc.address(), 0x0C);
+        assert_eq!(computer.cpu.reg(Reg16::A), 0x0003);
+        assert!(out.contains("CALL sub;"));
+        assert!(!out.contains("<sub>:"));
+
+        // Breakpoints inside the called subroutine are still hit
+        let mut computer = Computer::new(false);
+        run_commands(&mut computer, &["b sub+2", "n", "n"]);
+        assert_eq!(computer.cpu.pc.address(), 0x12);
+    }
+
+    #[test]
+    fn test_continue() {
+        let mut computer = Computer::new(false);
+        let out = run_commands(&mut computer, &["break sub", "c"]);
+        assert!(out.contains("Breakpoint set at 0x00000010 <sub>."));
+        assert!(out.contains("Hit breakpoint at sub."));
+        assert_eq!(computer.cpu.pc.address(), 0x10);
+
+        let out = run_commands(&mut computer, &["c", "c"]);
+        assert!(out.contains("The computer has stopped."));
+        assert!(computer.cpu.is_stopped);
+
+        let out = run_commands(&mut computer, &["b 0x04", "d 0x04", "d 0x04", "bl"]);
+        assert!(out.contains("No breakpoint at 0x00000004."));
+        assert!(out.ends_with("No breakpoints.\n"));
+    }
+
+    #[test]
+    fn test_watchpoints() {
+        let mut computer = Computer::new(false);
+        // The return address is pushed to the top of RAM
+        let out = run_commands(
+            &mut computer,
+            &[
+                "wa 0xFFFFFC 4",
+                "wa 0xFFFFFE 1 read",
+                "c",
+                "c",
+                "bl",
+                "uw 0xFFFFFC",
+                "uw",
+                "c",
+            ],
+        );
+        assert!(out.contains("Watching writes to 0x00FFFFFC..0x01000000."));
+        assert!(out.contains(
+            "Hit watchpoint on 0x00FFFFFC..0x01000000: \
+             write of 0x0000000A to 0x00FFFFFC (was 0x00000000).\n\
+             => 0x00000010 <sub>:"
+        ));
+        assert!(out.contains(
+            "Hit watchpoint on 0x00FFFFFE..0x00FFFFFF: read of 0x0000000A from 0x00FFFFFC."
+        ));
+        assert!(out.contains(
+            "No breakpoints.\n\
+             Watch writes to 0x00FFFFFC..0x01000000\n\
+             Watch reads of 0x00FFFFFE..0x00FFFFFF\n\
+             No breakpoints.\n\
+             Watch reads of 0x00FFFFFE..0x00FFFFFF\n\
+             No breakpoints.\n\
+             The computer has stopped.\n"
+        ));
+        assert!(computer.mmu.access_log.is_none());
+
+        let out = run_commands(&mut computer, &["wa 0x10 0", "wa 0x10 1 sometimes"]);
+        assert_eq!(
+            out,
+            "Can't watch 0 bytes.\nUnknown watchpoint kind `sometimes`.\n"
+        );
+    }
+
+    #[test]
+    fn test_reverse() {
+        let mut computer = Computer::new(false);
+        let out = run_commands(&mut computer, &["s", "rs"]);
+        assert!(out.ends_with("Reverse execution is off. Run with `--rewind` to turn it on.\n"));
+
+        let mut computer = Computer::new(false);
+        let mut video_recorder = None;
+        let mut tracer = None;
+        let rewind = RewindBuffer::new(4, 8);
+        let runner = Runner::new(None, None, &mut video_recorder, &mut tracer, Some(rewind));
+        let out = run_commands_with(
+            &mut computer,
+            runner,
+            &[
+                "s 4",
+                "rs",
+                "rs 2",
+                "r",
+                "wa 0xFFFFFC 4",
+                "c",
+                "c",
+                "rc",
+                "uw",
+                "b sub",
+                "c",
+                "c",
+                "rc",
+                "d",
+                "rc",
+            ],
+        );
+        assert!(out.contains(
+            "=> 0x0000000A <main+0xA>: 30 1D             INC A;\n\
+             => 0x00000012 <sub+0x2>: 13 81             RET;\n\
+             => 0x00000004 <main+0x4>:"
+        ));
+        assert!(out.contains("A:0x0001 B:0x0000"));
+        assert!(out.contains(
+            "The computer has stopped.\n\
+             => 0x0000000E <main+0xE>: 00 00             NOP;\n\
+             Hit watchpoint on 0x00FFFFFC..0x01000000: \
+             write of 0x0000000A to 0x00FFFFFC (was 0x00000000).\n\
+             => 0x00000004 <main+0x4>:"
+        ));
+        assert!(out.contains(
+            "The computer has stopped.\n\
+             => 0x0000000E <main+0xE>: 00 00             NOP;\n\
+             Hit breakpoint at sub.\n\
+             => 0x00000010 <sub>:"
+        ));
+        assert!(out.ends_with(
+            "No breakpoints.\n\
+             Reached the oldest snapshot.\n\
+             => 0x00000000 <main>: 00 03 01 00       LD A,0x0001:w;\n"
+        ));
+        assert_eq!(computer.cpu.reg(Reg16::A), 0x0000);
+        assert!(computer.mmu.access_log.is_none());
+    }
+
+    #[test]
+    fn test_reverse_frames() {
+        // Loop forever instead of stopping
+        let jump: Vec<String> = JpImm32
+            .into_opcode()
+            .to_le_bytes()
+            .into_iter()
+            .chain([0x0A, 0, 0, 0])
+            .map(|byte| byte.to_string())
+            .collect();
+        let frame_ready = (FRAME_CYCLES - VBLANK_CYCLES) as u128;
+
+        let mut computer = Computer::new(false);
+        let mut video_recorder = None;
+        let mut tracer = None;
+        let rewind = RewindBuffer::new(8, FRAME_CYCLES as u128 / 2);
+        let cycles = Some(frame_ready + FRAME_CYCLES as u128 + 100);
+        let runner = Runner::new(None, cycles, &mut video_recorder, &mut tracer, Some(rewind));
+        let out = run_commands_with(
+            &mut computer,
+            runner,
+            &[&format!("w 0x0A {}", jump.join(" ")), "c", "rf", "r", "rf"],
+        );
+        assert!(out.contains("Reached the cycle limit."));
+        let cycles_after_rf: Vec<u128> = out
+            .lines()
+            .filter_map(|line| line.strip_prefix("Cycles: "))
+            .map(|cycles| cycles.parse().unwrap())
+            .collect();
+        // The frames are presented on the cycle after the last visible line
+        let second_frame = frame_ready + FRAME_CYCLES as u128;
+        assert!((second_frame - 10..=second_frame).contains(&cycles_after_rf[0]));
+        assert!((frame_ready - 10..=frame_ready).contains(&computer.cycles));
+    }
+
+    #[test]
+    fn test_memory() {
+        let mut computer = Computer::new(false);
+        let out = run_commands(
+            &mut computer,
+            &[
+                "w main+0x1E 0xAB 0xCD",
+                "x 0x10 0x11",
+                "w 0x80_0000 1 256",
+                "x",
+            ],
+        );
+        assert_eq!(
+            out,
+            "0x00000010: 30 1D 13 81 00 00 00 00 00 00 00 00 00 00 AB CD\n\
+             0x00000020: 00\n\
+             `256` is not a byte.\n\
+             Expected an address.\n"
+        );
+        assert_eq!(computer.mmu.err_reg, 0);
+
+        // Examining the text input register doesn't consume the queue
+        computer.text_input("hi");
+        let out = run_commands(&mut computer, &["x 0xFFFFFF8F 1", "x 0xFFFFFF8F 1"]);
+        assert_eq!(out, "0xFFFFFF8F: 68\n0xFFFFFF8F: 68\n");
+        assert_eq!(computer.mmu.text_input_queue.len(), 2);
+
+        // Examining past the end of memory wraps around
+        let out = run_commands(&mut computer, &["x 0xFFFFFFF8 20"]);
+        assert_eq!(
+            out,
+            "0xFFFFFFF8: 00 00 00 00 00 00 00 02 00 03 01 00 00 81 10 00\n\
+             0x00000008: 00 00 30 1D\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_num() {
+        assert_eq!(parse_num("0x1234_5678").unwrap(), 0x1234_5678);
+        assert_eq!(parse_num("0b101").unwrap(), 5);
+        assert_eq!(parse_num("42").unwrap(), 42);
+        assert!(parse_num("main").is_err());
+    }
+}
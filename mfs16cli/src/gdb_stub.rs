@@ -0,0 +1,833 @@
+//! GDB remote serial protocol server, so that GDB and the front ends built on it can debug
+//! programs running on a [Computer].
+use std::{
+    io::{self, ErrorKind, Read, Write},
+    net::{TcpListener, TcpStream},
+};
+
+use color_eyre::eyre;
+use mfs16core::{Addr, Computer, Flag, MemAccess, Reg16, Reg32, WatchKind, Watchpoint};
+
+use crate::runner::{peek, poke, Runner};
+
+/// Describes the registers to GDB.
+const TARGET_XML: &str = include_str!("gdb_stub/target.xml");
+/// The largest packet GDB may send, in bytes.
+const MAX_PACKET_SIZE: usize = 0x1000;
+/// The number of instructions executed between checks for an interrupt from GDB.
+const POLL_INTERVAL: u32 = 0x4000;
+/// Sent by GDB to interrupt the running computer.
+const INTERRUPT_BYTE: u8 = 0x03;
+
+/// The error reply to a packet which couldn't be parsed.
+const ERR_MALFORMED: &str = "E01";
+/// The error reply to a packet with values the computer can't take.
+const ERR_INVALID: &str = "E02";
+
+/// The registers in the order GDB numbers them. Must match [TARGET_XML].
+const REGISTERS: [GdbReg; 13] = [
+    GdbReg::Reg(Reg16::A),
+    GdbReg::Reg(Reg16::B),
+    GdbReg::Reg(Reg16::C),
+    GdbReg::Reg(Reg16::D),
+    GdbReg::Reg(Reg16::E),
+    GdbReg::Reg(Reg16::H),
+    GdbReg::Reg(Reg16::L),
+    GdbReg::Breg(Reg32::BC),
+    GdbReg::Breg(Reg32::DE),
+    GdbReg::Breg(Reg32::HL),
+    GdbReg::Sp,
+    GdbReg::Pc,
+    GdbReg::Flags,
+];
+
+/// The CPU flags in the order of their bits in the flags register.
+const FLAGS: [Flag; 5] = [
+    Flag::Zero,
+    Flag::Carry,
+    Flag::Overflow,
+    Flag::Parity,
+    Flag::Negative,
+];
+
+/// Wait for GDB to connect on the given port of localhost, then serve its requests until it
+/// detaches or kills the computer.
+pub fn run(computer: &mut Computer, runner: Runner, port: u16) -> eyre::Result<()> {
+    let listener = TcpListener::bind(("127.0.0.1", port))?;
+    println!(
+        "Waiting for GDB to connect to {}...",
+        listener.local_addr()?
+    );
+    let (stream, address) = listener.accept()?;
+    println!("GDB connected from {address}.");
+    stream.set_nodelay(true)?;
+
+    let mut connection = Connection {
+        stream,
+        no_ack: false,
+    };
+    let mut stub = GdbStub::new(runner);
+    while let Some(packet) = connection.read_packet()? {
+        match stub.handle(computer, &packet, &mut || connection.interrupted())? {
+            Reply::Packet(reply) => connection.send(&reply)?,
+            Reply::Detach => {
+                connection.send("OK")?;
+                break;
+            }
+            Reply::Kill => break,
+        }
+        if packet == "QStartNoAckMode" {
+            connection.no_ack = true;
+        }
+    }
+    println!("GDB disconnected.");
+    Ok(())
+}
+
+/// What to do after handling a packet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Reply {
+    /// Send the given packet.
+    Packet(String),
+    /// Acknowledge, then close the connection.
+    Detach,
+    /// Close the connection without replying.
+    Kill,
+}
+impl<S: Into<String>> From<S> for Reply {
+    fn from(value: S) -> Self {
+        Self::Packet(value.into())
+    }
+}
+
+/// A register as seen by GDB.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GdbReg {
+    Reg(Reg16),
+    Breg(Reg32),
+    Sp,
+    Pc,
+    Flags,
+}
+impl GdbReg {
+    /// The size of the register, in bytes.
+    fn size(&self) -> usize {
+        match self {
+            Self::Reg(_) | Self::Flags => 2,
+            Self::Breg(_) | Self::Sp | Self::Pc => 4,
+        }
+    }
+
+    fn read(&self, computer: &Computer) -> u32 {
+        let cpu = &computer.cpu;
+        match *self {
+            Self::Reg(reg) => cpu.regs.reg(reg) as u32,
+            Self::Breg(breg) => cpu.regs.breg(breg),
+            Self::Sp => cpu.sp.address(),
+            Self::Pc => cpu.pc.address(),
+            Self::Flags => FLAGS
+                .iter()
+                .enumerate()
+                .map(|(bit, &flag)| (cpu.flags.flag(flag) as u32) << bit)
+                .sum(),
+        }
+    }
+
+    /// Write to the register, returning `false` if the value is out of range.
+    fn write(&self, computer: &mut Computer, value: u32) -> bool {
+        let cpu = &mut computer.cpu;
+        match *self {
+            Self::Reg(reg) => cpu.regs.set_reg(reg, value as u16),
+            Self::Breg(breg) => cpu.regs.set_breg(breg, value),
+            // The stack pointer can't leave RAM
+            Self::Sp => {
+                let (start, end) = (cpu.sp.range_start(), cpu.sp.range_end());
+                if !(start..=end).contains(&value) {
+                    return false;
+                }
+                cpu.sp = Addr::new(start as usize, (end - start) as usize, value);
+            }
+            Self::Pc => cpu.pc = Addr::new_default_range(value),
+            Self::Flags => {
+                for (bit, &flag) in FLAGS.iter().enumerate() {
+                    cpu.flags.change_flag(flag, (value >> bit) & 1 == 1);
+                }
+            }
+        }
+        true
+    }
+
+    /// Encode the register value as little-endian hex digits.
+    fn encode(&self, computer: &Computer) -> String {
+        encode_hex(&self.read(computer).to_le_bytes()[..self.size()])
+    }
+
+    /// Decode little-endian hex digits and write them to the register.
+    fn decode(&self, computer: &mut Computer, hex: &str) -> Option<bool> {
+        let bytes = decode_hex(hex)?;
+        if bytes.len() != self.size() {
+            return None;
+        }
+        let value = bytes
+            .iter()
+            .rev()
+            .fold(0, |value, &byte| (value << 8) | byte as u32);
+        Some(self.write(computer, value))
+    }
+}
+
+/// Runs the computer on behalf of GDB.
+struct GdbStub<'a> {
+    /// Executes the instructions.
+    runner: Runner<'a>,
+    /// The addresses to stop at.
+    breakpoints: Vec<u32>,
+    /// The memory to stop on accesses to.
+    watchpoints: Vec<Watchpoint>,
+    /// Whether GDB understands breakpoint stop replies.
+    swbreak: bool,
+    /// Why the computer last stopped.
+    stop_reply: String,
+}
+impl<'a> GdbStub<'a> {
+    fn new(runner: Runner<'a>) -> Self {
+        Self {
+            runner,
+            breakpoints: Vec::new(),
+            watchpoints: Vec::new(),
+            swbreak: false,
+            stop_reply: String::from("S05"),
+        }
+    }
+
+    /// Handle a single packet from GDB. `interrupted` is polled while the computer runs.
+    fn handle(
+        &mut self,
+        computer: &mut Computer,
+        packet: &str,
+        interrupted: &mut dyn FnMut() -> bool,
+    ) -> eyre::Result<Reply> {
+        let Some(command) = packet.chars().next() else {
+            return Ok("".into());
+        };
+        let args = &packet[command.len_utf8()..];
+
+        let reply = match command {
+            '?' => self.stop_reply.as_str().into(),
+            'g' => REGISTERS
+                .iter()
+                .map(|reg| reg.encode(computer))
+                .collect::<String>()
+                .into(),
+            'G' => self.write(computer, args, write_registers).into(),
+            'p' => match parse_hex(args).and_then(|n| REGISTERS.get(n as usize)) {
+                Some(reg) => reg.encode(computer).into(),
+                None => ERR_MALFORMED.into(),
+            },
+            'P' => self.write(computer, args, write_register).into(),
+            'm' => read_memory(computer, args).into(),
+            'M' => self.write(computer, args, write_memory).into(),
+            'c' | 's' => {
+                if !args.is_empty() {
+                    match parse_hex(args) {
+                        Some(address) => {
+                            computer.cpu.pc = Addr::new_default_range(address);
+                            self.runner.branch(computer);
+                        }
+                        None => return Ok(ERR_MALFORMED.into()),
+                    }
+                }
+                self.stop_reply = self.resume(computer, command == 's', interrupted)?;
+                self.stop_reply.as_str().into()
+            }
+            'b' if self.runner.can_rewind() && (args == "c" || args == "s") => {
+                self.stop_reply = self.resume_backwards(computer, args == "s")?;
+                self.stop_reply.as_str().into()
+            }
+            'Z' | 'z' => self.change_point(computer, command == 'Z', args).into(),
+            'q' => self.query(args).into(),
+            'Q' if args == "StartNoAckMode" => "OK".into(),
+            // There's only one thread
+            'H' | 'T' => "OK".into(),
+            'D' => Reply::Detach,
+            'k' => Reply::Kill,
+            _ => "".into(),
+        };
+        Ok(reply)
+    }
+
+    /// Execute one instruction or run until there's a reason to stop, returning the stop reply.
+    fn resume(
+        &mut self,
+        computer: &mut Computer,
+        step: bool,
+        interrupted: &mut dyn FnMut() -> bool,
+    ) -> eyre::Result<String> {
+        let mut num_instrs: u32 = 0;
+        loop {
+            if !self.runner.step_instruction(computer)? {
+                return Ok(String::from("W00"));
+            }
+            if let Some(watchpoint) =
+                triggered_watchpoint(&self.watchpoints, self.runner.accesses())
+            {
+                return Ok(watch_reply(watchpoint));
+            }
+            if self.breakpoints.contains(&computer.cpu.pc.address()) {
+                return Ok(self.breakpoint_reply());
+            }
+            if step {
+                return Ok(String::from("S05"));
+            }
+            num_instrs = num_instrs.wrapping_add(1);
+            if num_instrs.is_multiple_of(POLL_INTERVAL) && interrupted() {
+                return Ok(String::from("S02"));
+            }
+        }
+    }
+
+    /// Step back one instruction or run backwards until there's a reason to stop, returning the
+    /// stop reply.
+    fn resume_backwards(&mut self, computer: &mut Computer, step: bool) -> eyre::Result<String> {
+        let (breakpoints, watchpoints) = (&self.breakpoints, &self.watchpoints);
+        let breakpoint_reply = self.breakpoint_reply();
+        let found = self.runner.rewind(computer, 1, |computer, accesses, _| {
+            if step {
+                return Some(String::from("S05"));
+            }
+            if let Some(watchpoint) = triggered_watchpoint(watchpoints, accesses) {
+                return Some(watch_reply(watchpoint));
+            }
+            breakpoints
+                .contains(&computer.cpu.instr_addr)
+                .then(|| breakpoint_reply.clone())
+        })?;
+        Ok(found.unwrap_or_else(|| String::from("T05replaylog:begin;")))
+    }
+
+    /// The stop reply for hitting a breakpoint.
+    fn breakpoint_reply(&self) -> String {
+        String::from(if self.swbreak { "T05swbreak:;" } else { "S05" })
+    }
+
+    /// Handle a packet which writes registers or memory with the given function. Takes a snapshot
+    /// if they changed, so that rewinding doesn't lose the change.
+    fn write(
+        &mut self,
+        computer: &mut Computer,
+        args: &str,
+        write: fn(&mut Computer, &str) -> &'static str,
+    ) -> &'static str {
+        let reply = write(computer, args);
+        if reply == "OK" {
+            self.runner.branch(computer);
+        }
+        reply
+    }
+
+    /// Insert or remove a breakpoint or watchpoint.
+    fn change_point(&mut self, computer: &mut Computer, insert: bool, args: &str) -> &'static str {
+        let Some([kind, address, len]) = parse_hex_list::<3>(args) else {
+            return ERR_MALFORMED;
+        };
+        match (kind, insert) {
+            // Software and hardware breakpoints are the same thing here
+            (0 | 1, true) => {
+                if !self.breakpoints.contains(&address) {
+                    self.breakpoints.push(address);
+                }
+            }
+            (0 | 1, false) => self.breakpoints.retain(|&bp| bp != address),
+            (2..=4, _) => {
+                let watchpoint = Watchpoint {
+                    kind: match kind {
+                        2 => WatchKind::Write,
+                        3 => WatchKind::Read,
+                        _ => WatchKind::Access,
+                    },
+                    start: address,
+                    end: address.saturating_add(len),
+                };
+                if insert {
+                    self.watchpoints.push(watchpoint);
+                } else {
+                    self.watchpoints.retain(|&wp| wp != watchpoint);
+                }
+                self.runner
+                    .log_accesses(computer, !self.watchpoints.is_empty());
+            }
+            _ => return "",
+        }
+        "OK"
+    }
+
+    /// Answer a general query.
+    fn query(&mut self, args: &str) -> String {
+        if let Some(features) = args.strip_prefix("Supported") {
+            self.swbreak = features.split(';').any(|feature| feature == "swbreak+");
+            let reverse = if self.runner.can_rewind() {
+                ";ReverseStep+;ReverseContinue+"
+            } else {
+                ""
+            };
+            return format!(
+                "PacketSize={MAX_PACKET_SIZE:x};qXfer:features:read+;QStartNoAckMode+;swbreak+\
+                 {reverse}"
+            );
+        }
+        if let Some(range) = args.strip_prefix("Xfer:features:read:target.xml:") {
+            let Some([offset, len]) = parse_hex_list::<2>(range) else {
+                return String::from(ERR_MALFORMED);
+            };
+            let start = (offset as usize).min(TARGET_XML.len());
+            let end = (start + len as usize).min(TARGET_XML.len());
+            let marker = if end == TARGET_XML.len() { 'l' } else { 'm' };
+            return format!("{marker}{}", &TARGET_XML[start..end]);
+        }
+        match args {
+            "Attached" => String::from("1"),
+            "C" => String::from("QC1"),
+            "fThreadInfo" => String::from("m1"),
+            "sThreadInfo" => String::from("l"),
+            _ => String::new(),
+        }
+    }
+}
+
+/// A TCP connection to GDB.
+struct Connection {
+    stream: TcpStream,
+    /// Whether GDB has turned off acknowledgements.
+    no_ack: bool,
+}
+impl Connection {
+    /// Read the next packet, returning [None] if the connection is closed.
+    fn read_packet(&mut self) -> io::Result<Option<String>> {
+        loop {
+            // Skip acknowledgements, and interrupts which arrive after the computer stopped
+            match self.read_byte()? {
+                Some(b'$') => {}
+                Some(_) => continue,
+                None => return Ok(None),
+            }
+
+            let mut data = Vec::new();
+            loop {
+                match self.read_byte()? {
+                    Some(b'#') => break,
+                    Some(byte) => data.push(byte),
+                    None => return Ok(None),
+                }
+            }
+            let mut checksum_digits = [0; 2];
+            self.stream.read_exact(&mut checksum_digits)?;
+
+            let data = String::from_utf8_lossy(&data).into_owned();
+            let is_valid = std::str::from_utf8(&checksum_digits)
+                .ok()
+                .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+                == Some(checksum(&data));
+            if !self.no_ack {
+                self.stream.write_all(if is_valid { b"+" } else { b"-" })?;
+            }
+            if is_valid || self.no_ack {
+                return Ok(Some(data));
+            }
+        }
+    }
+
+    fn read_byte(&mut self) -> io::Result<Option<u8>> {
+        let mut byte = [0];
+        match self.stream.read(&mut byte)? {
+            0 => Ok(None),
+            _ => Ok(Some(byte[0])),
+        }
+    }
+
+    /// Check whether GDB has asked to interrupt the computer, without blocking. A closed
+    /// connection also counts as an interrupt.
+    fn interrupted(&mut self) -> bool {
+        if self.stream.set_nonblocking(true).is_err() {
+            return true;
+        }
+        let result = self.read_byte();
+        if self.stream.set_nonblocking(false).is_err() {
+            return true;
+        }
+        match result {
+            Ok(Some(byte)) => byte == INTERRUPT_BYTE,
+            Ok(None) => true,
+            Err(e) => e.kind() != ErrorKind::WouldBlock,
+        }
+    }
+
+    fn send(&mut self, data: &str) -> io::Result<()> {
+        self.stream.write_all(frame(data).as_bytes())
+    }
+}
+
+/// Find the first of the given watchpoints triggered by the given accesses.
+fn triggered_watchpoint(watchpoints: &[Watchpoint], accesses: &[MemAccess]) -> Option<Watchpoint> {
+    watchpoints
+        .iter()
+        .find(|wp| accesses.iter().any(|access| wp.is_triggered_by(access)))
+        .copied()
+}
+
+/// The stop reply for hitting the given watchpoint.
+fn watch_reply(watchpoint: Watchpoint) -> String {
+    let reason = match watchpoint.kind {
+        WatchKind::Read => "rwatch",
+        WatchKind::Access => "awatch",
+        WatchKind::Write | WatchKind::Change => "watch",
+    };
+    format!("T05{reason}:{:x};", watchpoint.start)
+}
+
+/// Write the values of all the registers, given in order.
+fn write_registers(computer: &mut Computer, hex: &str) -> &'static str {
+    let mut rest = hex;
+    for reg in REGISTERS {
+        let Some((digits, tail)) = rest.split_at_checked(reg.size() * 2) else {
+            return ERR_MALFORMED;
+        };
+        match reg.decode(computer, digits) {
+            Some(true) => {}
+            Some(false) => return ERR_INVALID,
+            None => return ERR_MALFORMED,
+        }
+        rest = tail;
+    }
+    "OK"
+}
+
+/// Write the value of a single register, e.g. `b=10000000` to set the PC to 0x10.
+fn write_register(computer: &mut Computer, args: &str) -> &'static str {
+    let Some((n, digits)) = args.split_once('=') else {
+        return ERR_MALFORMED;
+    };
+    let Some(reg) = parse_hex(n).and_then(|n| REGISTERS.get(n as usize)) else {
+        return ERR_MALFORMED;
+    };
+    match reg.decode(computer, digits) {
+        Some(true) => "OK",
+        Some(false) => ERR_INVALID,
+        None => ERR_MALFORMED,
+    }
+}
+
+/// Read memory, e.g. `800000,4` to read 4 bytes from 0x80_0000.
+fn read_memory(computer: &Computer, args: &str) -> String {
+    let Some([address, len]) = parse_hex_list::<2>(args) else {
+        return String::from(ERR_MALFORMED);
+    };
+    // Each byte takes two hex digits
+    let len = len.min(MAX_PACKET_SIZE as u32 / 2);
+    encode_hex(&read_bytes(computer, address, len))
+}
+
+/// Write memory, e.g. `800000,2:abcd` to write 0xAB and 0xCD to 0x80_0000 and 0x80_0001.
+fn write_memory(computer: &mut Computer, args: &str) -> &'static str {
+    let Some((range, digits)) = args.split_once(':') else {
+        return ERR_MALFORMED;
+    };
+    let (Some([address, len]), Some(bytes)) = (parse_hex_list::<2>(range), decode_hex(digits))
+    else {
+        return ERR_MALFORMED;
+    };
+    if bytes.len() != len as usize {
+        return ERR_MALFORMED;
+    }
+    for (i, &byte) in bytes.iter().enumerate() {
+        poke(computer, address.wrapping_add(i as u32), byte);
+    }
+    "OK"
+}
+
+fn read_bytes(computer: &Computer, address: u32, len: u32) -> Vec<u8> {
+    (0..len)
+        .map(|i| peek(computer, address.wrapping_add(i)))
+        .collect()
+}
+
+/// Wrap the data in a packet with its checksum.
+fn frame(data: &str) -> String {
+    format!("${data}#{:02x}", checksum(data))
+}
+
+fn checksum(data: &str) -> u8 {
+    data.bytes().fold(0, |sum, byte| sum.wrapping_add(byte))
+}
+
+fn parse_hex(s: &str) -> Option<u32> {
+    u32::from_str_radix(s, 16).ok()
+}
+
+/// Parse the given number of comma-separated hex numbers.
+fn parse_hex_list<const N: usize>(s: &str) -> Option<[u32; N]> {
+    let numbers = s.split(',').map(parse_hex).collect::<Option<Vec<u32>>>()?;
+    numbers.try_into().ok()
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn decode_hex(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use mfs16core::{test_utils::load_test_program, RewindBuffer};
+
+    use super::*;
+
+    /// Load the test program, then handle the packets and return the replies.
+    fn handle_packets(computer: &mut Computer, packets: &[&str]) -> Vec<Reply> {
+        let mut video_recorder = None;
+        let mut tracer = None;
+        let runner = Runner::new(None, None, &mut video_recorder, &mut tracer, None);
+        handle_packets_with(computer, runner, packets)
+    }
+
+    /// Load the test program, then handle the packets using the given [Runner] and return the
+    /// replies.
+    fn handle_packets_with(
+        computer: &mut Computer,
+        runner: Runner,
+        packets: &[&str],
+    ) -> Vec<Reply> {
+        load_test_program(computer);
+        let mut stub = GdbStub::new(runner);
+        packets
+            .iter()
+            .map(|packet| stub.handle(computer, packet, &mut || false).unwrap())
+            .collect()
+    }
+
+    fn packets(replies: &[&str]) -> Vec<Reply> {
+        replies.iter().map(|&reply| reply.into()).collect()
+    }
+
+    #[test]
+    fn test_frame() {
+        assert_eq!(frame("OK"), "$OK#9a");
+        assert_eq!(frame(""), "$#00");
+        assert_eq!(decode_hex("00ff1a"), Some(vec![0x00, 0xFF, 0x1A]));
+        assert_eq!(decode_hex("0"), None);
+        assert_eq!(parse_hex_list::<2>("800000,4"), Some([0x80_0000, 4]));
+        assert_eq!(parse_hex_list::<2>("800000"), None);
+    }
+
+    #[test]
+    fn test_registers() {
+        let mut computer = Computer::new(false);
+        let replies = handle_packets(
+            &mut computer,
+            &[
+                "g",
+                "P0=3412",
+                "P8=78563412",
+                "Pc=1f00",
+                "pa",
+                "Pa=00000000",
+                "p0",
+                "pd",
+            ],
+        );
+        assert_eq!(
+            replies,
+            packets(&[
+                "000000000000000000000000000000000000000000000000000000008000000000000000",
+                "OK",
+                "OK",
+                "OK",
+                "00008000",
+                ERR_INVALID,
+                "3412",
+                ERR_MALFORMED
+            ])
+        );
+        assert_eq!(computer.cpu.regs.reg(Reg16::A), 0x1234);
+        assert_eq!(computer.cpu.regs.reg(Reg16::D), 0x1234);
+        assert_eq!(computer.cpu.regs.reg(Reg16::E), 0x5678);
+        assert_eq!(computer.cpu.flags.to_string(), "ZCOPN");
+
+        let mut computer = Computer::new(false);
+        let mut all = REGISTERS.map(|reg| "00".repeat(reg.size())).concat();
+        all.replace_range(52..68, "0000800010000000");
+        let replies = handle_packets(&mut computer, &[&format!("G{all}"), "G00"]);
+        assert_eq!(replies, packets(&["OK", ERR_MALFORMED]));
+        assert_eq!(computer.cpu.pc.address(), 0x10);
+    }
+
+    #[test]
+    fn test_memory() {
+        let mut computer = Computer::new(false);
+        let replies = handle_packets(
+            &mut computer,
+            &[
+                "m0,4",
+                "M800000,2:abcd",
+                "m800000,3",
+                "M800000,2:ab",
+                "m800000",
+            ],
+        );
+        assert_eq!(
+            replies,
+            packets(&["00030100", "OK", "abcd00", ERR_MALFORMED, ERR_MALFORMED])
+        );
+
+        // Reading the input registers doesn't consume the queues
+        computer.text_input("hi");
+        computer.key_down(4_u16);
+        let replies = handle_packets(&mut computer, &["mffffff8f,5", "mffffff8f,5"]);
+        assert_eq!(replies[0], replies[1]);
+        assert_eq!(computer.mmu.text_input_queue.len(), 2);
+        assert_eq!(computer.mmu.kb_event_queue.len(), 1);
+    }
+
+    #[test]
+    fn test_resume() {
+        let mut computer = Computer::new(false);
+        let replies = handle_packets(&mut computer, &["s", "Z0,10,2", "c", "?", "z0,10,2", "c"]);
+        assert_eq!(replies, packets(&["S05", "OK", "S05", "S05", "OK", "W00"]));
+        assert!(computer.cpu.is_stopped);
+
+        let mut computer = Computer::new(false);
+        let replies = handle_packets(
+            &mut computer,
+            &[
+                "qSupported:multiprocess+;swbreak+",
+                "Z0,12,2",
+                "c",
+                "s",
+                "c0",
+            ],
+        );
+        assert_eq!(replies[1..3], packets(&["OK", "T05swbreak:;"]));
+        assert_eq!(computer.cpu.pc.address(), 0x12);
+        assert_eq!(replies[3..], packets(&["S05", "T05swbreak:;"]));
+    }
+
+    #[test]
+    fn test_watchpoints() {
+        let mut computer = Computer::new(false);
+        // The return address is pushed to the top of RAM
+        let replies = handle_packets(
+            &mut computer,
+            &[
+                "Z2,fffffc,4",
+                "c",
+                "Z3,fffffe,1",
+                "c",
+                "z2,fffffc,4",
+                "z3,fffffe,1",
+                "Z5,0,1",
+            ],
+        );
+        assert_eq!(
+            replies,
+            packets(&[
+                "OK",
+                "T05watch:fffffc;",
+                "OK",
+                "T05rwatch:fffffe;",
+                "OK",
+                "OK",
+                ""
+            ])
+        );
+        assert_eq!(computer.cpu.pc.address(), 0x0A);
+        assert!(computer.mmu.access_log.is_none());
+    }
+
+    #[test]
+    fn test_reverse() {
+        let mut computer = Computer::new(false);
+        let replies = handle_packets(&mut computer, &["s", "bs"]);
+        assert_eq!(replies, packets(&["S05", ""]));
+
+        let mut computer = Computer::new(false);
+        let mut video_recorder = None;
+        let mut tracer = None;
+        let rewind = RewindBuffer::new(4, 8);
+        let runner = Runner::new(None, None, &mut video_recorder, &mut tracer, Some(rewind));
+        let replies = handle_packets_with(
+            &mut computer,
+            runner,
+            &[
+                "qSupported:multiprocess+;swbreak+",
+                "s",
+                "s",
+                "bs",
+                "bs",
+                "bs",
+                "Z2,fffffc,4",
+                "c",
+                "c",
+                "bc",
+                "z2,fffffc,4",
+                "Z0,10,2",
+                "c",
+                "c",
+                "bc",
+                "z0,10,2",
+                "bc",
+            ],
+        );
+        let [Reply::Packet(supported), ..] = &replies[..] else {
+            panic!("Expected a packet, got {replies:?}");
+        };
+        assert!(supported.ends_with(";ReverseStep+;ReverseContinue+"));
+        assert_eq!(
+            replies[1..],
+            packets(&[
+                "S05",
+                "S05",
+                "S05",
+                "S05",
+                "T05replaylog:begin;",
+                "OK",
+                "T05watch:fffffc;",
+                "W00",
+                "T05watch:fffffc;",
+                "OK",
+                "OK",
+                "T05swbreak:;",
+                "W00",
+                "T05swbreak:;",
+                "OK",
+                "T05replaylog:begin;",
+            ])
+        );
+        assert_eq!(computer.cpu.pc.address(), 0x00);
+    }
+
+    #[test]
+    fn test_target_xml() {
+        let mut computer = Computer::new(false);
+        let replies = handle_packets(
+            &mut computer,
+            &[
+                "qXfer:features:read:target.xml:0,400",
+                "qXfer:features:read:target.xml:400,1000",
+            ],
+        );
+        let [Reply::Packet(first), Reply::Packet(second)] = &replies[..] else {
+            panic!("Expected two packets, got {replies:?}");
+        };
+        assert!(first.starts_with('m'));
+        assert!(second.starts_with('l'));
+        assert_eq!(format!("{}{}", &first[1..], &second[1..]), TARGET_XML);
+        assert_eq!(TARGET_XML.matches("<reg ").count(), REGISTERS.len());
+    }
+}
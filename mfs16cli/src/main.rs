@@ -1,13 +1,25 @@
-use std::{fs::File, io::Read};
+use std::{
+    fs::{self, File},
+    io::Read,
+    str::FromStr,
+};
 
-use camino::Utf8Path;
+use camino::{Utf8Path, Utf8PathBuf};
 use clap::Parser;
-use color_eyre::eyre;
-use mfs16core::{Addr, Computer};
+use color_eyre::eyre::{self, eyre};
+use mfs16core::{
+    Addr, Computer, InputLog, InputReplay, LineTable, RewindBuffer, SymbolMap, Tracer,
+    VideoRecorder,
+};
 
 mod arg_parser;
+mod debugger;
+mod gdb_stub;
+mod runner;
+mod terminal;
 
 use arg_parser::Cli;
+use runner::Runner;
 
 fn main() -> eyre::Result<()> {
     color_eyre::install()?;
@@ -18,9 +30,113 @@ fn main() -> eyre::Result<()> {
     let bytes: Vec<u8> = load_binary(&args.bin)?;
     computer.direct_write(Addr::new_default_range(0x00_0000), &bytes);
 
-    loop {
-        computer.cycle();
+    let input_replay = match &args.replay {
+        Some(path) => Some(load_input_log(path)?),
+        None => None,
+    };
+
+    let mut video_recorder = match &args.video {
+        Some(path) => Some(VideoRecorder::create(path)?),
+        None => None,
+    };
+
+    let mut tracer = match &args.trace {
+        Some(path) => Some(Tracer::create(path, args.trace_range.clone())?),
+        None => None,
+    };
+    if tracer.is_some() {
+        computer.mmu.log_accesses(true);
+    }
+
+    let rewind = args
+        .rewind
+        .map(|snapshots| RewindBuffer::new(snapshots, args.rewind_interval));
+
+    if args.terminal {
+        terminal::run(
+            &mut computer,
+            input_replay,
+            args.cycles,
+            args.colour_mode,
+            &mut video_recorder,
+        )?;
+    } else if let Some(port) = args.gdb {
+        gdb_stub::run(
+            &mut computer,
+            Runner::new(
+                input_replay,
+                args.cycles,
+                &mut video_recorder,
+                &mut tracer,
+                rewind,
+            ),
+            port,
+        )?;
+    } else if args.debugger {
+        let symbols = load_debug_file(args.symbols.as_deref(), SymbolMap::path_for(&args.bin))?;
+        let lines = load_debug_file(args.lines.as_deref(), LineTable::path_for(&args.bin))?;
+        debugger::run(
+            &mut computer,
+            Runner::new(
+                input_replay,
+                args.cycles,
+                &mut video_recorder,
+                &mut tracer,
+                rewind,
+            ),
+            symbols.unwrap_or_default(),
+            lines.unwrap_or_default(),
+        )?;
+    } else {
+        let mut runner = Runner::new(
+            input_replay,
+            args.cycles,
+            &mut video_recorder,
+            &mut tracer,
+            None,
+        );
+        while runner.step_instruction(&mut computer)? {}
+    }
+
+    if let Some(recorder) = video_recorder {
+        recorder.finish()?;
+    }
+    if let Some(tracer) = tracer {
+        tracer.finish()?;
     }
+
+    if args.debug {
+        println!(
+            "Stopped after {} cycles.\n{}",
+            computer.cycles, computer.cpu
+        );
+    }
+
+    Ok(())
+}
+
+fn load_input_log(file_path: &Utf8Path) -> eyre::Result<InputReplay> {
+    let input_log = fs::read_to_string(file_path)?
+        .parse::<InputLog>()
+        .map_err(|e| eyre!("Failed to load input log \"{file_path}\": {e}"))?;
+    Ok(InputReplay::new(input_log))
+}
+
+/// Load the debug file at the given path, or at the default path if none is given and a file
+/// exists there.
+fn load_debug_file<T: FromStr<Err = String>>(
+    path: Option<&Utf8Path>,
+    default_path: Utf8PathBuf,
+) -> eyre::Result<Option<T>> {
+    let path = match path {
+        Some(path) => path.to_owned(),
+        None if default_path.exists() => default_path,
+        None => return Ok(None),
+    };
+    fs::read_to_string(&path)?
+        .parse::<T>()
+        .map(Some)
+        .map_err(|e| eyre!("Failed to load \"{path}\": {e}"))
 }
 
 fn load_binary(file_path: &Utf8Path) -> eyre::Result<Vec<u8>> {
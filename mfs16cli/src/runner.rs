@@ -0,0 +1,219 @@
+//! Runs a [Computer] one instruction at a time, for the front ends which debug programs.
+use std::{fs::File, io::BufWriter};
+
+use color_eyre::eyre::{self, eyre};
+use mfs16core::{
+    Addr, Computer, DecodedInstruction, InputReplay, MemAccess, RewindBuffer, Snapshot, Tracer,
+    VideoRecorder, ROM_OFFSET, ROM_SIZE,
+};
+
+/// The size of the longest instruction, in bytes.
+const MAX_INSTR_SIZE: u32 = 6;
+
+/// Executes instructions while replaying inputs, recording video and tracing, just like a normal
+/// run.
+pub struct Runner<'a> {
+    /// The inputs to give to the computer as it runs.
+    input_replay: Option<InputReplay>,
+    /// Never run the computer past this many cycles.
+    cycles: Option<u128>,
+    /// Records every frame presented while the computer runs.
+    video_recorder: &'a mut Option<VideoRecorder<BufWriter<File>>>,
+    /// Writes a record of every instruction executed.
+    tracer: &'a mut Option<Tracer<BufWriter<File>>>,
+    /// The reads and writes made by the last instruction, while the Mmu is logging them.
+    accesses: Vec<MemAccess>,
+    /// Whether a frame was presented during the last instruction.
+    frame_presented: bool,
+    /// Snapshots of the computer to rewind it to, if reverse execution is on.
+    rewind: Option<RewindBuffer>,
+    /// The most cycles the computer has run for. Frames and instructions up to here were already
+    /// recorded, so they aren't recorded again when re-executed after rewinding.
+    high_water: u128,
+}
+impl<'a> Runner<'a> {
+    /// Create a new [Runner].
+    pub fn new(
+        input_replay: Option<InputReplay>,
+        cycles: Option<u128>,
+        video_recorder: &'a mut Option<VideoRecorder<BufWriter<File>>>,
+        tracer: &'a mut Option<Tracer<BufWriter<File>>>,
+        rewind: Option<RewindBuffer>,
+    ) -> Self {
+        Self {
+            input_replay,
+            cycles,
+            video_recorder,
+            tracer,
+            accesses: Vec::new(),
+            frame_presented: false,
+            rewind,
+            high_water: 0,
+        }
+    }
+
+    /// The reads and writes made by the last instruction, while the Mmu is logging them.
+    pub fn accesses(&self) -> &[MemAccess] {
+        &self.accesses
+    }
+
+    /// Start or stop logging memory accesses, e.g. for watchpoints. They're always logged while
+    /// tracing.
+    pub fn log_accesses(&self, computer: &mut Computer, enabled: bool) {
+        computer.mmu.log_accesses(enabled || self.tracer.is_some());
+    }
+
+    /// Perform cycles until the current instruction is done. Returns `false` if the computer
+    /// can't run any further because it has stopped or reached the cycle limit.
+    pub fn step_instruction(&mut self, computer: &mut Computer) -> eyre::Result<bool> {
+        if let Some(rewind) = &mut self.rewind {
+            rewind.record(computer);
+        }
+        self.frame_presented = false;
+        loop {
+            if computer.cpu.is_stopped || self.cycles.is_some_and(|c| computer.cycles >= c) {
+                return Ok(false);
+            }
+
+            computer.cycle();
+
+            if let Some(replay) = &mut self.input_replay {
+                replay.apply(computer);
+            }
+            if computer.mmu.gpu.consume_frame_ready() {
+                self.frame_presented = true;
+                if let Some(recorder) = self.video_recorder {
+                    if computer.cycles > self.high_water {
+                        recorder.record(computer)?;
+                    }
+                }
+            }
+
+            if computer.cpu.instr_is_done() {
+                self.accesses = computer.mmu.take_accesses();
+                if computer.cycles > self.high_water {
+                    self.high_water = computer.cycles;
+                    if let Some(tracer) = self.tracer {
+                        tracer.record(computer, self.accesses.clone())?;
+                    }
+                }
+                return Ok(true);
+            }
+        }
+    }
+
+    /// Whether reverse execution is on.
+    pub fn can_rewind(&self) -> bool {
+        self.rewind.is_some()
+    }
+
+    /// Rewind to the start of the `count`th most recent instruction for which `is_match` returns
+    /// a value, given the computer after the instruction, the accesses it made and whether it
+    /// presented a frame. Returns that value, or [None] if there weren't enough matching
+    /// instructions since the oldest snapshot, in which case the computer is rewound to it.
+    ///
+    /// The instructions are found by restoring snapshots and executing forwards again, so they
+    /// aren't recorded or traced a second time.
+    pub fn rewind<T>(
+        &mut self,
+        computer: &mut Computer,
+        count: usize,
+        mut is_match: impl FnMut(&Computer, &[MemAccess], bool) -> Option<T>,
+    ) -> eyre::Result<Option<T>> {
+        let Some(buffer) = self.rewind.take() else {
+            return Err(eyre!(
+                "Reverse execution is off. Run with `--rewind` to turn it on."
+            ));
+        };
+        let is_logging = computer.mmu.access_log.is_some();
+        computer.mmu.log_accesses(true);
+
+        let result = self.rewind_with(&buffer, computer, count.max(1), &mut is_match);
+
+        computer.mmu.log_accesses(is_logging);
+        self.accesses.clear();
+        self.rewind = Some(buffer);
+        result
+    }
+
+    fn rewind_with<T>(
+        &mut self,
+        buffer: &RewindBuffer,
+        computer: &mut Computer,
+        mut remaining: usize,
+        is_match: &mut impl FnMut(&Computer, &[MemAccess], bool) -> Option<T>,
+    ) -> eyre::Result<Option<T>> {
+        // Search backwards one snapshot at a time, starting with the instructions since the
+        // newest one
+        let mut end = computer.cycles;
+        while let Some(snapshot) = buffer.before(end) {
+            self.restore(computer, snapshot);
+            let mut matches = Vec::new();
+            while computer.cycles < end {
+                let start = computer.cycles;
+                if !self.step_instruction(computer)? {
+                    break;
+                }
+                if let Some(value) = is_match(computer, &self.accesses, self.frame_presented) {
+                    matches.push((start, value));
+                }
+            }
+
+            if matches.len() >= remaining {
+                let (target, value) = matches.swap_remove(matches.len() - remaining);
+                self.restore(computer, snapshot);
+                while computer.cycles < target && self.step_instruction(computer)? {}
+                return Ok(Some(value));
+            }
+            remaining -= matches.len();
+            end = snapshot.cycles();
+        }
+
+        if let Some(oldest) = buffer.oldest() {
+            self.restore(computer, oldest);
+        }
+        Ok(None)
+    }
+
+    /// Restore the computer to the given [Snapshot], replaying inputs from there.
+    fn restore(&mut self, computer: &mut Computer, snapshot: &Snapshot) {
+        snapshot.restore(computer);
+        if let Some(replay) = &mut self.input_replay {
+            replay.seek(snapshot.cycles());
+        }
+    }
+
+    /// Take a snapshot after the computer was changed by something other than running it, e.g.
+    /// by writing memory, so that the change isn't lost by rewinding past it.
+    pub fn branch(&mut self, computer: &Computer) {
+        if let Some(rewind) = &mut self.rewind {
+            rewind.branch(computer);
+        }
+    }
+}
+
+/// Read a byte for display. Unlike a program, the debugger doesn't raise an error interrupt when
+/// reading an unmapped address, and doesn't trigger watchpoints.
+pub fn peek(computer: &Computer, address: u32) -> u8 {
+    computer.mmu.inspect_byte(address)
+}
+
+/// Write a byte on behalf of the debugger. Unlike a program, the debugger can patch ROM, and
+/// doesn't trigger watchpoints.
+pub fn poke(computer: &mut Computer, address: u32, byte: u8) {
+    if (address as usize) < ROM_OFFSET + ROM_SIZE {
+        computer.direct_write(Addr::new_default_range(address), &[byte]);
+    } else {
+        computer
+            .mmu
+            .without_access_log(|mmu| mmu.write_byte(address, byte));
+    }
+}
+
+/// Decode the instruction at the given address.
+pub fn decode(computer: &Computer, address: u32) -> Option<DecodedInstruction> {
+    let bytes: Vec<u8> = (0..MAX_INSTR_SIZE)
+        .map(|i| peek(computer, address.wrapping_add(i)))
+        .collect();
+    DecodedInstruction::decode(&bytes)
+}
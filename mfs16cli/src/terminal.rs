@@ -0,0 +1,353 @@
+//! Terminal front end. Draws the screen with Unicode half-block characters and ANSI colours, and
+//! forwards key presses to the computer.
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    thread,
+    time::{Duration, Instant},
+};
+
+use clap::ValueEnum;
+use color_eyre::eyre;
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    queue,
+    style::{Color, Colors, Print, ResetColor, SetColors},
+    terminal,
+};
+use mfs16core::{
+    Computer, InputReplay, KbCode, Keystroke, KeystrokeScript, VideoRecorder, CLOCK_FREQ,
+    DEFAULT_KEYSTROKE_CYCLES, DEFAULT_PALETTE, FRAME_CYCLES,
+};
+
+/// Drawn in every character cell. The foreground colour is the top pixel and the background
+/// colour is the bottom pixel.
+const UPPER_HALF_BLOCK: char = '▀';
+
+/// The kind of ANSI colour escape codes used to draw the screen.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, ValueEnum)]
+pub enum ColourMode {
+    /// 24-bit colour.
+    #[default]
+    TrueColour,
+    /// The 256-colour palette supported by most terminals.
+    Ansi256,
+}
+
+/// Run the computer in the terminal until it stops, the cycle limit is reached, or Ctrl+C is
+/// pressed. The terminal is restored afterwards, even if drawing fails.
+pub fn run(
+    computer: &mut Computer,
+    input_replay: Option<InputReplay>,
+    cycles: Option<u128>,
+    colour_mode: ColourMode,
+    video_recorder: &mut Option<VideoRecorder<BufWriter<File>>>,
+) -> eyre::Result<()> {
+    let mut stdout = io::stdout();
+    terminal::enable_raw_mode()?;
+    queue!(stdout, terminal::EnterAlternateScreen, cursor::Hide)?;
+
+    let result = run_loop(computer, input_replay, cycles, colour_mode, video_recorder);
+
+    queue!(
+        stdout,
+        ResetColor,
+        cursor::Show,
+        terminal::LeaveAlternateScreen
+    )?;
+    stdout.flush()?;
+    terminal::disable_raw_mode()?;
+    result
+}
+
+fn run_loop(
+    computer: &mut Computer,
+    mut input_replay: Option<InputReplay>,
+    cycles: Option<u128>,
+    colour_mode: ColourMode,
+    video_recorder: &mut Option<VideoRecorder<BufWriter<File>>>,
+) -> eyre::Result<()> {
+    let frame_duration = Duration::from_secs_f64(FRAME_CYCLES as f64 / CLOCK_FREQ as f64);
+    let mut renderer = Renderer::new(colour_mode);
+    // Terminals only report typed keys, so each one is typed out as a press and a release
+    let mut keystroke_script =
+        KeystrokeScript::new(computer.mmu.kb_layout, DEFAULT_KEYSTROKE_CYCLES);
+
+    loop {
+        let frame_start = Instant::now();
+
+        // Handle terminal events. Live input is ignored until the replay is done.
+        while event::poll(Duration::ZERO)? {
+            match event::read()? {
+                Event::Key(key) if key.kind != KeyEventKind::Release => {
+                    if key.code == KeyCode::Char('c')
+                        && key.modifiers.contains(KeyModifiers::CONTROL)
+                    {
+                        return Ok(());
+                    }
+                    if input_replay.is_none() {
+                        forward_key(key, computer, &mut keystroke_script);
+                    }
+                }
+                Event::Resize(..) => renderer.clear(),
+                _ => {}
+            }
+        }
+
+        // Perform the CPU cycles for this frame
+        for _ in 0..FRAME_CYCLES {
+            if computer.cpu.is_stopped || cycles.is_some_and(|cycles| computer.cycles >= cycles) {
+                return Ok(());
+            }
+            computer.cycle();
+
+            if let Some(replay) = &mut input_replay {
+                replay.apply(computer);
+                if replay.is_done() {
+                    input_replay = None;
+                }
+            } else {
+                keystroke_script.cycle(computer);
+            }
+
+            if computer.mmu.gpu.consume_frame_ready() {
+                renderer.draw(computer)?;
+                if let Some(recorder) = video_recorder {
+                    recorder.record(computer)?;
+                }
+            }
+        }
+
+        // Maintain the frame rate
+        let frame_time = frame_start.elapsed();
+        if frame_time < frame_duration {
+            thread::sleep(frame_duration - frame_time);
+        }
+    }
+}
+
+/// Forward a terminal key press to the computer. Typed characters are also passed on as text
+/// input.
+fn forward_key(key: KeyEvent, computer: &mut Computer, keystroke_script: &mut KeystrokeScript) {
+    let mut modifiers = Vec::new();
+    if key.modifiers.contains(KeyModifiers::CONTROL) {
+        modifiers.push(KbCode::LCtrl);
+    }
+    if key.modifiers.contains(KeyModifiers::ALT) {
+        modifiers.push(KbCode::LAlt);
+    }
+
+    let code = match key.code {
+        KeyCode::Char(ch) if modifiers.is_empty() => {
+            computer.text_input(&ch.to_string());
+            keystroke_script.push_text(&ch.to_string());
+            return;
+        }
+        KeyCode::Char(ch) => {
+            match KeystrokeScript::key_for_char(ch.to_ascii_lowercase(), computer.mmu.kb_layout) {
+                Some((code, _)) => code,
+                None => return,
+            }
+        }
+        KeyCode::BackTab => {
+            modifiers.push(KbCode::LShift);
+            KbCode::Tab
+        }
+        KeyCode::Enter => KbCode::Return,
+        KeyCode::Esc => KbCode::Escape,
+        KeyCode::Backspace => KbCode::Backspace,
+        KeyCode::Tab => KbCode::Tab,
+        KeyCode::Delete => KbCode::Delete,
+        KeyCode::Insert => KbCode::Insert,
+        KeyCode::Home => KbCode::Home,
+        KeyCode::End => KbCode::End,
+        KeyCode::PageUp => KbCode::PageUp,
+        KeyCode::PageDown => KbCode::PageDown,
+        KeyCode::Left => KbCode::Left,
+        KeyCode::Right => KbCode::Right,
+        KeyCode::Up => KbCode::Up,
+        KeyCode::Down => KbCode::Down,
+        KeyCode::F(n @ 1..=12) => {
+            match KbCode::try_from_u16(u16::from(KbCode::F1) + n as u16 - 1) {
+                Some(code) => code,
+                None => return,
+            }
+        }
+        _ => return,
+    };
+
+    for &modifier in &modifiers {
+        keystroke_script.push_keystroke(Keystroke::new(modifier, true));
+    }
+    keystroke_script.push_keystroke(Keystroke::new(code, true));
+    keystroke_script.push_keystroke(Keystroke::new(code, false));
+    for &modifier in modifiers.iter().rev() {
+        keystroke_script.push_keystroke(Keystroke::new(modifier, false));
+    }
+}
+
+/// Draws frames in the terminal, shrunk by a whole number of pixels to fit.
+struct Renderer {
+    colour_mode: ColourMode,
+    /// The output of the frame being drawn.
+    output: Vec<u8>,
+    /// The output of the last frame drawn. Frames which look the same aren't drawn again.
+    last_output: Vec<u8>,
+}
+impl Renderer {
+    fn new(colour_mode: ColourMode) -> Self {
+        Self {
+            colour_mode,
+            output: Vec::new(),
+            last_output: Vec::new(),
+        }
+    }
+
+    /// Clear the terminal, making sure the next frame is drawn in full.
+    fn clear(&mut self) {
+        self.last_output.clear();
+    }
+
+    /// Draw the most recently presented frame of the computer.
+    fn draw(&mut self, computer: &Computer) -> eyre::Result<()> {
+        let gpu = &computer.mmu.gpu;
+        let frame = gpu.frame_buffer();
+        let (width, height) = gpu.frame_size();
+        // Guard against terminals which report a size of 0
+        let (columns, rows) = terminal::size()?;
+        let (columns, rows) = (columns.max(1) as usize, rows.max(1) as usize);
+        let colours: Vec<Color> = gpu
+            .palette()
+            .iter()
+            .map(|&colour| terminal_colour(colour, self.colour_mode))
+            .collect();
+
+        // Each character cell holds two pixels, one above the other
+        let scale = width
+            .div_ceil(columns)
+            .max(height.div_ceil(rows * 2))
+            .max(1);
+
+        self.output.clear();
+        for row in 0..(height / (scale * 2)) {
+            queue!(self.output, cursor::MoveTo(0, row as u16))?;
+            let top_line = row * 2 * scale * width;
+            let bottom_line = top_line + scale * width;
+            let mut current_colours = None;
+            for column in 0..(width / scale) {
+                let x = column * scale;
+                let cell_colours = Colors::new(
+                    colours[frame[top_line + x] as usize],
+                    colours[frame[bottom_line + x] as usize],
+                );
+                if current_colours != Some(cell_colours) {
+                    queue!(self.output, SetColors(cell_colours))?;
+                    current_colours = Some(cell_colours);
+                }
+                queue!(self.output, Print(UPPER_HALF_BLOCK))?;
+            }
+        }
+
+        if self.output != self.last_output {
+            let mut stdout = io::stdout();
+            if self.last_output.is_empty() {
+                queue!(
+                    stdout,
+                    ResetColor,
+                    terminal::Clear(terminal::ClearType::All)
+                )?;
+            }
+            stdout.write_all(&self.output)?;
+            stdout.flush()?;
+            std::mem::swap(&mut self.output, &mut self.last_output);
+        }
+        Ok(())
+    }
+}
+
+/// Convert a colour stored as `0x00RRGGBB` to a terminal colour.
+fn terminal_colour(colour: u32, colour_mode: ColourMode) -> Color {
+    match colour_mode {
+        ColourMode::TrueColour => Color::Rgb {
+            r: (colour >> 16) as u8,
+            g: (colour >> 8) as u8,
+            b: colour as u8,
+        },
+        ColourMode::Ansi256 => Color::AnsiValue(ansi_256_index(colour)),
+    }
+}
+
+/// Find the closest colour out of the colour cube and greyscale ramp of 256-colour terminals,
+/// which are the same as colours 16-255 of the default MFS-16 palette. The first 16 colours are
+/// skipped, since terminals often change them.
+fn ansi_256_index(colour: u32) -> u8 {
+    let distance = |other: u32| {
+        [16, 8, 0]
+            .into_iter()
+            .map(|shift| {
+                let difference =
+                    ((colour >> shift) & 0xFF) as i32 - ((other >> shift) & 0xFF) as i32;
+                difference * difference
+            })
+            .sum::<i32>()
+    };
+    (16..DEFAULT_PALETTE.len())
+        .min_by_key(|&index| distance(DEFAULT_PALETTE[index]))
+        .unwrap_or(0) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_forward_key() {
+        let mut computer = Computer::new(false);
+        let mut script = KeystrokeScript::new(computer.mmu.kb_layout, 0);
+
+        forward_key(
+            KeyEvent::new(KeyCode::Char('a'), KeyModifiers::CONTROL),
+            &mut computer,
+            &mut script,
+        );
+        forward_key(
+            KeyEvent::new(KeyCode::F(2), KeyModifiers::NONE),
+            &mut computer,
+            &mut script,
+        );
+        let keystrokes: Vec<(KbCode, bool)> = script
+            .keystrokes()
+            .iter()
+            .map(|keystroke| (keystroke.code, keystroke.pressed))
+            .collect();
+        assert_eq!(
+            keystrokes,
+            [
+                (KbCode::LCtrl, true),
+                (KbCode::A, true),
+                (KbCode::A, false),
+                (KbCode::LCtrl, false),
+                (KbCode::F2, true),
+                (KbCode::F2, false),
+            ]
+        );
+
+        forward_key(
+            KeyEvent::new(KeyCode::Char('H'), KeyModifiers::SHIFT),
+            &mut computer,
+            &mut script,
+        );
+        assert_eq!(script.keystrokes().len(), 10);
+        assert_eq!(computer.mmu.text_input_queue.pop(), Some(b'H'));
+    }
+
+    #[test]
+    fn test_ansi_256_index() {
+        assert_eq!(ansi_256_index(0x000000), 16);
+        assert_eq!(ansi_256_index(0xFFFFFF), 231);
+        assert_eq!(ansi_256_index(0xFF875F), 209);
+        assert_eq!(ansi_256_index(0x7F7F7F), 244);
+        assert_eq!(ansi_256_index(0xC0C0C0), 250);
+    }
+}
@@ -0,0 +1,446 @@
+//! Conditions written in a small expression language, which decide when a debugger breaks.
+//!
+//! For example: `pc == label(main_loop) && [0x800010].w > 5 || flag(Z)`.
+use std::{fmt::Display, iter::Peekable, str::CharIndices};
+
+use crate::{Computer, Flag, Reg16, Reg32, Reg8, SymbolMap};
+
+/// A condition which is checked against the state of a [Computer].
+///
+/// Every value is an unsigned 64-bit integer. Comparisons and logical operators give 1 if true
+/// and 0 if false, and any value other than 0 counts as true. From lowest to highest precedence,
+/// the operators are:
+///
+/// - `||`
+/// - `&&`
+/// - `==`, `!=`, `<`, `<=`, `>`, `>=`
+/// - `|`
+/// - `^`
+/// - `&`
+/// - `+`, `-` (wrapping)
+/// - `!` (logical not), `~` (bitwise not)
+///
+/// The values are:
+///
+/// - Numbers in decimal, hexadecimal (`0x`) or binary (`0b`), optionally separated by `_`.
+/// - Registers: `a`-`l`, `bc`, `de`, `hl`, `a1`-`l0`, `pc` and `sp`.
+/// - `flag(Z)`: 1 if the given flag (`Z`, `C`, `O`, `P` or `N`) is set.
+/// - `ei`: 1 if interrupts are enabled.
+/// - `cycles`: the total number of cycles performed by the computer.
+/// - `label(name)`: the address of a label of the program.
+/// - `[address]`: the byte at an address. Append `.w` or `.d` to read a little-endian word or
+///   double word instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BreakCondition {
+    /// The condition as it was written.
+    source: String,
+    /// The parsed condition.
+    expr: Expr,
+}
+impl BreakCondition {
+    /// Parse a condition. Labels are looked up in the given [SymbolMap].
+    pub fn parse(source: &str, symbols: Option<&SymbolMap>) -> Result<Self, String> {
+        let mut parser = Parser {
+            source,
+            chars: source.char_indices().peekable(),
+            symbols,
+        };
+        let expr = parser.parse_expr(0)?;
+        parser.skip_whitespace();
+        if let Some((i, _)) = parser.chars.peek() {
+            return Err(format!("Unexpected `{}`.", &source[*i..]));
+        }
+        Ok(Self {
+            source: source.to_string(),
+            expr,
+        })
+    }
+
+    /// Check whether the given [Computer] satisfies this condition.
+    pub fn is_satisfied(&self, computer: &Computer) -> bool {
+        self.expr.evaluate(computer) != 0
+    }
+}
+impl Display for BreakCondition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+/// A binary operator.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum BinOp {
+    Or,
+    And,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    BitOr,
+    BitXor,
+    BitAnd,
+    Add,
+    Sub,
+}
+impl BinOp {
+    /// The binary operators, grouped from lowest to highest precedence. Longer symbols come first
+    /// so that e.g. `<=` isn't parsed as `<`.
+    const PRECEDENCE: [&'static [(&'static str, BinOp)]; 7] = [
+        &[("||", BinOp::Or)],
+        &[("&&", BinOp::And)],
+        &[
+            ("==", BinOp::Eq),
+            ("!=", BinOp::Ne),
+            ("<=", BinOp::Le),
+            (">=", BinOp::Ge),
+            ("<", BinOp::Lt),
+            (">", BinOp::Gt),
+        ],
+        &[("|", BinOp::BitOr)],
+        &[("^", BinOp::BitXor)],
+        &[("&", BinOp::BitAnd)],
+        &[("+", BinOp::Add), ("-", BinOp::Sub)],
+    ];
+
+    fn apply(self, lhs: u64, rhs: u64) -> u64 {
+        match self {
+            BinOp::Or => ((lhs != 0) || (rhs != 0)) as u64,
+            BinOp::And => ((lhs != 0) && (rhs != 0)) as u64,
+            BinOp::Eq => (lhs == rhs) as u64,
+            BinOp::Ne => (lhs != rhs) as u64,
+            BinOp::Lt => (lhs < rhs) as u64,
+            BinOp::Le => (lhs <= rhs) as u64,
+            BinOp::Gt => (lhs > rhs) as u64,
+            BinOp::Ge => (lhs >= rhs) as u64,
+            BinOp::BitOr => lhs | rhs,
+            BinOp::BitXor => lhs ^ rhs,
+            BinOp::BitAnd => lhs & rhs,
+            BinOp::Add => lhs.wrapping_add(rhs),
+            BinOp::Sub => lhs.wrapping_sub(rhs),
+        }
+    }
+}
+
+/// A parsed expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Expr {
+    Num(u64),
+    Reg(Reg16),
+    BigReg(Reg32),
+    VirtualReg(Reg8),
+    Pc,
+    Sp,
+    Flag(Flag),
+    InterruptsEnabled,
+    Cycles,
+    /// Read the given number of bytes at an address.
+    Mem(Box<Expr>, u32),
+    Not(Box<Expr>),
+    BitNot(Box<Expr>),
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+}
+impl Expr {
+    fn evaluate(&self, computer: &Computer) -> u64 {
+        match self {
+            Expr::Num(n) => *n,
+            Expr::Reg(reg) => computer.cpu.reg(*reg) as u64,
+            Expr::BigReg(breg) => computer.cpu.breg(*breg) as u64,
+            Expr::VirtualReg(vreg) => computer.cpu.vreg(*vreg) as u64,
+            Expr::Pc => computer.cpu.pc.address() as u64,
+            Expr::Sp => computer.cpu.sp.address() as u64,
+            Expr::Flag(flag) => computer.cpu.flag(*flag) as u64,
+            Expr::InterruptsEnabled => computer.cpu.interrupts_enabled as u64,
+            Expr::Cycles => computer.cycles as u64,
+            Expr::Mem(address, width) => {
+                let address = address.evaluate(computer) as u32;
+                (0..*width).rev().fold(0, |value, i| {
+                    (value << 8) | computer.mmu.inspect_byte(address.wrapping_add(i)) as u64
+                })
+            }
+            Expr::Not(expr) => (expr.evaluate(computer) == 0) as u64,
+            Expr::BitNot(expr) => !expr.evaluate(computer),
+            Expr::Binary(op, lhs, rhs) => {
+                let lhs = lhs.evaluate(computer);
+                // Short-circuit so that e.g. `pc == 0x10 && [hl] == 0` only reads at the PC
+                match (op, lhs != 0) {
+                    (BinOp::Or, true) => 1,
+                    (BinOp::And, false) => 0,
+                    _ => op.apply(lhs, rhs.evaluate(computer)),
+                }
+            }
+        }
+    }
+}
+
+/// Parses a [BreakCondition] by recursive descent.
+struct Parser<'a> {
+    source: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+    symbols: Option<&'a SymbolMap>,
+}
+impl Parser<'_> {
+    /// Parse the binary operators of the given precedence level and higher.
+    fn parse_expr(&mut self, level: usize) -> Result<Expr, String> {
+        let Some(ops) = BinOp::PRECEDENCE.get(level) else {
+            return self.parse_unary();
+        };
+        let mut lhs = self.parse_expr(level + 1)?;
+        'outer: loop {
+            for &(symbol, op) in *ops {
+                if self.eat(symbol) {
+                    let rhs = self.parse_expr(level + 1)?;
+                    lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+                    continue 'outer;
+                }
+            }
+            return Ok(lhs);
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, String> {
+        if self.eat("!") {
+            Ok(Expr::Not(Box::new(self.parse_unary()?)))
+        } else if self.eat("~") {
+            Ok(Expr::BitNot(Box::new(self.parse_unary()?)))
+        } else {
+            self.parse_value()
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<Expr, String> {
+        if self.eat("(") {
+            let expr = self.parse_expr(0)?;
+            self.expect(")")?;
+            return Ok(expr);
+        }
+        if self.eat("[") {
+            let address = self.parse_expr(0)?;
+            self.expect("]")?;
+            let width = if self.eat(".") {
+                match self.word().as_str() {
+                    "b" => 1,
+                    "w" => 2,
+                    "d" => 4,
+                    width => {
+                        return Err(format!("Unknown width `.{width}`. Expected .b, .w or .d."))
+                    }
+                }
+            } else {
+                1
+            };
+            return Ok(Expr::Mem(Box::new(address), width));
+        }
+
+        let word = self.word();
+        if word.is_empty() {
+            return Err(match self.chars.peek() {
+                Some((i, _)) => format!("Expected a value, found `{}`.", &self.source[*i..]),
+                None => String::from("Expected a value, found the end of the condition."),
+            });
+        }
+        if word.starts_with(|c: char| c.is_ascii_digit()) {
+            return parse_num(&word).map(Expr::Num);
+        }
+        match word.as_str() {
+            "pc" => return Ok(Expr::Pc),
+            "sp" => return Ok(Expr::Sp),
+            "ei" => return Ok(Expr::InterruptsEnabled),
+            "cycles" => return Ok(Expr::Cycles),
+            "flag" => return self.parse_flag(),
+            "label" => return self.parse_label(),
+            _ => {}
+        }
+        let name = word.to_uppercase();
+        if let Ok(reg) = Reg16::try_from(name.as_str()) {
+            Ok(Expr::Reg(reg))
+        } else if let Ok(breg) = Reg32::try_from(name.as_str()) {
+            Ok(Expr::BigReg(breg))
+        } else if let Ok(vreg) = Reg8::try_from(name.as_str()) {
+            Ok(Expr::VirtualReg(vreg))
+        } else {
+            Err(format!("Unknown value `{word}`."))
+        }
+    }
+
+    fn parse_flag(&mut self) -> Result<Expr, String> {
+        self.expect("(")?;
+        let flag = match self.word().as_str() {
+            "Z" => Flag::Zero,
+            "C" => Flag::Carry,
+            "O" => Flag::Overflow,
+            "P" => Flag::Parity,
+            "N" => Flag::Negative,
+            flag => return Err(format!("Unknown flag `{flag}`. Expected Z, C, O, P or N.")),
+        };
+        self.expect(")")?;
+        Ok(Expr::Flag(flag))
+    }
+
+    fn parse_label(&mut self) -> Result<Expr, String> {
+        self.expect("(")?;
+        let name = self.word();
+        self.expect(")")?;
+        let symbols = self
+            .symbols
+            .ok_or_else(|| format!("Can't find label `{name}` without a symbol map."))?;
+        symbols
+            .address_of(&name)
+            .map(|address| Expr::Num(address as u64))
+            .ok_or_else(|| format!("Unknown label `{name}`."))
+    }
+
+    /// Consume the given symbol if it comes next.
+    fn eat(&mut self, symbol: &str) -> bool {
+        self.skip_whitespace();
+        let Some(&(i, _)) = self.chars.peek() else {
+            return false;
+        };
+        let rest = &self.source[i..];
+        // Don't mistake `||` for `|` or `&&` for `&`
+        if !rest.starts_with(symbol)
+            || (matches!(symbol, "|" | "&") && rest[1..].starts_with(symbol))
+        {
+            return false;
+        }
+        for _ in symbol.chars() {
+            self.chars.next();
+        }
+        true
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<(), String> {
+        if self.eat(symbol) {
+            Ok(())
+        } else {
+            Err(format!("Expected `{symbol}`."))
+        }
+    }
+
+    /// Consume a name or number, which may be empty.
+    fn word(&mut self) -> String {
+        self.skip_whitespace();
+        let mut word = String::new();
+        while let Some((_, c)) = self
+            .chars
+            .next_if(|(_, c)| c.is_alphanumeric() || *c == '_')
+        {
+            word.push(c);
+        }
+        word
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+    }
+}
+
+/// Parse a number in decimal, hexadecimal (`0x`) or binary (`0b`), optionally separated by `_`.
+fn parse_num(word: &str) -> Result<u64, String> {
+    let digits = word.replace('_', "");
+    let result = if let Some(hex) = digits.strip_prefix("0x") {
+        u64::from_str_radix(hex, 16)
+    } else if let Some(bin) = digits.strip_prefix("0b") {
+        u64::from_str_radix(bin, 2)
+    } else {
+        digits.parse()
+    };
+    result.map_err(|_| format!("Invalid number `{word}`."))
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::{Addr, RAM_OFFSET};
+
+    use super::*;
+
+    fn check(computer: &mut Computer, source: &str) -> bool {
+        let mut symbols = SymbolMap::default();
+        symbols.add_label("main_loop", 0x10);
+        BreakCondition::parse(source, Some(&symbols))
+            .unwrap()
+            .is_satisfied(computer)
+    }
+
+    #[test]
+    fn test_values() {
+        let mut computer = Computer::new(false);
+        computer.cpu.set_reg(Reg16::A, 0x1234);
+        computer.cpu.set_breg(Reg32::HL, 0x00AB_CDEF);
+        computer.cpu.pc = Addr::new_default_range(0x10);
+        computer.cpu.set_flag(Flag::Zero);
+        computer.cycles = 1_000;
+        computer
+            .mmu
+            .write_dword(RAM_OFFSET as u32 + 0x10, 0x0102_0304);
+
+        assert!(check(&mut computer, "a == 0x1234"));
+        assert!(check(&mut computer, "A0 == 0x34 && a1 == 0x12"));
+        assert!(check(&mut computer, "hl == 0xAB_CDEF && h == 0xAB"));
+        assert!(check(&mut computer, "pc == label(main_loop)"));
+        assert!(check(&mut computer, "sp == 0x800000"));
+        assert!(check(&mut computer, "flag(Z) && !flag(C)"));
+        assert!(check(&mut computer, "!ei"));
+        assert!(check(&mut computer, "cycles >= 1000 && cycles < 1001"));
+        assert!(check(&mut computer, "[0x800010] == 4"));
+        assert!(check(&mut computer, "[0x800010].w == 0x0304"));
+        assert!(check(&mut computer, "[0x800010 + 2].w == 0x0102"));
+        assert!(check(&mut computer, "[0x800010].d == 0x0102_0304"));
+        assert!(check(&mut computer, "[[0x800010].b + 0x800008] == 0"));
+        assert!(!check(&mut computer, "0b10 & 0b01"));
+
+        // Inspecting memory has no side effects
+        assert!(check(&mut computer, "[0xFFFFFFFF] == 0"));
+        assert_eq!(computer.mmu.err_reg, 0);
+        assert_eq!(computer.mmu.interrupt_register, 0);
+    }
+
+    #[test]
+    fn test_precedence() {
+        let mut computer = Computer::new(false);
+        computer.cpu.set_reg(Reg16::A, 6);
+        computer.cpu.set_flag(Flag::Zero);
+
+        assert!(check(&mut computer, "a == 5 && a == 6 || flag(Z)"));
+        assert!(!check(&mut computer, "a == 5 && (a == 6 || flag(Z))"));
+        assert!(check(&mut computer, "a & 3 == 2"));
+        assert!(check(&mut computer, "a + 1 > 6 == 1"));
+        assert!(check(&mut computer, "(a | 1) ^ 1 == 6"));
+        assert!(check(&mut computer, "a - 7 == ~0"));
+        assert!(check(&mut computer, "!!a"));
+        assert!(check(&mut computer, "a>5&&a<=6"));
+    }
+
+    #[test]
+    fn test_errors() {
+        let parse = |source| BreakCondition::parse(source, None).unwrap_err();
+        assert_eq!(
+            parse("pc =="),
+            "Expected a value, found the end of the condition."
+        );
+        assert_eq!(parse("pc == )"), "Expected a value, found `)`.");
+        assert_eq!(parse("pc pc"), "Unexpected `pc`.");
+        assert_eq!(parse("(pc == 1"), "Expected `)`.");
+        assert_eq!(parse("x == 1"), "Unknown value `x`.");
+        assert_eq!(parse("0x1G == 1"), "Invalid number `0x1G`.");
+        assert_eq!(
+            parse("[pc].q"),
+            "Unknown width `.q`. Expected .b, .w or .d."
+        );
+        assert_eq!(
+            parse("flag(Q)"),
+            "Unknown flag `Q`. Expected Z, C, O, P or N."
+        );
+        assert_eq!(
+            parse("pc == label(main)"),
+            "Can't find label `main` without a symbol map."
+        );
+        assert_eq!(
+            BreakCondition::parse("pc == label(main)", Some(&SymbolMap::default())).unwrap_err(),
+            "Unknown label `main`."
+        );
+    }
+}
@@ -1,7 +1,10 @@
 use crate::{
     cpu::Cpu,
     drive::Drive,
-    keyboard::KbCode,
+    gamepad::{GamepadAxis, GamepadButton},
+    gpu::{OAM_SIZE, PATTERN_RAM_SIZE, TILE_MAP_SIZE},
+    input_log::{InputAction, InputLog},
+    keyboard::{KbCode, KbEvent, KbLayout},
     mmu::{Interrupt, Mmu},
     Addr,
 };
@@ -19,15 +22,23 @@ pub const RAM_SIZE: usize = 0x80_0000;
 pub const RAM_OFFSET: usize = ROM_SIZE;
 /// VRAM offset
 pub const VRAM_OFFSET: usize = RAM_SIZE + ROM_SIZE;
-/// Video RAM size in bytes
+/// Video RAM size in bytes. Not every video mode uses all of it.
 pub const VRAM_SIZE: usize = DISPLAY_WIDTH * DISPLAY_HEIGHT / 2;
+/// Pattern RAM offset. Address range: [0x0103_0000, 0x0103_FFFF]
+pub const PATTERN_RAM_OFFSET: usize = 0x0103_0000;
+/// Tile map offset. Address range: [0x0104_0000, 0x0104_3FFF]
+pub const TILE_MAP_OFFSET: usize = PATTERN_RAM_OFFSET + PATTERN_RAM_SIZE;
+/// Object attribute memory (OAM) offset. Address range: [0x0104_4000, 0x0104_41FF]
+pub const OAM_OFFSET: usize = TILE_MAP_OFFSET + TILE_MAP_SIZE;
+/// Palette RAM offset. Address range: [0x0104_4200, 0x0104_45FF]
+pub const PALETTE_RAM_OFFSET: usize = OAM_OFFSET + OAM_SIZE;
 
 /// Starting memory location of interrupt handlers
 pub const INTERRUPT_HANDLERS_OFFSET: usize = 0x0000_0100;
 
-/// Display height
+/// Display height of the default video mode. Also the number of visible lines in each frame.
 pub const DISPLAY_HEIGHT: usize = 480;
-/// Display width
+/// Display width of the default video mode.
 pub const DISPLAY_WIDTH: usize = 640;
 
 /// Drive block size
@@ -49,8 +60,11 @@ pub struct Computer {
     pub cycles: u128,
     /// Will print debug messages to stdout when true.
     pub debug: bool,
-    /// Will print keyboard debug messages to stdout when true.
+    /// Will print keyboard and gamepad debug messages to stdout when true.
     pub kb_debug: bool,
+    /// When set, every input given to the computer is recorded to this log along with the cycle
+    /// count at which it was given.
+    pub input_log: Option<InputLog>,
 }
 impl Computer {
     /// The system clock frequency in Hz.
@@ -114,6 +128,8 @@ impl Computer {
     /// Perform one clock cycle.
     pub fn cycle(&mut self) {
         self.mmu.cycle(&mut self.drives);
+        self.mmu.gpu.cycle();
+        self.mmu.interrupt_register |= self.mmu.gpu.consume_interrupts();
         self.cpu.cycle(&mut self.mmu);
         self.cycles += 1;
     }
@@ -126,10 +142,18 @@ impl Computer {
 
     /// Handle a pressed keyboard key.
     pub fn key_down<C: Into<u16> + Copy>(&mut self, code: C) {
+        self.record_input(InputAction::KeyDown(code.into()));
         if !self.mmu.kb_reg.key(code) {
             self.mmu.set_interrupt(Interrupt::Keyboard);
         }
         self.mmu.kb_reg.key_down(code);
+        self.push_kb_event(code.into(), true);
+        if let Some(kbc) = KbCode::try_from_u16(code.into()) {
+            let modifiers = self.mmu.kb_reg.modifiers();
+            if let Some(ch) = self.mmu.kb_layout.translate(kbc, modifiers) {
+                self.push_text_char(ch);
+            }
+        }
         if self.kb_debug {
             if let Some(kbc) = KbCode::try_from_u16(code.into()) {
                 println!("`{}` pressed", kbc);
@@ -137,9 +161,13 @@ impl Computer {
         }
     }
 
-    /// Handle a released keyboard key.
+    /// Handle a released keyboard key. The release is queued as a keyboard event, so it raises
+    /// the keyboard interrupt too.
     pub fn key_up<C: Into<u16> + Copy>(&mut self, code: C) {
+        self.record_input(InputAction::KeyUp(code.into()));
         self.mmu.kb_reg.key_up(code);
+        self.push_kb_event(code.into(), false);
+        self.mmu.set_interrupt(Interrupt::Keyboard);
         if self.kb_debug {
             if let Some(kbc) = KbCode::try_from_u16(code.into()) {
                 println!("`{}` released", kbc);
@@ -147,6 +175,83 @@ impl Computer {
         }
     }
 
+    /// Add a key press or release to the keyboard event queue.
+    fn push_kb_event(&mut self, code: u16, pressed: bool) {
+        let modifiers = self.mmu.kb_reg.modifiers();
+        self.mmu.kb_event_queue.push(KbEvent {
+            code,
+            pressed,
+            modifiers,
+        });
+    }
+
+    /// Handle text typed on the host. Characters outside of Latin-1 are dropped. The text is
+    /// ignored unless the keyboard layout is [KbLayout::Host]; other layouts translate key presses
+    /// instead.
+    pub fn text_input(&mut self, text: &str) {
+        for ch in text.chars() {
+            self.record_input(InputAction::Text(ch));
+        }
+        if self.mmu.kb_layout != KbLayout::Host {
+            return;
+        }
+        for ch in text.chars() {
+            if let Ok(byte) = u8::try_from(ch) {
+                self.push_text_char(byte);
+            }
+        }
+    }
+
+    /// Add a character to the text input queue.
+    fn push_text_char(&mut self, ch: u8) {
+        self.mmu.text_input_queue.push(ch);
+        self.mmu.set_interrupt(Interrupt::Keyboard);
+        if self.kb_debug {
+            println!("`{}` typed", ch as char);
+        }
+    }
+
+    /// Handle a pressed gamepad button.
+    pub fn button_down(&mut self, button: GamepadButton) {
+        self.record_input(InputAction::ButtonDown(button));
+        if !self.mmu.gamepad_reg.button(button) {
+            self.mmu.set_interrupt(Interrupt::Gamepad);
+        }
+        self.mmu.gamepad_reg.button_down(button);
+        if self.kb_debug {
+            println!("`{}` pressed", button);
+        }
+    }
+
+    /// Handle a released gamepad button.
+    pub fn button_up(&mut self, button: GamepadButton) {
+        self.record_input(InputAction::ButtonUp(button));
+        self.mmu.gamepad_reg.button_up(button);
+        if self.kb_debug {
+            println!("`{}` released", button);
+        }
+    }
+
+    /// Handle a moved gamepad axis.
+    pub fn axis_motion(&mut self, axis: GamepadAxis, value: i16) {
+        self.record_input(InputAction::AxisMotion(axis, value));
+        self.mmu.gamepad_reg.set_axis(axis, value);
+    }
+
+    /// Set the keyboard layout on behalf of the host, e.g. from its config. Programs set it
+    /// through the keyboard layout register instead.
+    pub fn set_kb_layout(&mut self, layout: KbLayout) {
+        self.record_input(InputAction::KbLayout(layout));
+        self.mmu.kb_layout = layout;
+    }
+
+    /// Add an input to the input log, if recording.
+    fn record_input(&mut self, action: InputAction) {
+        if let Some(input_log) = &mut self.input_log {
+            input_log.record(self.cycles, action);
+        }
+    }
+
     /// Find the [Drive] with the given drive number.
     pub fn find_drive(&self, drive_number: u8) -> Option<&Drive> {
         self.drives
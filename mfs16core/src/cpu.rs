@@ -17,7 +17,7 @@ pub use register::{Reg, Reg16, Reg32, Reg8};
 use crate::{
     computer::INTERRUPT_HANDLERS_OFFSET,
     mmu::{Mmu, IE_REGISTER_ADDR, INTERRUPT_REGISTER_ADDR},
-    Interrupt, RAM_OFFSET, RAM_SIZE, ROM_OFFSET,
+    Interrupt, SymbolMap, RAM_OFFSET, RAM_SIZE, ROM_OFFSET,
 };
 use register::Registers;
 
@@ -36,6 +36,8 @@ pub struct Cpu {
     pub sp: Addr,
     /// The current instruction.
     pub instr: Instruction,
+    /// The address of the current instruction.
+    pub instr_addr: u32,
     /// Step number within the current instruction.
     pub step_num: u32,
     /// If true, the CPU is halted and will not do anything until an interrupt.
@@ -108,8 +110,13 @@ impl Cpu {
             return false;
         }
 
-        let ie_register_val = mmu.read_byte(IE_REGISTER_ADDR as u32);
-        let interrupt_register_val = mmu.read_byte(INTERRUPT_REGISTER_ADDR as u32);
+        // Checking for interrupts isn't an access made by the program, so don't log it
+        let (ie_register_val, interrupt_register_val) = mmu.without_access_log(|mmu| {
+            (
+                mmu.read_byte(IE_REGISTER_ADDR as u32),
+                mmu.read_byte(INTERRUPT_REGISTER_ADDR as u32),
+            )
+        });
         let activated_interrupts = ie_register_val & interrupt_register_val;
         if activated_interrupts == 0 {
             return false;
@@ -127,10 +134,12 @@ impl Cpu {
         if self.debug {
             println!("INTERRUPT: {}", Interrupt::from_byte(offset as u8));
         }
-        mmu.write_byte(
-            INTERRUPT_REGISTER_ADDR as u32,
-            interrupt_register_val & !(1 << offset),
-        );
+        mmu.without_access_log(|mmu| {
+            mmu.write_byte(
+                INTERRUPT_REGISTER_ADDR as u32,
+                interrupt_register_val & !(1 << offset),
+            )
+        });
         self.push_stack(mmu, self.pc.address());
         self.pc = Addr::new_default_range(
             ((ROM_OFFSET + INTERRUPT_HANDLERS_OFFSET) + ((offset as usize) * 0x100)) as u32,
@@ -200,6 +209,7 @@ impl Cpu {
 
     /// Set the current instruction.
     fn read_opcode(&mut self, mmu: &mut Mmu) {
+        self.instr_addr = self.pc.address();
         self.read_next_word(mmu);
         self.instr = Instruction::from_opcode(self.last_word);
     }
@@ -250,6 +260,19 @@ impl Cpu {
     fn check_conditional(&mut self, flag: Flag, expected: bool) {
         self.last_conditional_satisfied = self.flag(flag) == expected;
     }
+
+    /// Format this [Cpu] the same way as its [Display] implementation, but with the program
+    /// counter shown relative to the nearest label of the given [SymbolMap].
+    pub fn to_string_with_symbols(&self, symbols: &SymbolMap) -> String {
+        format!(
+            "{:<10}|PC:{} SP:{}|{}|{}",
+            self.instr,
+            symbols.format_addr(self.pc.address()),
+            self.sp,
+            self.regs,
+            self.flags
+        )
+    }
 }
 impl Default for Cpu {
     /// Default: Stack pointer at top of stack. Everything else initialised to 0/false.
@@ -260,6 +283,7 @@ impl Default for Cpu {
             pc: Addr::default(),
             sp: Addr::new(RAM_OFFSET, RAM_SIZE - 1, RAM_OFFSET as u32),
             instr: Instruction::default(),
+            instr_addr: 0x0000_0000,
             step_num: Instruction::default().num_steps(),
             is_halted: false,
             is_stopped: false,
@@ -39,10 +39,11 @@ const NUM_BREGS: u8 = 3;
 // - [instruction.rs]           Add Instruction enum entry
 // - [instruction_helpers.rs]   Add match to step fn
 // - [instruction_helpers.rs]   Add step helper fn
-// - [instruction_impl.rs]      Impl from_opcode
+// - [instruction_impl.rs]      Impl try_from_opcode
 // - [instruction_impl.rs]      Impl into_opcode
 // - [instruction_impl.rs]      Impl num_steps
-// - [instruction_impl.rs]      Impl Display
+// - [instruction_impl.rs]      Add to imm_size if it takes an immediate
+// - [instruction_impl.rs]      Impl Display (must be valid mfs16asm)
 // - [tests]                    Add tests
 // - [instruction_parser.rs]    Add Operation if new operation name
 // - [instruction_parser.rs]    Add to instr_to_bytes
@@ -2,176 +2,188 @@ use super::*;
 use crate::Reg;
 
 impl Instruction {
-    /// Get the [Instruction] from the given opcode.
+    /// Get the [Instruction] from the given opcode, panicking if no instruction matches.
     pub fn from_opcode(opcode: u16) -> Self {
+        Self::try_from_opcode(opcode)
+            .unwrap_or_else(|| panic!("Opcode {:#04X} has no corresponding instruction.", opcode))
+    }
+
+    /// Get the [Instruction] from the given opcode, returning [None] if no instruction matches.
+    pub fn try_from_opcode(opcode: u16) -> Option<Self> {
         let nib_1 = (opcode >> 12) as u8;
         let nib_2 = ((opcode & 0x0F00) >> 8) as u8;
         let nib_3 = ((opcode & 0x00F0) >> 4) as u8;
         let nib_4 = (opcode & 0x000F) as u8;
 
-        match (nib_1, nib_2, nib_3, nib_4) {
+        Some(match (nib_1, nib_2, nib_3, nib_4) {
             (0x0, 0x0, _, _) => Nop,
             (0x0, 0x1, 0xA, 0x0) => LdSpImm32,
             (0x0, 0x1, 0xA, 0x1) => LdImm32Sp,
-            (0x0, 0x1, 0xB, bra) => LdSpBra(Reg::from_nib(bra)),
-            (0x0, 0x1, 0xC, bra) => LdBraSp(Reg::from_nib(bra)),
+            (0x0, 0x1, 0xB, bra) => LdSpBra(Reg::try_from_nib(bra)?),
+            (0x0, 0x1, 0xC, bra) => LdBraSp(Reg::try_from_nib(bra)?),
             (0x0, 0x1, ra, rb) if ra < NUM_REGS && rb < NUM_REGS => {
-                LdRaRb(Reg::from_nib(ra), Reg::from_nib(rb))
-            }
-            (0x0, 0x1, bra, brb) => {
-                LdBraBrb(Reg::from_nib(bra - NUM_REGS), Reg::from_nib(brb - NUM_REGS))
+                LdRaRb(Reg::try_from_nib(ra)?, Reg::try_from_nib(rb)?)
             }
-            (0x0, 0x2, vra, vrb) => LdVraVrb(Reg::from_nib(vra), Reg::from_nib(vrb)),
-            (0x0, 0x3, 0x0, ra) => LdRaImm16(Reg::from_nib(ra)),
-            (0x0, 0x3, 0x1, bra) => LdBraImm32(Reg::from_nib(bra)),
-            (0x0, 0x3, 0x2, vra) => LdVraImm8(Reg::from_nib(vra)),
-            (0x0, 0x3, 0x3, bra) => LdBraImm16(Reg::from_nib(bra)),
-            (0x0, 0x4, bra, rb) => LdBraRb(Reg::from_nib(bra), Reg::from_nib(rb)),
-            (0x0, 0x5, 0x7, ra) => LdrRaImm32(Reg::from_nib(ra)),
-            (0x0, 0x5, ra, brb) => LdRaBrb(Reg::from_nib(ra), Reg::from_nib(brb)),
-            (0x0, 0x6, bra, rb) => LdiBraRb(Reg::from_nib(bra), Reg::from_nib(rb)),
-            (0x0, 0x7, bra, rb) => LddBraRb(Reg::from_nib(bra), Reg::from_nib(rb)),
-            (0x0, 0x8, ra, brb) => LdiRaBrb(Reg::from_nib(ra), Reg::from_nib(brb)),
-            (0x0, 0x9, 0x7, bra) => LdiBraImm16(Reg::from_nib(bra)),
-            (0x0, 0x9, 0x8, bra) => LddBraImm16(Reg::from_nib(bra)),
-            (0x0, 0x9, 0x9, ra) => LdImm32Ra(Reg::from_nib(ra)),
-            (0x0, 0x9, 0xA, ra) => LdRaImm32(Reg::from_nib(ra)),
-            (0x0, 0x9, ra, brb) => LddRaBrb(Reg::from_nib(ra), Reg::from_nib(brb)),
-            (0x0, 0xC, 0x3, bra) => VldBraImm32(Reg::from_nib(bra)),
-            (0x0, 0xC, 0x4, bra) => VldiBraImm32(Reg::from_nib(bra)),
-            (0x0, 0xC, 0x5, bra) => VlddBraImm32(Reg::from_nib(bra)),
-            (0x0, 0xA, bra, brb) => VldBraBrb(Reg::from_nib(bra), Reg::from_nib(brb)),
-            (0x0, 0xB, bra, brb) => VldiBraBrb(Reg::from_nib(bra), Reg::from_nib(brb)),
-            (0x0, 0xC, bra, brb) => VlddBraBrb(Reg::from_nib(bra), Reg::from_nib(brb)),
+            (0x0, 0x1, bra, brb) => LdBraBrb(
+                Reg::try_from_nib(bra.wrapping_sub(NUM_REGS))?,
+                Reg::try_from_nib(brb.wrapping_sub(NUM_REGS))?,
+            ),
+            (0x0, 0x2, vra, vrb) => LdVraVrb(Reg::try_from_nib(vra)?, Reg::try_from_nib(vrb)?),
+            (0x0, 0x3, 0x0, ra) => LdRaImm16(Reg::try_from_nib(ra)?),
+            (0x0, 0x3, 0x1, bra) => LdBraImm32(Reg::try_from_nib(bra)?),
+            (0x0, 0x3, 0x2, vra) => LdVraImm8(Reg::try_from_nib(vra)?),
+            (0x0, 0x3, 0x3, bra) => LdBraImm16(Reg::try_from_nib(bra)?),
+            (0x0, 0x4, bra, rb) => LdBraRb(Reg::try_from_nib(bra)?, Reg::try_from_nib(rb)?),
+            (0x0, 0x5, 0x7, ra) => LdrRaImm32(Reg::try_from_nib(ra)?),
+            (0x0, 0x5, ra, brb) => LdRaBrb(Reg::try_from_nib(ra)?, Reg::try_from_nib(brb)?),
+            (0x0, 0x6, bra, rb) => LdiBraRb(Reg::try_from_nib(bra)?, Reg::try_from_nib(rb)?),
+            (0x0, 0x7, bra, rb) => LddBraRb(Reg::try_from_nib(bra)?, Reg::try_from_nib(rb)?),
+            (0x0, 0x8, ra, brb) => LdiRaBrb(Reg::try_from_nib(ra)?, Reg::try_from_nib(brb)?),
+            (0x0, 0x9, 0x7, bra) => LdiBraImm16(Reg::try_from_nib(bra)?),
+            (0x0, 0x9, 0x8, bra) => LddBraImm16(Reg::try_from_nib(bra)?),
+            (0x0, 0x9, 0x9, ra) => LdImm32Ra(Reg::try_from_nib(ra)?),
+            (0x0, 0x9, 0xA, ra) => LdRaImm32(Reg::try_from_nib(ra)?),
+            (0x0, 0x9, ra, brb) => LddRaBrb(Reg::try_from_nib(ra)?, Reg::try_from_nib(brb)?),
+            (0x0, 0xC, 0x3, bra) => VldBraImm32(Reg::try_from_nib(bra)?),
+            (0x0, 0xC, 0x4, bra) => VldiBraImm32(Reg::try_from_nib(bra)?),
+            (0x0, 0xC, 0x5, bra) => VlddBraImm32(Reg::try_from_nib(bra)?),
+            (0x0, 0xA, bra, brb) => VldBraBrb(Reg::try_from_nib(bra)?, Reg::try_from_nib(brb)?),
+            (0x0, 0xB, bra, brb) => VldiBraBrb(Reg::try_from_nib(bra)?, Reg::try_from_nib(brb)?),
+            (0x0, 0xC, bra, brb) => VlddBraBrb(Reg::try_from_nib(bra)?, Reg::try_from_nib(brb)?),
             (0x1, 0x0, ra, rb) if ra < NUM_REGS && rb < NUM_REGS => {
-                AddRaRb(Reg::from_nib(ra), Reg::from_nib(rb))
+                AddRaRb(Reg::try_from_nib(ra)?, Reg::try_from_nib(rb)?)
             }
-            (0x1, 0x0, bra, brb) => {
-                AddBraBrb(Reg::from_nib(bra - NUM_REGS), Reg::from_nib(brb - NUM_REGS))
-            }
-            (0x1, 0x1, vra, vrb) => AddVraVrb(Reg::from_nib(vra), Reg::from_nib(vrb)),
+            (0x1, 0x0, bra, brb) => AddBraBrb(
+                Reg::try_from_nib(bra.wrapping_sub(NUM_REGS))?,
+                Reg::try_from_nib(brb.wrapping_sub(NUM_REGS))?,
+            ),
+            (0x1, 0x1, vra, vrb) => AddVraVrb(Reg::try_from_nib(vra)?, Reg::try_from_nib(vrb)?),
             (0x1, 0x2, ra, rb) if ra < NUM_REGS && rb < NUM_REGS => {
-                AdcRaRb(Reg::from_nib(ra), Reg::from_nib(rb))
-            }
-            (0x1, 0x2, bra, brb) => {
-                AdcBraBrb(Reg::from_nib(bra - NUM_REGS), Reg::from_nib(brb - NUM_REGS))
+                AdcRaRb(Reg::try_from_nib(ra)?, Reg::try_from_nib(rb)?)
             }
-            (0x1, 0x3, vra, vrb) => AdcVraVrb(Reg::from_nib(vra), Reg::from_nib(vrb)),
+            (0x1, 0x2, bra, brb) => AdcBraBrb(
+                Reg::try_from_nib(bra.wrapping_sub(NUM_REGS))?,
+                Reg::try_from_nib(brb.wrapping_sub(NUM_REGS))?,
+            ),
+            (0x1, 0x3, vra, vrb) => AdcVraVrb(Reg::try_from_nib(vra)?, Reg::try_from_nib(vrb)?),
             (0x1, 0x4, ra, rb) if ra < NUM_REGS && rb < NUM_REGS => {
-                SubRaRb(Reg::from_nib(ra), Reg::from_nib(rb))
+                SubRaRb(Reg::try_from_nib(ra)?, Reg::try_from_nib(rb)?)
             }
-            (0x1, 0x4, bra, brb) => {
-                SubBraBrb(Reg::from_nib(bra - NUM_REGS), Reg::from_nib(brb - NUM_REGS))
-            }
-            (0x1, 0x5, vra, vrb) => SubVraVrb(Reg::from_nib(vra), Reg::from_nib(vrb)),
+            (0x1, 0x4, bra, brb) => SubBraBrb(
+                Reg::try_from_nib(bra.wrapping_sub(NUM_REGS))?,
+                Reg::try_from_nib(brb.wrapping_sub(NUM_REGS))?,
+            ),
+            (0x1, 0x5, vra, vrb) => SubVraVrb(Reg::try_from_nib(vra)?, Reg::try_from_nib(vrb)?),
             (0x1, 0x6, ra, rb) if ra < NUM_REGS && rb < NUM_REGS => {
-                SbbRaRb(Reg::from_nib(ra), Reg::from_nib(rb))
-            }
-            (0x1, 0x6, bra, brb) => {
-                SbbBraBrb(Reg::from_nib(bra - NUM_REGS), Reg::from_nib(brb - NUM_REGS))
+                SbbRaRb(Reg::try_from_nib(ra)?, Reg::try_from_nib(rb)?)
             }
-            (0x1, 0x7, vra, vrb) => SbbVraVrb(Reg::from_nib(vra), Reg::from_nib(vrb)),
-            (0x1, 0x8, 0x0, ra) => AddRaImm16(Reg::from_nib(ra)),
-            (0x1, 0x8, 0x1, ra) => AdcRaImm16(Reg::from_nib(ra)),
-            (0x1, 0x8, 0x2, bra) => AddBraImm32(Reg::from_nib(bra)),
-            (0x1, 0x8, 0x3, bra) => AdcBraImm32(Reg::from_nib(bra)),
-            (0x1, 0x8, 0x4, vra) => AddVraImm8(Reg::from_nib(vra)),
-            (0x1, 0x8, 0x5, vra) => AdcVraImm8(Reg::from_nib(vra)),
-            (0x1, 0x8, 0x6, ra) => SubRaImm16(Reg::from_nib(ra)),
-            (0x1, 0x8, 0x7, ra) => SbbRaImm16(Reg::from_nib(ra)),
-            (0x1, 0x8, 0x8, bra) => SubBraImm32(Reg::from_nib(bra)),
-            (0x1, 0x8, 0x9, bra) => SbbBraImm32(Reg::from_nib(bra)),
-            (0x1, 0x8, 0xA, vra) => SubVraImm8(Reg::from_nib(vra)),
-            (0x1, 0x8, 0xB, vra) => SbbVraImm8(Reg::from_nib(vra)),
-            (0x1, 0x9, ra, brb) => AddRaBrb(Reg::from_nib(ra), Reg::from_nib(brb)),
-            (0x1, 0xA, ra, brb) => AdcRaBrb(Reg::from_nib(ra), Reg::from_nib(brb)),
-            (0x1, 0xB, ra, brb) => SubRaBrb(Reg::from_nib(ra), Reg::from_nib(brb)),
-            (0x1, 0xC, ra, brb) => SbbRaBrb(Reg::from_nib(ra), Reg::from_nib(brb)),
-            (0x1, 0xD, 0x0, ra) => TcpRa(Reg::from_nib(ra)),
-            (0x1, 0xD, 0x1, bra) => TcpBra(Reg::from_nib(bra)),
-            (0x1, 0xD, 0x2, vra) => TcpVra(Reg::from_nib(vra)),
-            (0x1, 0xD, 0x3, ra) => IncRa(Reg::from_nib(ra)),
-            (0x1, 0xD, 0x4, bra) => IncBra(Reg::from_nib(bra)),
-            (0x1, 0xD, 0x5, vra) => IncVra(Reg::from_nib(vra)),
-            (0x1, 0xD, 0x6, ra) => DecRa(Reg::from_nib(ra)),
-            (0x1, 0xD, 0x7, bra) => DecBra(Reg::from_nib(bra)),
-            (0x1, 0xD, 0x8, vra) => DecVra(Reg::from_nib(vra)),
-            (0x1, 0xD, 0x9, ra) => PssRa(Reg::from_nib(ra)),
-            (0x1, 0xD, 0xA, bra) => PssBra(Reg::from_nib(bra)),
-            (0x1, 0xD, 0xB, vra) => PssVra(Reg::from_nib(vra)),
+            (0x1, 0x6, bra, brb) => SbbBraBrb(
+                Reg::try_from_nib(bra.wrapping_sub(NUM_REGS))?,
+                Reg::try_from_nib(brb.wrapping_sub(NUM_REGS))?,
+            ),
+            (0x1, 0x7, vra, vrb) => SbbVraVrb(Reg::try_from_nib(vra)?, Reg::try_from_nib(vrb)?),
+            (0x1, 0x8, 0x0, ra) => AddRaImm16(Reg::try_from_nib(ra)?),
+            (0x1, 0x8, 0x1, ra) => AdcRaImm16(Reg::try_from_nib(ra)?),
+            (0x1, 0x8, 0x2, bra) => AddBraImm32(Reg::try_from_nib(bra)?),
+            (0x1, 0x8, 0x3, bra) => AdcBraImm32(Reg::try_from_nib(bra)?),
+            (0x1, 0x8, 0x4, vra) => AddVraImm8(Reg::try_from_nib(vra)?),
+            (0x1, 0x8, 0x5, vra) => AdcVraImm8(Reg::try_from_nib(vra)?),
+            (0x1, 0x8, 0x6, ra) => SubRaImm16(Reg::try_from_nib(ra)?),
+            (0x1, 0x8, 0x7, ra) => SbbRaImm16(Reg::try_from_nib(ra)?),
+            (0x1, 0x8, 0x8, bra) => SubBraImm32(Reg::try_from_nib(bra)?),
+            (0x1, 0x8, 0x9, bra) => SbbBraImm32(Reg::try_from_nib(bra)?),
+            (0x1, 0x8, 0xA, vra) => SubVraImm8(Reg::try_from_nib(vra)?),
+            (0x1, 0x8, 0xB, vra) => SbbVraImm8(Reg::try_from_nib(vra)?),
+            (0x1, 0x9, ra, brb) => AddRaBrb(Reg::try_from_nib(ra)?, Reg::try_from_nib(brb)?),
+            (0x1, 0xA, ra, brb) => AdcRaBrb(Reg::try_from_nib(ra)?, Reg::try_from_nib(brb)?),
+            (0x1, 0xB, ra, brb) => SubRaBrb(Reg::try_from_nib(ra)?, Reg::try_from_nib(brb)?),
+            (0x1, 0xC, ra, brb) => SbbRaBrb(Reg::try_from_nib(ra)?, Reg::try_from_nib(brb)?),
+            (0x1, 0xD, 0x0, ra) => TcpRa(Reg::try_from_nib(ra)?),
+            (0x1, 0xD, 0x1, bra) => TcpBra(Reg::try_from_nib(bra)?),
+            (0x1, 0xD, 0x2, vra) => TcpVra(Reg::try_from_nib(vra)?),
+            (0x1, 0xD, 0x3, ra) => IncRa(Reg::try_from_nib(ra)?),
+            (0x1, 0xD, 0x4, bra) => IncBra(Reg::try_from_nib(bra)?),
+            (0x1, 0xD, 0x5, vra) => IncVra(Reg::try_from_nib(vra)?),
+            (0x1, 0xD, 0x6, ra) => DecRa(Reg::try_from_nib(ra)?),
+            (0x1, 0xD, 0x7, bra) => DecBra(Reg::try_from_nib(bra)?),
+            (0x1, 0xD, 0x8, vra) => DecVra(Reg::try_from_nib(vra)?),
+            (0x1, 0xD, 0x9, ra) => PssRa(Reg::try_from_nib(ra)?),
+            (0x1, 0xD, 0xA, bra) => PssBra(Reg::try_from_nib(bra)?),
+            (0x1, 0xD, 0xB, vra) => PssVra(Reg::try_from_nib(vra)?),
             (0x1, 0xD, 0xC, 0x0) => PssImm16,
             (0x1, 0xD, 0xC, 0x1) => PssImm32,
             (0x1, 0xD, 0xC, 0x2) => PssImm8,
-            (0x1, 0xE, ra, rb) => AndRaRb(Reg::from_nib(ra), Reg::from_nib(rb)),
-            (0x1, 0xF, bra, brb) => AndBraBrb(Reg::from_nib(bra), Reg::from_nib(brb)),
-            (0x2, 0x0, vra, vrb) => AndVraVrb(Reg::from_nib(vra), Reg::from_nib(vrb)),
-            (0x2, 0x1, ra, brb) => AndRaBrb(Reg::from_nib(ra), Reg::from_nib(brb)),
-            (0x2, 0x2, ra, rb) => OrRaRb(Reg::from_nib(ra), Reg::from_nib(rb)),
-            (0x2, 0x3, bra, brb) => OrBraBrb(Reg::from_nib(bra), Reg::from_nib(brb)),
-            (0x2, 0x4, vra, vrb) => OrVraVrb(Reg::from_nib(vra), Reg::from_nib(vrb)),
-            (0x2, 0x5, ra, brb) => OrRaBrb(Reg::from_nib(ra), Reg::from_nib(brb)),
-            (0x2, 0x6, ra, rb) => XorRaRb(Reg::from_nib(ra), Reg::from_nib(rb)),
-            (0x2, 0x7, bra, brb) => XorBraBrb(Reg::from_nib(bra), Reg::from_nib(brb)),
-            (0x2, 0x8, vra, vrb) => XorVraVrb(Reg::from_nib(vra), Reg::from_nib(vrb)),
-            (0x2, 0x9, ra, brb) => XorRaBrb(Reg::from_nib(ra), Reg::from_nib(brb)),
-            (0x2, 0xA, 0x0, ra) => AndRaImm16(Reg::from_nib(ra)),
-            (0x2, 0xA, 0x1, bra) => AndBraImm32(Reg::from_nib(bra)),
-            (0x2, 0xA, 0x2, vra) => AndVraImm8(Reg::from_nib(vra)),
-            (0x2, 0xA, 0x3, ra) => OrRaImm16(Reg::from_nib(ra)),
-            (0x2, 0xA, 0x4, bra) => OrBraImm32(Reg::from_nib(bra)),
-            (0x2, 0xA, 0x5, vra) => OrVraImm8(Reg::from_nib(vra)),
-            (0x2, 0xA, 0x6, ra) => XorRaImm16(Reg::from_nib(ra)),
-            (0x2, 0xA, 0x7, bra) => XorBraImm32(Reg::from_nib(bra)),
-            (0x2, 0xA, 0x8, vra) => XorVraImm8(Reg::from_nib(vra)),
-            (0x2, 0xA, 0x9, ra) => NotRa(Reg::from_nib(ra)),
-            (0x2, 0xA, 0xA, bra) => NotBra(Reg::from_nib(bra)),
-            (0x2, 0xA, 0xB, vra) => NotVra(Reg::from_nib(vra)),
-            (0x2, 0xB, ra, b) => AsrRaB(Reg::from_nib(ra), b),
-            (0x2, 0xC, bra, b) => AsrBraB(Reg::from_nib(bra), b),
-            (0x2, 0xD, vra, b) => AsrVraB(Reg::from_nib(vra), b),
-            (0x2, 0xE, ra, b) => AslRaB(Reg::from_nib(ra), b),
-            (0x2, 0xF, bra, b) => AslBraB(Reg::from_nib(bra), b),
-            (0x3, 0x0, vra, b) => AslVraB(Reg::from_nib(vra), b),
-            (0x3, 0x1, ra, b) => LsrRaB(Reg::from_nib(ra), b),
-            (0x3, 0x2, bra, b) => LsrBraB(Reg::from_nib(bra), b),
-            (0x3, 0x3, vra, b) => LsrVraB(Reg::from_nib(vra), b),
-            (0x3, 0x4, ra, b) => RtrRaB(Reg::from_nib(ra), b),
-            (0x3, 0x5, bra, b) => RtrBraB(Reg::from_nib(bra), b),
-            (0x3, 0x6, vra, b) => RtrVraB(Reg::from_nib(vra), b),
-            (0x3, 0x7, ra, b) => RtlRaB(Reg::from_nib(ra), b),
-            (0x3, 0x8, bra, b) => RtlBraB(Reg::from_nib(bra), b),
-            (0x3, 0x9, vra, b) => RtlVraB(Reg::from_nib(vra), b),
-            (0x3, 0xA, ra, b) => RcrRaB(Reg::from_nib(ra), b),
-            (0x3, 0xB, bra, b) => RcrBraB(Reg::from_nib(bra), b),
-            (0x3, 0xC, vra, b) => RcrVraB(Reg::from_nib(vra), b),
-            (0x3, 0xD, ra, b) => RclRaB(Reg::from_nib(ra), b),
-            (0x3, 0xE, bra, b) => RclBraB(Reg::from_nib(bra), b),
-            (0x3, 0xF, vra, b) => RclVraB(Reg::from_nib(vra), b),
+            (0x1, 0xE, ra, rb) => AndRaRb(Reg::try_from_nib(ra)?, Reg::try_from_nib(rb)?),
+            (0x1, 0xF, bra, brb) => AndBraBrb(Reg::try_from_nib(bra)?, Reg::try_from_nib(brb)?),
+            (0x2, 0x0, vra, vrb) => AndVraVrb(Reg::try_from_nib(vra)?, Reg::try_from_nib(vrb)?),
+            (0x2, 0x1, ra, brb) => AndRaBrb(Reg::try_from_nib(ra)?, Reg::try_from_nib(brb)?),
+            (0x2, 0x2, ra, rb) => OrRaRb(Reg::try_from_nib(ra)?, Reg::try_from_nib(rb)?),
+            (0x2, 0x3, bra, brb) => OrBraBrb(Reg::try_from_nib(bra)?, Reg::try_from_nib(brb)?),
+            (0x2, 0x4, vra, vrb) => OrVraVrb(Reg::try_from_nib(vra)?, Reg::try_from_nib(vrb)?),
+            (0x2, 0x5, ra, brb) => OrRaBrb(Reg::try_from_nib(ra)?, Reg::try_from_nib(brb)?),
+            (0x2, 0x6, ra, rb) => XorRaRb(Reg::try_from_nib(ra)?, Reg::try_from_nib(rb)?),
+            (0x2, 0x7, bra, brb) => XorBraBrb(Reg::try_from_nib(bra)?, Reg::try_from_nib(brb)?),
+            (0x2, 0x8, vra, vrb) => XorVraVrb(Reg::try_from_nib(vra)?, Reg::try_from_nib(vrb)?),
+            (0x2, 0x9, ra, brb) => XorRaBrb(Reg::try_from_nib(ra)?, Reg::try_from_nib(brb)?),
+            (0x2, 0xA, 0x0, ra) => AndRaImm16(Reg::try_from_nib(ra)?),
+            (0x2, 0xA, 0x1, bra) => AndBraImm32(Reg::try_from_nib(bra)?),
+            (0x2, 0xA, 0x2, vra) => AndVraImm8(Reg::try_from_nib(vra)?),
+            (0x2, 0xA, 0x3, ra) => OrRaImm16(Reg::try_from_nib(ra)?),
+            (0x2, 0xA, 0x4, bra) => OrBraImm32(Reg::try_from_nib(bra)?),
+            (0x2, 0xA, 0x5, vra) => OrVraImm8(Reg::try_from_nib(vra)?),
+            (0x2, 0xA, 0x6, ra) => XorRaImm16(Reg::try_from_nib(ra)?),
+            (0x2, 0xA, 0x7, bra) => XorBraImm32(Reg::try_from_nib(bra)?),
+            (0x2, 0xA, 0x8, vra) => XorVraImm8(Reg::try_from_nib(vra)?),
+            (0x2, 0xA, 0x9, ra) => NotRa(Reg::try_from_nib(ra)?),
+            (0x2, 0xA, 0xA, bra) => NotBra(Reg::try_from_nib(bra)?),
+            (0x2, 0xA, 0xB, vra) => NotVra(Reg::try_from_nib(vra)?),
+            (0x2, 0xB, ra, b) => AsrRaB(Reg::try_from_nib(ra)?, b),
+            (0x2, 0xC, bra, b) => AsrBraB(Reg::try_from_nib(bra)?, b),
+            (0x2, 0xD, vra, b) => AsrVraB(Reg::try_from_nib(vra)?, b),
+            (0x2, 0xE, ra, b) => AslRaB(Reg::try_from_nib(ra)?, b),
+            (0x2, 0xF, bra, b) => AslBraB(Reg::try_from_nib(bra)?, b),
+            (0x3, 0x0, vra, b) => AslVraB(Reg::try_from_nib(vra)?, b),
+            (0x3, 0x1, ra, b) => LsrRaB(Reg::try_from_nib(ra)?, b),
+            (0x3, 0x2, bra, b) => LsrBraB(Reg::try_from_nib(bra)?, b),
+            (0x3, 0x3, vra, b) => LsrVraB(Reg::try_from_nib(vra)?, b),
+            (0x3, 0x4, ra, b) => RtrRaB(Reg::try_from_nib(ra)?, b),
+            (0x3, 0x5, bra, b) => RtrBraB(Reg::try_from_nib(bra)?, b),
+            (0x3, 0x6, vra, b) => RtrVraB(Reg::try_from_nib(vra)?, b),
+            (0x3, 0x7, ra, b) => RtlRaB(Reg::try_from_nib(ra)?, b),
+            (0x3, 0x8, bra, b) => RtlBraB(Reg::try_from_nib(bra)?, b),
+            (0x3, 0x9, vra, b) => RtlVraB(Reg::try_from_nib(vra)?, b),
+            (0x3, 0xA, ra, b) => RcrRaB(Reg::try_from_nib(ra)?, b),
+            (0x3, 0xB, bra, b) => RcrBraB(Reg::try_from_nib(bra)?, b),
+            (0x3, 0xC, vra, b) => RcrVraB(Reg::try_from_nib(vra)?, b),
+            (0x3, 0xD, ra, b) => RclRaB(Reg::try_from_nib(ra)?, b),
+            (0x3, 0xE, bra, b) => RclBraB(Reg::try_from_nib(bra)?, b),
+            (0x3, 0xF, vra, b) => RclVraB(Reg::try_from_nib(vra)?, b),
             (0x4, 0x0, ra, rb) if ra < NUM_REGS && rb < NUM_REGS => {
-                CmpRaRb(Reg::from_nib(ra), Reg::from_nib(rb))
-            }
-            (0x4, 0x0, bra, brb) => {
-                CmpBraBrb(Reg::from_nib(bra - NUM_REGS), Reg::from_nib(brb - NUM_REGS))
+                CmpRaRb(Reg::try_from_nib(ra)?, Reg::try_from_nib(rb)?)
             }
-            (0x4, 0x1, vra, vrb) => CmpVraVrb(Reg::from_nib(vra), Reg::from_nib(vrb)),
-            (0x4, 0x2, 0x0, ra) => CmpRaImm16(Reg::from_nib(ra)),
-            (0x4, 0x2, 0x1, bra) => CmpBraImm32(Reg::from_nib(bra)),
-            (0x4, 0x2, 0x2, vra) => CmpVraImm8(Reg::from_nib(vra)),
-            (0x4, 0x2, 0x3, ra) => CmpImm16Ra(Reg::from_nib(ra)),
-            (0x4, 0x2, 0x4, bra) => CmpImm32Bra(Reg::from_nib(bra)),
-            (0x4, 0x2, 0x5, vra) => CmpImm8Vra(Reg::from_nib(vra)),
-            (0x4, 0x3, ra, brb) => CmpRaBrb(Reg::from_nib(ra), Reg::from_nib(brb)),
-            (0x4, 0x4, bra, rb) => CmpBraRb(Reg::from_nib(bra), Reg::from_nib(rb)),
-            (0x4, 0x5, ra, b) => BitRaB(Reg::from_nib(ra), b),
-            (0x4, 0x6, bra, b) => BitBraB(Reg::from_nib(bra), b),
-            (0x4, 0x7, ra, b) => StbRaB(Reg::from_nib(ra), b),
-            (0x4, 0x8, bra, b) => StbBraB(Reg::from_nib(bra), b),
-            (0x4, 0x9, ra, b) => RsbRaB(Reg::from_nib(ra), b),
-            (0x4, 0xA, bra, b) => RsbBraB(Reg::from_nib(bra), b),
-            (0x4, 0xB, ra, b) => TgbRaB(Reg::from_nib(ra), b),
-            (0x4, 0xC, bra, b) => TgbBraB(Reg::from_nib(bra), b),
-            (0x4, 0xD, 0x0, ra) => SwpRa(Reg::from_nib(ra)),
-            (0x4, 0xD, 0x1, bra) => SwpBra(Reg::from_nib(bra)),
+            (0x4, 0x0, bra, brb) => CmpBraBrb(
+                Reg::try_from_nib(bra.wrapping_sub(NUM_REGS))?,
+                Reg::try_from_nib(brb.wrapping_sub(NUM_REGS))?,
+            ),
+            (0x4, 0x1, vra, vrb) => CmpVraVrb(Reg::try_from_nib(vra)?, Reg::try_from_nib(vrb)?),
+            (0x4, 0x2, 0x0, ra) => CmpRaImm16(Reg::try_from_nib(ra)?),
+            (0x4, 0x2, 0x1, bra) => CmpBraImm32(Reg::try_from_nib(bra)?),
+            (0x4, 0x2, 0x2, vra) => CmpVraImm8(Reg::try_from_nib(vra)?),
+            (0x4, 0x2, 0x3, ra) => CmpImm16Ra(Reg::try_from_nib(ra)?),
+            (0x4, 0x2, 0x4, bra) => CmpImm32Bra(Reg::try_from_nib(bra)?),
+            (0x4, 0x2, 0x5, vra) => CmpImm8Vra(Reg::try_from_nib(vra)?),
+            (0x4, 0x3, ra, brb) => CmpRaBrb(Reg::try_from_nib(ra)?, Reg::try_from_nib(brb)?),
+            (0x4, 0x4, bra, rb) => CmpBraRb(Reg::try_from_nib(bra)?, Reg::try_from_nib(rb)?),
+            (0x4, 0x5, ra, b) => BitRaB(Reg::try_from_nib(ra)?, b),
+            (0x4, 0x6, bra, b) => BitBraB(Reg::try_from_nib(bra)?, b),
+            (0x4, 0x7, ra, b) => StbRaB(Reg::try_from_nib(ra)?, b),
+            (0x4, 0x8, bra, b) => StbBraB(Reg::try_from_nib(bra)?, b),
+            (0x4, 0x9, ra, b) => RsbRaB(Reg::try_from_nib(ra)?, b),
+            (0x4, 0xA, bra, b) => RsbBraB(Reg::try_from_nib(bra)?, b),
+            (0x4, 0xB, ra, b) => TgbRaB(Reg::try_from_nib(ra)?, b),
+            (0x4, 0xC, bra, b) => TgbBraB(Reg::try_from_nib(bra)?, b),
+            (0x4, 0xD, 0x0, ra) => SwpRa(Reg::try_from_nib(ra)?),
+            (0x4, 0xD, 0x1, bra) => SwpBra(Reg::try_from_nib(bra)?),
             (0x4, 0xD, 0x2, 0x0) => Szf,
             (0x4, 0xD, 0x2, 0x1) => Rzf,
             (0x4, 0xD, 0x2, 0x2) => Tzf,
@@ -190,52 +202,56 @@ impl Instruction {
             (0x4, 0xD, 0x2, 0xF) => Saf,
             (0x4, 0xD, 0x3, 0x0) => Raf,
             (0x5, 0x0, ra, rb) if ra < NUM_REGS && rb < NUM_REGS => {
-                MuluRaRb(Reg::from_nib(ra), Reg::from_nib(rb))
+                MuluRaRb(Reg::try_from_nib(ra)?, Reg::try_from_nib(rb)?)
             }
             (0x5, 0x1, ra, rb) if ra < NUM_REGS && rb < NUM_REGS => {
-                MuliRaRb(Reg::from_nib(ra), Reg::from_nib(rb))
+                MuliRaRb(Reg::try_from_nib(ra)?, Reg::try_from_nib(rb)?)
             }
             (0x5, 0x2, ra, rb) if ra < NUM_REGS && rb < NUM_REGS => {
-                DivuRaRb(Reg::from_nib(ra), Reg::from_nib(rb))
+                DivuRaRb(Reg::try_from_nib(ra)?, Reg::try_from_nib(rb)?)
             }
             (0x5, 0x3, ra, rb) if ra < NUM_REGS && rb < NUM_REGS => {
-                DiviRaRb(Reg::from_nib(ra), Reg::from_nib(rb))
-            }
-            (0x5, 0x0, bra, brb) => {
-                MuluBraBrb(Reg::from_nib(bra - NUM_REGS), Reg::from_nib(brb - NUM_REGS))
-            }
-            (0x5, 0x1, bra, brb) => {
-                MuliBraBrb(Reg::from_nib(bra - NUM_REGS), Reg::from_nib(brb - NUM_REGS))
+                DiviRaRb(Reg::try_from_nib(ra)?, Reg::try_from_nib(rb)?)
             }
-            (0x5, 0x2, bra, brb) => {
-                DivuBraBrb(Reg::from_nib(bra - NUM_REGS), Reg::from_nib(brb - NUM_REGS))
-            }
-            (0x5, 0x3, bra, brb) => {
-                DiviBraBrb(Reg::from_nib(bra - NUM_REGS), Reg::from_nib(brb - NUM_REGS))
-            }
-            (0x5, 0x4, vra, vrb) => MuluVraVrb(Reg::from_nib(vra), Reg::from_nib(vrb)),
-            (0x5, 0x5, vra, vrb) => MuliVraVrb(Reg::from_nib(vra), Reg::from_nib(vrb)),
-            (0x5, 0x6, vra, vrb) => DivuVraVrb(Reg::from_nib(vra), Reg::from_nib(vrb)),
-            (0x5, 0x7, vra, vrb) => DiviVraVrb(Reg::from_nib(vra), Reg::from_nib(vrb)),
-            (0x5, 0x8, ra, brb) => MuluRaBrb(Reg::from_nib(ra), Reg::from_nib(brb)),
-            (0x5, 0x9, ra, brb) => MuliRaBrb(Reg::from_nib(ra), Reg::from_nib(brb)),
-            (0x5, 0xA, ra, brb) => DivuRaBrb(Reg::from_nib(ra), Reg::from_nib(brb)),
-            (0x5, 0xB, ra, brb) => DiviRaBrb(Reg::from_nib(ra), Reg::from_nib(brb)),
-            (0x5, 0xC, 0x0, ra) => MuluRaImm16(Reg::from_nib(ra)),
-            (0x5, 0xC, 0x1, ra) => MuliRaImm16(Reg::from_nib(ra)),
-            (0x5, 0xC, 0x2, ra) => DivuRaImm16(Reg::from_nib(ra)),
-            (0x5, 0xC, 0x3, ra) => DiviRaImm16(Reg::from_nib(ra)),
-            (0x5, 0xC, 0x4, bra) => MuluBraImm32(Reg::from_nib(bra)),
-            (0x5, 0xC, 0x5, bra) => MuliBraImm32(Reg::from_nib(bra)),
-            (0x5, 0xC, 0x6, bra) => DivuBraImm32(Reg::from_nib(bra)),
-            (0x5, 0xC, 0x7, bra) => DiviBraImm32(Reg::from_nib(bra)),
-            (0x5, 0xC, 0x8, vra) => MuluVraImm8(Reg::from_nib(vra)),
-            (0x5, 0xC, 0x9, vra) => MuliVraImm8(Reg::from_nib(vra)),
-            (0x5, 0xC, 0xA, vra) => DivuVraImm8(Reg::from_nib(vra)),
-            (0x5, 0xC, 0xB, vra) => DiviVraImm8(Reg::from_nib(vra)),
-            (0x6, 0x0, 0x0, ra) => RandRa(Reg::from_nib(ra)),
-            (0x6, 0x0, 0x1, bra) => RandBra(Reg::from_nib(bra)),
-            (0x6, 0x0, 0x2, vra) => RandVra(Reg::from_nib(vra)),
+            (0x5, 0x0, bra, brb) => MuluBraBrb(
+                Reg::try_from_nib(bra.wrapping_sub(NUM_REGS))?,
+                Reg::try_from_nib(brb.wrapping_sub(NUM_REGS))?,
+            ),
+            (0x5, 0x1, bra, brb) => MuliBraBrb(
+                Reg::try_from_nib(bra.wrapping_sub(NUM_REGS))?,
+                Reg::try_from_nib(brb.wrapping_sub(NUM_REGS))?,
+            ),
+            (0x5, 0x2, bra, brb) => DivuBraBrb(
+                Reg::try_from_nib(bra.wrapping_sub(NUM_REGS))?,
+                Reg::try_from_nib(brb.wrapping_sub(NUM_REGS))?,
+            ),
+            (0x5, 0x3, bra, brb) => DiviBraBrb(
+                Reg::try_from_nib(bra.wrapping_sub(NUM_REGS))?,
+                Reg::try_from_nib(brb.wrapping_sub(NUM_REGS))?,
+            ),
+            (0x5, 0x4, vra, vrb) => MuluVraVrb(Reg::try_from_nib(vra)?, Reg::try_from_nib(vrb)?),
+            (0x5, 0x5, vra, vrb) => MuliVraVrb(Reg::try_from_nib(vra)?, Reg::try_from_nib(vrb)?),
+            (0x5, 0x6, vra, vrb) => DivuVraVrb(Reg::try_from_nib(vra)?, Reg::try_from_nib(vrb)?),
+            (0x5, 0x7, vra, vrb) => DiviVraVrb(Reg::try_from_nib(vra)?, Reg::try_from_nib(vrb)?),
+            (0x5, 0x8, ra, brb) => MuluRaBrb(Reg::try_from_nib(ra)?, Reg::try_from_nib(brb)?),
+            (0x5, 0x9, ra, brb) => MuliRaBrb(Reg::try_from_nib(ra)?, Reg::try_from_nib(brb)?),
+            (0x5, 0xA, ra, brb) => DivuRaBrb(Reg::try_from_nib(ra)?, Reg::try_from_nib(brb)?),
+            (0x5, 0xB, ra, brb) => DiviRaBrb(Reg::try_from_nib(ra)?, Reg::try_from_nib(brb)?),
+            (0x5, 0xC, 0x0, ra) => MuluRaImm16(Reg::try_from_nib(ra)?),
+            (0x5, 0xC, 0x1, ra) => MuliRaImm16(Reg::try_from_nib(ra)?),
+            (0x5, 0xC, 0x2, ra) => DivuRaImm16(Reg::try_from_nib(ra)?),
+            (0x5, 0xC, 0x3, ra) => DiviRaImm16(Reg::try_from_nib(ra)?),
+            (0x5, 0xC, 0x4, bra) => MuluBraImm32(Reg::try_from_nib(bra)?),
+            (0x5, 0xC, 0x5, bra) => MuliBraImm32(Reg::try_from_nib(bra)?),
+            (0x5, 0xC, 0x6, bra) => DivuBraImm32(Reg::try_from_nib(bra)?),
+            (0x5, 0xC, 0x7, bra) => DiviBraImm32(Reg::try_from_nib(bra)?),
+            (0x5, 0xC, 0x8, vra) => MuluVraImm8(Reg::try_from_nib(vra)?),
+            (0x5, 0xC, 0x9, vra) => MuliVraImm8(Reg::try_from_nib(vra)?),
+            (0x5, 0xC, 0xA, vra) => DivuVraImm8(Reg::try_from_nib(vra)?),
+            (0x5, 0xC, 0xB, vra) => DiviVraImm8(Reg::try_from_nib(vra)?),
+            (0x6, 0x0, 0x0, ra) => RandRa(Reg::try_from_nib(ra)?),
+            (0x6, 0x0, 0x1, bra) => RandBra(Reg::try_from_nib(bra)?),
+            (0x6, 0x0, 0x2, vra) => RandVra(Reg::try_from_nib(vra)?),
             (0x8, 0x0, 0x0, 0x0) => JpImm32,
             (0x8, 0x0, 0x0, 0x1) => JrImm32,
             (0x8, 0x0, 0x0, 0x2) => JpzImm32,
@@ -248,18 +264,18 @@ impl Instruction {
             (0x8, 0x0, 0x0, 0x9) => JnpImm32,
             (0x8, 0x0, 0x0, 0xA) => JpnImm32,
             (0x8, 0x0, 0x0, 0xB) => JnnImm32,
-            (0x8, 0x0, 0x1, bra) => JpBra(Reg::from_nib(bra)),
-            (0x8, 0x0, 0x2, bra) => JrBra(Reg::from_nib(bra)),
-            (0x8, 0x0, 0x3, bra) => JpzBra(Reg::from_nib(bra)),
-            (0x8, 0x0, 0x4, bra) => JnzBra(Reg::from_nib(bra)),
-            (0x8, 0x0, 0x5, bra) => JpcBra(Reg::from_nib(bra)),
-            (0x8, 0x0, 0x6, bra) => JncBra(Reg::from_nib(bra)),
-            (0x8, 0x0, 0x7, bra) => JpoBra(Reg::from_nib(bra)),
-            (0x8, 0x0, 0x8, bra) => JnoBra(Reg::from_nib(bra)),
-            (0x8, 0x0, 0x9, bra) => JppBra(Reg::from_nib(bra)),
-            (0x8, 0x0, 0xA, bra) => JnpBra(Reg::from_nib(bra)),
-            (0x8, 0x0, 0xB, bra) => JpnBra(Reg::from_nib(bra)),
-            (0x8, 0x0, 0xC, bra) => JnnBra(Reg::from_nib(bra)),
+            (0x8, 0x0, 0x1, bra) => JpBra(Reg::try_from_nib(bra)?),
+            (0x8, 0x0, 0x2, bra) => JrBra(Reg::try_from_nib(bra)?),
+            (0x8, 0x0, 0x3, bra) => JpzBra(Reg::try_from_nib(bra)?),
+            (0x8, 0x0, 0x4, bra) => JnzBra(Reg::try_from_nib(bra)?),
+            (0x8, 0x0, 0x5, bra) => JpcBra(Reg::try_from_nib(bra)?),
+            (0x8, 0x0, 0x6, bra) => JncBra(Reg::try_from_nib(bra)?),
+            (0x8, 0x0, 0x7, bra) => JpoBra(Reg::try_from_nib(bra)?),
+            (0x8, 0x0, 0x8, bra) => JnoBra(Reg::try_from_nib(bra)?),
+            (0x8, 0x0, 0x9, bra) => JppBra(Reg::try_from_nib(bra)?),
+            (0x8, 0x0, 0xA, bra) => JnpBra(Reg::try_from_nib(bra)?),
+            (0x8, 0x0, 0xB, bra) => JpnBra(Reg::try_from_nib(bra)?),
+            (0x8, 0x0, 0xC, bra) => JnnBra(Reg::try_from_nib(bra)?),
             (0x8, 0x1, 0x0, 0x0) => CallImm32,
             (0x8, 0x1, 0x0, 0x1) => ClzImm32,
             (0x8, 0x1, 0x0, 0x2) => CnzImm32,
@@ -283,28 +299,30 @@ impl Instruction {
             (0x8, 0x1, 0x1, 0xC) => Rtn,
             (0x8, 0x1, 0x1, 0xD) => Rnn,
             (0x8, 0x1, 0x1, 0xE) => Reti,
-            (0x8, 0x1, 0x1, bra) => CallBra(Reg::from_nib(bra)),
-            (0x8, 0x1, 0x2, bra) => ClzBra(Reg::from_nib(bra)),
-            (0x8, 0x1, 0x3, bra) => CnzBra(Reg::from_nib(bra)),
-            (0x8, 0x1, 0x4, bra) => ClcBra(Reg::from_nib(bra)),
-            (0x8, 0x1, 0x5, bra) => CncBra(Reg::from_nib(bra)),
-            (0x8, 0x1, 0x6, bra) => CloBra(Reg::from_nib(bra)),
-            (0x8, 0x1, 0x7, bra) => CnoBra(Reg::from_nib(bra)),
-            (0x8, 0x1, 0x8, bra) => ClpBra(Reg::from_nib(bra)),
-            (0x8, 0x1, 0x9, bra) => CnpBra(Reg::from_nib(bra)),
-            (0x8, 0x1, 0xA, bra) => ClnBra(Reg::from_nib(bra)),
-            (0x8, 0x1, 0xB, bra) => CnnBra(Reg::from_nib(bra)),
+            (0x8, 0x1, 0x1, bra) => CallBra(Reg::try_from_nib(bra)?),
+            (0x8, 0x1, 0x2, bra) => ClzBra(Reg::try_from_nib(bra)?),
+            (0x8, 0x1, 0x3, bra) => CnzBra(Reg::try_from_nib(bra)?),
+            (0x8, 0x1, 0x4, bra) => ClcBra(Reg::try_from_nib(bra)?),
+            (0x8, 0x1, 0x5, bra) => CncBra(Reg::try_from_nib(bra)?),
+            (0x8, 0x1, 0x6, bra) => CloBra(Reg::try_from_nib(bra)?),
+            (0x8, 0x1, 0x7, bra) => CnoBra(Reg::try_from_nib(bra)?),
+            (0x8, 0x1, 0x8, bra) => ClpBra(Reg::try_from_nib(bra)?),
+            (0x8, 0x1, 0x9, bra) => CnpBra(Reg::try_from_nib(bra)?),
+            (0x8, 0x1, 0xA, bra) => ClnBra(Reg::try_from_nib(bra)?),
+            (0x8, 0x1, 0xB, bra) => CnnBra(Reg::try_from_nib(bra)?),
             (0x8, 0x2, 0x0, 0x9) => PushImm32,
-            (0x8, 0x2, 0x0, bra) if bra < NUM_BREGS => PushBra(Reg::from_nib(bra)),
-            (0x8, 0x2, 0x0, bra) if bra < (NUM_BREGS * 2) => PopBra(Reg::from_nib(bra - NUM_BREGS)),
-            (0x8, 0x2, 0x0, bra) => PeekBra(Reg::from_nib(bra - (NUM_BREGS * 2))),
+            (0x8, 0x2, 0x0, bra) if bra < NUM_BREGS => PushBra(Reg::try_from_nib(bra)?),
+            (0x8, 0x2, 0x0, bra) if bra < (NUM_BREGS * 2) => {
+                PopBra(Reg::try_from_nib(bra - NUM_BREGS)?)
+            }
+            (0x8, 0x2, 0x0, bra) => PeekBra(Reg::try_from_nib(bra - (NUM_BREGS * 2))?),
             (0xF, 0xF, 0xF, 0xB) => Clv,
             (0xF, 0xF, 0xF, 0xC) => Stop,
             (0xF, 0xF, 0xF, 0xD) => Ei,
             (0xF, 0xF, 0xF, 0xE) => Di,
             (0xF, 0xF, 0xF, 0xF) => Halt,
-            _ => panic!("Opcode {:#04X} has no corresponding instruction.", opcode),
-        }
+            _ => return None,
+        })
     }
 
     /// Convert the [Instruction] into its opcode.
@@ -824,6 +842,31 @@ impl Instruction {
             Halt => 2,
         }
     }
+
+    /// Get the size of the immediate value following this [Instruction]'s opcode, in bytes.
+    pub fn imm_size(&self) -> usize {
+        match self {
+            PssImm8 | LdVraImm8(_) | AddVraImm8(_) | AdcVraImm8(_) | SubVraImm8(_)
+            | SbbVraImm8(_) | AndVraImm8(_) | OrVraImm8(_) | XorVraImm8(_) | CmpVraImm8(_)
+            | CmpImm8Vra(_) | MuluVraImm8(_) | MuliVraImm8(_) | DivuVraImm8(_) | DiviVraImm8(_) => {
+                1
+            }
+            PssImm16 | LdRaImm16(_) | LdBraImm16(_) | LdiBraImm16(_) | LddBraImm16(_)
+            | AddRaImm16(_) | AdcRaImm16(_) | SubRaImm16(_) | SbbRaImm16(_) | AndRaImm16(_)
+            | OrRaImm16(_) | XorRaImm16(_) | CmpRaImm16(_) | CmpImm16Ra(_) | MuluRaImm16(_)
+            | MuliRaImm16(_) | DivuRaImm16(_) | DiviRaImm16(_) => 2,
+            LdSpImm32 | LdImm32Sp | PssImm32 | JpImm32 | JrImm32 | JpzImm32 | JnzImm32
+            | JpcImm32 | JncImm32 | JpoImm32 | JnoImm32 | JppImm32 | JnpImm32 | JpnImm32
+            | JnnImm32 | CallImm32 | ClzImm32 | CnzImm32 | ClcImm32 | CncImm32 | CloImm32
+            | CnoImm32 | ClpImm32 | CnpImm32 | ClnImm32 | CnnImm32 | PushImm32 | LdBraImm32(_)
+            | LdrRaImm32(_) | LdImm32Ra(_) | LdRaImm32(_) | VldBraImm32(_) | VldiBraImm32(_)
+            | VlddBraImm32(_) | AddBraImm32(_) | AdcBraImm32(_) | SubBraImm32(_)
+            | SbbBraImm32(_) | AndBraImm32(_) | OrBraImm32(_) | XorBraImm32(_) | CmpBraImm32(_)
+            | CmpImm32Bra(_) | MuluBraImm32(_) | MuliBraImm32(_) | DivuBraImm32(_)
+            | DiviBraImm32(_) => 4,
+            _ => 0,
+        }
+    }
 }
 impl Display for Instruction {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -850,16 +893,16 @@ impl Display for Instruction {
                 LddBraRb(bra, rb) => format!("LDD [{bra}],{rb}"),
                 LdiRaBrb(ra, brb) => format!("LDI {ra},[{brb}]"),
                 LddRaBrb(ra, brb) => format!("LDD {ra},[{brb}]"),
-                LdiBraImm16(bra) => format!("LDI {bra},imm16"),
-                LddBraImm16(bra) => format!("LDD {bra},imm16"),
+                LdiBraImm16(bra) => format!("LDI [{bra}],imm16"),
+                LddBraImm16(bra) => format!("LDD [{bra}],imm16"),
                 LdImm32Ra(ra) => format!("LD [imm32],{ra}"),
                 LdRaImm32(ra) => format!("LD {ra},[imm32]"),
                 VldBraBrb(bra, brb) => format!("VLD [{bra}],{brb}"),
-                VldiBraBrb(bra, brb) => format!("VLD [{bra}],{brb}"),
-                VlddBraBrb(bra, brb) => format!("VLD [{bra}],{brb}"),
+                VldiBraBrb(bra, brb) => format!("VLDI [{bra}],{brb}"),
+                VlddBraBrb(bra, brb) => format!("VLDD [{bra}],{brb}"),
                 VldBraImm32(bra) => format!("VLD [{bra}],imm32"),
-                VldiBraImm32(bra) => format!("VLD [{bra}],imm32"),
-                VlddBraImm32(bra) => format!("VLD [{bra}],imm32"),
+                VldiBraImm32(bra) => format!("VLDI [{bra}],imm32"),
+                VlddBraImm32(bra) => format!("VLDD [{bra}],imm32"),
                 AddRaRb(ra, rb) => format!("ADD {ra},{rb}"),
                 AddBraBrb(bra, brb) => format!("ADD {bra},{brb}"),
                 AddVraVrb(vra, vrb) => format!("ADD {vra},{vrb}"),
@@ -884,10 +927,10 @@ impl Display for Instruction {
                 SbbBraImm32(bra) => format!("SBB {bra},imm32"),
                 SubVraImm8(vra) => format!("SUB {vra},imm8"),
                 SbbVraImm8(vra) => format!("SBB {vra},imm8"),
-                AddRaBrb(ra, brb) => format!("ADD {ra}[{brb}]"),
-                AdcRaBrb(ra, brb) => format!("ADC {ra}[{brb}]"),
-                SubRaBrb(ra, brb) => format!("SUB {ra}[{brb}]"),
-                SbbRaBrb(ra, brb) => format!("SBB {ra}[{brb}]"),
+                AddRaBrb(ra, brb) => format!("ADD {ra},[{brb}]"),
+                AdcRaBrb(ra, brb) => format!("ADC {ra},[{brb}]"),
+                SubRaBrb(ra, brb) => format!("SUB {ra},[{brb}]"),
+                SbbRaBrb(ra, brb) => format!("SBB {ra},[{brb}]"),
                 TcpRa(ra) => format!("TCP {ra}"),
                 TcpBra(bra) => format!("TCP {bra}"),
                 TcpVra(vra) => format!("TCP {vra}"),
@@ -8,3 +8,35 @@ fn test_into_from_opcode() {
         assert_eq!(instr, Instruction::from_opcode(instr.into_opcode()));
     }
 }
+
+#[test]
+fn test_try_from_opcode() {
+    assert_eq!(Instruction::try_from_opcode(0x1D05), Some(TcpRa(Reg16::H)));
+    assert_eq!(Instruction::try_from_opcode(0x1D20), Some(TcpVra(Reg8::A1)));
+    // There is no 16-bit register 7
+    assert_eq!(Instruction::try_from_opcode(0x1D07), None);
+    assert_eq!(Instruction::try_from_opcode(0x7000), None);
+    // Big register pairs must both be big registers
+    assert_eq!(Instruction::try_from_opcode(0x1039), None);
+
+    for opcode in 0..=u16::MAX {
+        Instruction::try_from_opcode(opcode);
+    }
+}
+
+#[test]
+fn test_imm_size() {
+    for instr in Instruction::iter() {
+        let text = instr.to_string();
+        let expected = if text.contains("imm8") {
+            1
+        } else if text.contains("imm16") {
+            2
+        } else if text.contains("imm32") {
+            4
+        } else {
+            0
+        };
+        assert_eq!(instr.imm_size(), expected, "{instr:?}");
+    }
+}
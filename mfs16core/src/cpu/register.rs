@@ -30,19 +30,19 @@ pub enum Reg16 {
     L,
 }
 impl Reg16 {
-    /// Get the [Reg16] corresponding to the given nibble, panicking if the nibble does not
+    /// Get the [Reg16] corresponding to the given nibble, returning [None] if the nibble does not
     /// correspond to any variant.
     #[inline(always)]
-    fn from_nib(nibble: u8) -> Reg16 {
+    fn try_from_nib(nibble: u8) -> Option<Reg16> {
         match nibble {
-            0x0 => A,
-            0x1 => B,
-            0x2 => C,
-            0x3 => D,
-            0x4 => E,
-            0x5 => H,
-            0x6 => L,
-            _ => panic!("Nibble {:#04X} does not match any 16-bit register.", nibble),
+            0x0 => Some(A),
+            0x1 => Some(B),
+            0x2 => Some(C),
+            0x3 => Some(D),
+            0x4 => Some(E),
+            0x5 => Some(H),
+            0x6 => Some(L),
+            _ => None,
         }
     }
 }
@@ -93,15 +93,15 @@ pub enum Reg32 {
     HL,
 }
 impl Reg32 {
-    /// Get the [Reg32] corresponding to the given nibble, panicking if the nibble does not
+    /// Get the [Reg32] corresponding to the given nibble, returning [None] if the nibble does not
     /// correspond to any variant.
     #[inline(always)]
-    fn from_nib(nibble: u8) -> Reg32 {
+    fn try_from_nib(nibble: u8) -> Option<Reg32> {
         match nibble {
-            0x0 => BC,
-            0x1 => DE,
-            0x2 => HL,
-            _ => panic!("Nibble {:#04X} does not match any 32-bit register.", nibble),
+            0x0 => Some(BC),
+            0x1 => Some(DE),
+            0x2 => Some(HL),
+            _ => None,
         }
     }
 }
@@ -166,26 +166,26 @@ pub enum Reg8 {
     L0,
 }
 impl Reg8 {
-    /// Get the [Reg8] corresponding to the given nibble, panicking if the nibble does not
+    /// Get the [Reg8] corresponding to the given nibble, returning [None] if the nibble does not
     /// correspond to any variant.
     #[inline(always)]
-    fn from_nib(nibble: u8) -> Reg8 {
+    fn try_from_nib(nibble: u8) -> Option<Reg8> {
         match nibble {
-            0x0 => A1,
-            0x1 => A0,
-            0x2 => B1,
-            0x3 => B0,
-            0x4 => C1,
-            0x5 => C0,
-            0x6 => D1,
-            0x7 => D0,
-            0x8 => E1,
-            0x9 => E0,
-            0xA => H1,
-            0xB => H0,
-            0xC => L1,
-            0xD => L0,
-            _ => panic!("Nibble {:#04X} does not match any 8-bit register.", nibble),
+            0x0 => Some(A1),
+            0x1 => Some(A0),
+            0x2 => Some(B1),
+            0x3 => Some(B0),
+            0x4 => Some(C1),
+            0x5 => Some(C0),
+            0x6 => Some(D1),
+            0x7 => Some(D0),
+            0x8 => Some(E1),
+            0x9 => Some(E0),
+            0xA => Some(H1),
+            0xB => Some(H0),
+            0xC => Some(L1),
+            0xD => Some(L0),
+            _ => None,
         }
     }
 }
@@ -423,8 +423,11 @@ pub trait Reg {
     fn get(&self, cpu: &Cpu) -> Self::ValueType;
     /// Set the register of the given [Cpu] to the given value.
     fn set(&self, cpu: &mut Cpu, val: Self::ValueType);
-    /// Get the register variant from the given nibble.
-    fn from_nib(nibble: u8) -> Self;
+    /// Get the register variant from the given nibble, returning [None] if the nibble does not
+    /// correspond to any variant.
+    fn try_from_nib(nibble: u8) -> Option<Self>
+    where
+        Self: Sized;
 }
 macro_rules! impl_reg {
     ($(($t:ty, $g_fn:ident, $s_fn:ident, $f4_fn:ident, $vt:ty)),+) => {
@@ -441,7 +444,7 @@ macro_rules! impl_reg {
                 }
 
                 #[inline(always)]
-                fn from_nib(nibble: u8) -> Self {
+                fn try_from_nib(nibble: u8) -> Option<Self> {
                     Self::$f4_fn(nibble)
                 }
             }
@@ -449,9 +452,9 @@ macro_rules! impl_reg {
     };
 }
 impl_reg!(
-    (Reg16, reg, set_reg, from_nib, u16),
-    (Reg32, breg, set_breg, from_nib, u32),
-    (Reg8, vreg, set_vreg, from_nib, u8)
+    (Reg16, reg, set_reg, try_from_nib, u16),
+    (Reg32, breg, set_breg, try_from_nib, u32),
+    (Reg8, vreg, set_vreg, try_from_nib, u8)
 );
 
 #[cfg(test)]
@@ -0,0 +1,434 @@
+//! Conversion of MFS-16 machine code back into mfs16asm.
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{self, Display, Write},
+};
+
+use crate::{
+    computer::INTERRUPT_HANDLERS_OFFSET, cpu::Instruction, mmu::Interrupt, symbols::SymbolMap,
+};
+
+use Instruction::*;
+
+/// Runs of unreached zero bytes at least this long are emitted as absolute labels.
+const ZERO_RUN_MIN_LEN: usize = 16;
+/// The maximum number of raw bytes emitted on one line.
+const BYTES_PER_LINE: usize = 16;
+
+/// An [Instruction] decoded together with its immediate value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodedInstruction {
+    /// The decoded instruction.
+    pub instruction: Instruction,
+    /// The immediate value following the opcode. Zero if the instruction has no immediate.
+    pub immediate: u32,
+}
+impl DecodedInstruction {
+    /// Decode the instruction at the start of the given bytes.
+    ///
+    /// Returns [None] if the bytes don't start with a valid opcode, the immediate is cut off, or
+    /// the opcode wouldn't be reproduced by assembling the decoded instruction.
+    pub fn decode(bytes: &[u8]) -> Option<Self> {
+        let opcode = u16::from_le_bytes([*bytes.first()?, *bytes.get(1)?]);
+        let instruction = Instruction::try_from_opcode(opcode)?;
+        if instruction.into_opcode() != opcode {
+            return None;
+        }
+
+        let imm_bytes = bytes.get(2..(2 + instruction.imm_size()))?;
+        let immediate = imm_bytes
+            .iter()
+            .rev()
+            .fold(0_u32, |acc, &byte| (acc << 8) | byte as u32);
+
+        Some(Self {
+            instruction,
+            immediate,
+        })
+    }
+
+    /// The number of bytes taken up by this instruction, including its immediate.
+    pub fn size(&self) -> usize {
+        2 + self.instruction.imm_size()
+    }
+
+    /// The absolute address this instruction may jump to or call, if any.
+    pub fn jump_target(&self) -> Option<u32> {
+        match self.instruction {
+            JpImm32 | JpzImm32 | JnzImm32 | JpcImm32 | JncImm32 | JpoImm32 | JnoImm32
+            | JppImm32 | JnpImm32 | JpnImm32 | JnnImm32 | CallImm32 | ClzImm32 | CnzImm32
+            | ClcImm32 | CncImm32 | CloImm32 | CnoImm32 | ClpImm32 | CnpImm32 | ClnImm32
+            | CnnImm32 => Some(self.immediate),
+            _ => None,
+        }
+    }
+
+    /// Return `true` iff execution never continues to the instruction after this one.
+    pub fn ends_flow(&self) -> bool {
+        matches!(
+            self.instruction,
+            JpImm32 | JrImm32 | JpBra(_) | JrBra(_) | Ret | Reti | Stop
+        )
+    }
+
+    /// Format this instruction as mfs16asm, naming its jump target after the given [SymbolMap]
+    /// where possible.
+    pub fn to_string_with_symbols(&self, symbols: &SymbolMap) -> String {
+        let label = self
+            .jump_target()
+            .and_then(|target| symbols.labels_at(target).next());
+        let mut asm = String::new();
+        // Writing to a String can't fail
+        let _ = self.write_asm(&mut asm, label);
+        asm
+    }
+
+    /// Write this instruction as mfs16asm, substituting the given label for an imm32.
+    fn write_asm(&self, f: &mut impl Write, label: Option<&str>) -> fmt::Result {
+        let text = self.instruction.to_string();
+        let text = text.trim_end();
+        let (placeholder, value) = match self.instruction.imm_size() {
+            1 => ("imm8", format!("{:#04X}", self.immediate)),
+            2 => ("imm16", format!("{:#06X}:w", self.immediate)),
+            4 => (
+                "imm32",
+                label.map_or_else(|| fmt_dword(self.immediate), String::from),
+            ),
+            _ => return write!(f, "{text};"),
+        };
+        write!(f, "{};", text.replacen(placeholder, &value, 1))
+    }
+}
+impl Display for DecodedInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write_asm(f, None)
+    }
+}
+
+/// A single unit of disassembled output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DisassembledItem {
+    /// An instruction reached from one of the entry points.
+    Code(DecodedInstruction),
+    /// Bytes which weren't reached as code.
+    Data(Vec<u8>),
+    /// A run of unreached zero bytes.
+    Zeroes(usize),
+}
+
+/// A binary split into code and data by recursive traversal from a set of entry points.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Disassembly {
+    items: Vec<(u32, DisassembledItem)>,
+    raw: Vec<u8>,
+    labels: BTreeMap<u32, Vec<String>>,
+}
+impl Disassembly {
+    /// Disassemble the given binary, starting from address 0 and from any interrupt handlers
+    /// which look like they were placed by an absolute label.
+    pub fn new(bytes: &[u8]) -> Self {
+        Self::with_entry_points(bytes, &default_entry_points(bytes))
+    }
+
+    /// Disassemble the given binary, treating only bytes reachable from the given entry points
+    /// as code.
+    pub fn with_entry_points(bytes: &[u8], entry_points: &[u32]) -> Self {
+        Self::with_symbols(bytes, entry_points, &SymbolMap::default())
+    }
+
+    /// Disassemble the given binary, treating only bytes reachable from the given entry points
+    /// as code. Labels are named after the given [SymbolMap] where possible.
+    pub fn with_symbols(bytes: &[u8], entry_points: &[u32], symbols: &SymbolMap) -> Self {
+        let mut instructions: BTreeMap<usize, DecodedInstruction> = BTreeMap::new();
+        let mut covered = vec![false; bytes.len()];
+        let mut targets = BTreeSet::new();
+        let mut pending: Vec<u32> = entry_points.iter().rev().copied().collect();
+
+        while let Some(start) = pending.pop() {
+            let mut addr = start as usize;
+            while addr < bytes.len() && !covered[addr] {
+                let Some(decoded) = DecodedInstruction::decode(&bytes[addr..]) else {
+                    break;
+                };
+                let end = addr + decoded.size();
+                if covered[addr..end].iter().any(|&c| c) {
+                    break;
+                }
+                covered[addr..end].fill(true);
+                instructions.insert(addr, decoded);
+
+                if let Some(target) = decoded.jump_target() {
+                    targets.insert(target);
+                    pending.push(target);
+                }
+                if decoded.ends_flow() {
+                    break;
+                }
+                addr = end;
+            }
+        }
+
+        let mut labels: BTreeMap<u32, Vec<String>> = BTreeMap::new();
+        for (addr, name) in symbols.labels() {
+            labels.entry(*addr).or_default().push(name.clone());
+        }
+        for target in targets {
+            if instructions.contains_key(&(target as usize)) && !labels.contains_key(&target) {
+                let name = format!("addr_{:04X}_{:04X}", target >> 16, target & 0xFFFF);
+                labels.insert(target, vec![name]);
+            }
+        }
+
+        let mut items = Vec::new();
+        let mut addr = 0;
+        while addr < bytes.len() {
+            if let Some(&decoded) = instructions.get(&addr) {
+                items.push((addr as u32, DisassembledItem::Code(decoded)));
+                addr += decoded.size();
+                continue;
+            }
+
+            // Data runs are split at labels so that the labels can be placed.
+            let end = (addr..bytes.len())
+                .find(|&a| covered[a] || (a > addr && labels.contains_key(&(a as u32))))
+                .unwrap_or(bytes.len());
+            let data = &bytes[addr..end];
+            if data.len() >= ZERO_RUN_MIN_LEN && data.iter().all(|&byte| byte == 0) {
+                items.push((addr as u32, DisassembledItem::Zeroes(data.len())));
+            } else {
+                for (i, chunk) in data.chunks(BYTES_PER_LINE).enumerate() {
+                    items.push((
+                        (addr + i * BYTES_PER_LINE) as u32,
+                        DisassembledItem::Data(chunk.to_vec()),
+                    ));
+                }
+            }
+            addr = end;
+        }
+
+        // Labels pointing into the middle of an instruction or past the end can't be placed.
+        labels.retain(|&label_addr, _| {
+            label_addr as usize == bytes.len()
+                || items
+                    .binary_search_by_key(&label_addr, |(item_addr, _)| *item_addr)
+                    .is_ok()
+        });
+
+        Self {
+            items,
+            raw: bytes.to_vec(),
+            labels,
+        }
+    }
+
+    /// The disassembled items, each paired with its address.
+    pub fn items(&self) -> &[(u32, DisassembledItem)] {
+        &self.items
+    }
+
+    /// The names of the labels placed in the output, keyed by address.
+    pub fn labels(&self) -> &BTreeMap<u32, Vec<String>> {
+        &self.labels
+    }
+
+    fn write_labels(&self, f: &mut fmt::Formatter, addr: u32) -> fmt::Result {
+        for name in self.labels.get(&addr).into_iter().flatten() {
+            writeln!(f, "\n{name}:")?;
+        }
+        Ok(())
+    }
+}
+impl Display for Disassembly {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (addr, item) in &self.items {
+            self.write_labels(f, *addr)?;
+            match item {
+                DisassembledItem::Code(decoded) => {
+                    let bytes = &self.raw[(*addr as usize)..(*addr as usize + decoded.size())];
+                    write!(f, "    /* {}: {:<17} */ ", fmt_addr(*addr), fmt_hex(bytes))?;
+                    let label = decoded
+                        .jump_target()
+                        .and_then(|target| self.labels.get(&target)?.first())
+                        .map(String::as_str);
+                    decoded.write_asm(f, label)?;
+                    writeln!(f)?;
+                }
+                DisassembledItem::Data(bytes) => {
+                    let list = bytes
+                        .iter()
+                        .map(|byte| format!("{byte:#04X}"))
+                        .collect::<Vec<String>>()
+                        .join(", ");
+                    writeln!(f, "    /* {} */ [{list}]", fmt_addr(*addr))?;
+                }
+                DisassembledItem::Zeroes(len) => {
+                    writeln!(f, "\n{}:", fmt_dword(*addr + *len as u32))?;
+                }
+            }
+        }
+        self.write_labels(f, self.raw.len() as u32)
+    }
+}
+
+/// Address 0, plus the handler of every interrupt whose first bytes are non-zero and are preceded
+/// by zero padding.
+pub fn default_entry_points(bytes: &[u8]) -> Vec<u32> {
+    let mut entry_points = vec![0];
+    for n in 0..=Interrupt::Blitter.into_byte() {
+        let addr = INTERRUPT_HANDLERS_OFFSET + (n as usize * 0x100);
+        if bytes
+            .get(addr..(addr + 2))
+            .is_some_and(|word| word != [0, 0])
+            && bytes[(addr - 2)..addr] == [0, 0]
+        {
+            entry_points.push(addr as u32);
+        }
+    }
+    entry_points
+}
+
+fn fmt_dword(value: u32) -> String {
+    format!("{:#06X}_{:04X}:d", value >> 16, value & 0xFFFF)
+}
+
+fn fmt_addr(addr: u32) -> String {
+    format!("{:04X}_{:04X}", addr >> 16, addr & 0xFFFF)
+}
+
+fn fmt_hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|byte| format!("{byte:02X}"))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    use crate::Reg16;
+
+    #[test]
+    fn test_decode() {
+        let decoded = DecodedInstruction::decode(&[0x00, 0x03, 0x34, 0x12]).unwrap();
+        assert_eq!(decoded.instruction, LdRaImm16(Reg16::A));
+        assert_eq!(decoded.immediate, 0x1234);
+        assert_eq!(decoded.size(), 4);
+        assert_eq!(decoded.to_string(), "LD A,0x1234:w;");
+
+        let decoded = DecodedInstruction::decode(&[0x00, 0x80, 0x78, 0x56, 0x34, 0x12]).unwrap();
+        assert_eq!(decoded.jump_target(), Some(0x1234_5678));
+        assert_eq!(decoded.to_string(), "JP 0x1234_5678:d;");
+        assert!(decoded.ends_flow());
+
+        let mut symbols = SymbolMap::default();
+        symbols.add_label("far_away", 0x1234_5678);
+        assert_eq!(decoded.to_string_with_symbols(&symbols), "JP far_away;");
+
+        // Immediate cut off
+        assert_eq!(DecodedInstruction::decode(&[0x00, 0x80, 0x78, 0x56]), None);
+        // No such instruction
+        assert_eq!(DecodedInstruction::decode(&[0x00, 0x70]), None);
+    }
+
+    #[test]
+    fn test_disassembly() {
+        let bytes = [
+            // JP 0x0000_000A:d;
+            0x00, 0x80, 0x0A, 0x00, 0x00, 0x00, // data
+            0xAB, 0xCD, 0xEF, 0x01, // LD A,B; (0x0A)
+            0x01, 0x01, // STOP;
+            0xFC, 0xFF,
+        ];
+        let disassembly = Disassembly::new(&bytes);
+        assert_eq!(
+            disassembly.items(),
+            &[
+                (
+                    0x00,
+                    DisassembledItem::Code(DecodedInstruction {
+                        instruction: JpImm32,
+                        immediate: 0x0A
+                    })
+                ),
+                (0x06, DisassembledItem::Data(vec![0xAB, 0xCD, 0xEF, 0x01])),
+                (
+                    0x0A,
+                    DisassembledItem::Code(DecodedInstruction {
+                        instruction: LdRaRb(Reg16::A, Reg16::B),
+                        immediate: 0
+                    })
+                ),
+                (
+                    0x0C,
+                    DisassembledItem::Code(DecodedInstruction {
+                        instruction: Stop,
+                        immediate: 0
+                    })
+                ),
+            ]
+        );
+        assert_eq!(
+            disassembly.to_string(),
+            "    /* 0000_0000: 00 80 0A 00 00 00 */ JP addr_0000_000A;\n\
+            \x20   /* 0000_0006 */ [0xAB, 0xCD, 0xEF, 0x01]\n\
+            \n\
+            addr_0000_000A:\n\
+            \x20   /* 0000_000A: 01 01             */ LD A,B;\n\
+            \x20   /* 0000_000C: FC FF             */ STOP;\n"
+        );
+    }
+
+    #[test]
+    fn test_symbols() {
+        let bytes = [
+            // CALL 0x0000_000A:d;
+            0x00, 0x81, 0x0A, 0x00, 0x00, 0x00, // data
+            0xAB, 0xCD, 0xEF, 0x01, // RET;
+            0x13, 0x81,
+        ];
+        let mut symbols = SymbolMap::default();
+        symbols.add_label("main", 0x00);
+        symbols.add_label("mid_instruction", 0x02);
+        symbols.add_label("sprite", 0x08);
+        symbols.add_label("func", 0x0A);
+        symbols.add_label("end", 0x0C);
+        let disassembly = Disassembly::with_symbols(&bytes, &[0], &symbols);
+        assert_eq!(
+            disassembly.labels().keys().copied().collect::<Vec<u32>>(),
+            [0x00, 0x08, 0x0A, 0x0C]
+        );
+        assert_eq!(
+            disassembly.to_string(),
+            "\nmain:\n\
+            \x20   /* 0000_0000: 00 81 0A 00 00 00 */ CALL func;\n\
+            \x20   /* 0000_0006 */ [0xAB, 0xCD]\n\
+            \n\
+            sprite:\n\
+            \x20   /* 0000_0008 */ [0xEF, 0x01]\n\
+            \n\
+            func:\n\
+            \x20   /* 0000_000A: 13 81             */ RET;\n\
+            \n\
+            end:\n"
+        );
+    }
+
+    #[test]
+    fn test_zero_padding() {
+        let mut bytes = vec![0xFC, 0xFF];
+        bytes.resize(0x100, 0);
+        bytes.extend([0xFC, 0xFF]);
+        let disassembly = Disassembly::new(&bytes);
+        assert_eq!(
+            disassembly.items()[1],
+            (0x02, DisassembledItem::Zeroes(0xFE))
+        );
+        assert!(disassembly
+            .to_string()
+            .contains("\n0x0000_0100:d:\n    /* 0000_0100: FC FF             */ STOP;\n"));
+    }
+}
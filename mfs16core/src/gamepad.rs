@@ -0,0 +1,281 @@
+//! The virtual gamepad hardware.
+use std::{default::Default, fmt::Display};
+
+use crate::{
+    helpers::{combine_u16_le, combine_u8_le, split_word},
+    mmu::{print_warning_message, NOT_READABLE_BYTE},
+};
+
+/// The number of analog axes on the gamepad.
+pub const NUM_GAMEPAD_AXES: usize = 6;
+
+/// Size of the gamepad register: one button word followed by one word per analog axis.
+pub const GAMEPAD_REG_SIZE: usize = 2 + (2 * NUM_GAMEPAD_AXES);
+
+/// The different digital buttons of the gamepad. The value of each button is its bit index within
+/// the gamepad button word.
+#[repr(u8)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum GamepadButton {
+    /// D-pad up.
+    Up = 0,
+    /// D-pad down.
+    Down = 1,
+    /// D-pad left.
+    Left = 2,
+    /// D-pad right.
+    Right = 3,
+    /// The bottom face button.
+    A = 4,
+    /// The right face button.
+    B = 5,
+    /// The left face button.
+    X = 6,
+    /// The top face button.
+    Y = 7,
+    /// The left shoulder button.
+    L = 8,
+    /// The right shoulder button.
+    R = 9,
+    /// The start button.
+    Start = 10,
+    /// The select button.
+    Select = 11,
+}
+impl GamepadButton {
+    /// Get the [GamepadButton] matching the given bit index, returning [None] if no button matches
+    /// the given index.
+    pub fn try_from_u8(val: u8) -> Option<Self> {
+        match val {
+            0 => Some(Self::Up),
+            1 => Some(Self::Down),
+            2 => Some(Self::Left),
+            3 => Some(Self::Right),
+            4 => Some(Self::A),
+            5 => Some(Self::B),
+            6 => Some(Self::X),
+            7 => Some(Self::Y),
+            8 => Some(Self::L),
+            9 => Some(Self::R),
+            10 => Some(Self::Start),
+            11 => Some(Self::Select),
+            _ => None,
+        }
+    }
+}
+impl From<GamepadButton> for u8 {
+    fn from(value: GamepadButton) -> Self {
+        value as u8
+    }
+}
+impl Display for GamepadButton {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Up => "Up",
+                Self::Down => "Down",
+                Self::Left => "Left",
+                Self::Right => "Right",
+                Self::A => "A",
+                Self::B => "B",
+                Self::X => "X",
+                Self::Y => "Y",
+                Self::L => "L",
+                Self::R => "R",
+                Self::Start => "Start",
+                Self::Select => "Select",
+            }
+        )
+    }
+}
+
+/// The different analog axes of the gamepad. The value of each axis is its index within the axis
+/// words of the gamepad register.
+#[repr(u8)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum GamepadAxis {
+    /// Left stick, horizontal. Negative = left, positive = right.
+    LeftX = 0,
+    /// Left stick, vertical. Negative = up, positive = down.
+    LeftY = 1,
+    /// Right stick, horizontal. Negative = left, positive = right.
+    RightX = 2,
+    /// Right stick, vertical. Negative = up, positive = down.
+    RightY = 3,
+    /// Left trigger. 0 = released, [i16::MAX] = fully pressed.
+    LeftTrigger = 4,
+    /// Right trigger. 0 = released, [i16::MAX] = fully pressed.
+    RightTrigger = 5,
+}
+impl GamepadAxis {
+    /// Get the [GamepadAxis] matching the given index, returning [None] if no axis matches the
+    /// given index.
+    pub fn try_from_u8(val: u8) -> Option<Self> {
+        match val {
+            0 => Some(Self::LeftX),
+            1 => Some(Self::LeftY),
+            2 => Some(Self::RightX),
+            3 => Some(Self::RightY),
+            4 => Some(Self::LeftTrigger),
+            5 => Some(Self::RightTrigger),
+            _ => None,
+        }
+    }
+}
+impl Display for GamepadAxis {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::LeftX => "LeftX",
+                Self::LeftY => "LeftY",
+                Self::RightX => "RightX",
+                Self::RightY => "RightY",
+                Self::LeftTrigger => "LeftTrigger",
+                Self::RightTrigger => "RightTrigger",
+            }
+        )
+    }
+}
+
+/// The register storing the state of the gamepad. The first word holds one bit per
+/// [GamepadButton] (1 = pressed, 0 = not pressed). Each following word holds the signed position
+/// of one [GamepadAxis].
+#[derive(Debug, Clone, PartialEq)]
+pub struct GamepadReg {
+    /// The button states. Each bit corresponds to a [GamepadButton].
+    buttons: u16,
+    /// The analog axis positions, indexed by [GamepadAxis].
+    axes: [i16; NUM_GAMEPAD_AXES],
+    /// If true, will print warning messages to stderr.
+    pub debug: bool,
+}
+impl GamepadReg {
+    /// Create a new [GamepadReg].
+    pub fn new(debug: bool) -> Self {
+        Self {
+            debug,
+            ..Default::default()
+        }
+    }
+
+    /// Read a raw byte from the GamepadReg.
+    pub fn read_byte(&self, address: u32) -> u8 {
+        match address as usize {
+            0 => split_word(self.buttons).1,
+            1 => split_word(self.buttons).0,
+            i if i < GAMEPAD_REG_SIZE => {
+                let (high_byte, low_byte) = split_word(self.axes[(i - 2) / 2] as u16);
+                if i % 2 == 0 {
+                    low_byte
+                } else {
+                    high_byte
+                }
+            }
+            _ => {
+                print_warning_message("read from gamepad register", address, self.debug);
+                NOT_READABLE_BYTE
+            }
+        }
+    }
+
+    /// Read a word from the GamepadReg starting at the given address.
+    pub fn read_word(&self, address: u32) -> u16 {
+        combine_u8_le(self.read_byte(address), self.read_byte(address + 1))
+    }
+
+    /// Read a double word from the GamepadReg starting at the given address.
+    pub fn read_dword(&self, address: u32) -> u32 {
+        combine_u16_le(self.read_word(address), self.read_word(address + 2))
+    }
+
+    /// Get the status of the given [GamepadButton].
+    pub fn button(&self, button: GamepadButton) -> bool {
+        (self.buttons & (1 << (button as u8))) != 0
+    }
+
+    /// Set the register bit corresponding to the given [GamepadButton].
+    pub fn button_down(&mut self, button: GamepadButton) {
+        self.buttons |= 1 << (button as u8);
+    }
+
+    /// Reset the register bit corresponding to the given [GamepadButton].
+    pub fn button_up(&mut self, button: GamepadButton) {
+        self.buttons &= !(1 << (button as u8));
+    }
+
+    /// Get the position of the given [GamepadAxis].
+    pub fn axis(&self, axis: GamepadAxis) -> i16 {
+        self.axes[axis as usize]
+    }
+
+    /// Set the position of the given [GamepadAxis].
+    pub fn set_axis(&mut self, axis: GamepadAxis, value: i16) {
+        self.axes[axis as usize] = value;
+    }
+}
+impl Default for GamepadReg {
+    fn default() -> Self {
+        Self {
+            buttons: 0,
+            axes: [0; NUM_GAMEPAD_AXES],
+            debug: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    use GamepadButton::*;
+
+    #[test]
+    fn test_buttons() {
+        let mut gpr = GamepadReg::new(true);
+
+        assert!(!gpr.button(A));
+        gpr.button_down(A);
+        assert!(gpr.button(A));
+        gpr.button_down(Start);
+        assert_eq!(gpr.read_word(0), 0b0000_0100_0001_0000);
+        assert_eq!(gpr.read_byte(0), 0b0001_0000);
+        assert_eq!(gpr.read_byte(1), 0b0000_0100);
+
+        gpr.button_up(A);
+        assert!(!gpr.button(A));
+        assert!(gpr.button(Start));
+        assert_eq!(gpr.read_word(0), 0b0000_0100_0000_0000);
+
+        for i in 0..=11 {
+            assert_eq!(GamepadButton::try_from_u8(i).unwrap() as u8, i);
+        }
+        assert_eq!(GamepadButton::try_from_u8(12), None);
+    }
+
+    #[test]
+    fn test_axes() {
+        let mut gpr = GamepadReg::new(true);
+
+        gpr.set_axis(GamepadAxis::LeftX, -2);
+        gpr.set_axis(GamepadAxis::RightTrigger, 0x1234);
+        assert_eq!(gpr.axis(GamepadAxis::LeftX), -2);
+        assert_eq!(gpr.read_word(2), 0xFFFE);
+        assert_eq!(gpr.read_word(4), 0x0000);
+        assert_eq!(gpr.read_byte(12), 0x34);
+        assert_eq!(gpr.read_byte(13), 0x12);
+        assert_eq!(gpr.read_dword(0), 0xFFFE_0000);
+
+        assert_eq!(gpr.read_byte(GAMEPAD_REG_SIZE as u32), NOT_READABLE_BYTE);
+
+        for i in 0..NUM_GAMEPAD_AXES as u8 {
+            assert_eq!(GamepadAxis::try_from_u8(i).unwrap() as u8, i);
+        }
+        assert_eq!(GamepadAxis::try_from_u8(NUM_GAMEPAD_AXES as u8), None);
+    }
+}
@@ -1,20 +1,400 @@
 //! The virtual GPU hardware.
-use std::default::Default;
+use std::{default::Default, mem};
 
-use crate::VRAM_SIZE;
+use crate::{
+    helpers::{combine_u16_le, combine_u8_le, split_dword, split_word},
+    memory::Memory,
+    mmu::Interrupt,
+    DISPLAY_HEIGHT, DISPLAY_WIDTH, VRAM_SIZE,
+};
+
+mod blitter;
+mod sprite;
+mod text;
+mod tile_map;
+mod video_mode;
+
+pub use blitter::{BlitCommand, BlitRegs, Blitter, BLIT_PIXELS_PER_CYCLE, BLIT_SETUP_CYCLES};
+pub use sprite::{Sprite, NUM_SPRITES, OAM_SIZE, SPRITE_ENTRY_SIZE};
+pub use text::{glyph_row, CHAR_SIZE, TEXT_CELL_SIZE, TEXT_COLUMNS, TEXT_ROWS};
+pub use tile_map::{TILE_MAP_HEIGHT, TILE_MAP_SIZE, TILE_MAP_WIDTH, TILE_PATTERN_SIZE, TILE_SIZE};
+pub use video_mode::VideoMode;
+
+/// The number of cycles the GPU takes to draw one line of pixels, including horizontal blanking.
+pub const LINE_CYCLES: u32 = 1065;
+/// The number of lines in the vertical blanking period at the end of each frame.
+pub const VBLANK_LINES: u32 = 45;
+/// The number of cycles in the vertical blanking period at the end of each frame.
+pub const VBLANK_CYCLES: u32 = LINE_CYCLES * VBLANK_LINES;
+/// The total number of lines in one frame, including the vertical blanking period.
+pub const FRAME_LINES: u32 = DISPLAY_HEIGHT as u32 + VBLANK_LINES;
+/// The number of cycles in one frame. At the system clock frequency, this is ~60.01 frames per
+/// second.
+pub const FRAME_CYCLES: u32 = LINE_CYCLES * FRAME_LINES;
+
+/// The number of colours in the palette.
+pub const NUM_PALETTE_COLOURS: usize = 256;
+/// Size of the palette registers, which hold the first 16 colours of the palette. Each colour
+/// takes up one double word.
+pub const PALETTE_SIZE: usize = 16 * 4;
+/// Size of palette RAM, which holds every colour of the palette.
+pub const PALETTE_RAM_SIZE: usize = NUM_PALETTE_COLOURS * 4;
+/// The power-on palette, stored as `0x00RRGGBB`: the 16 standard ANSI terminal colours, then a
+/// 6x6x6 colour cube and a 24-step greyscale ramp, the same as 256-colour terminals.
+pub const DEFAULT_PALETTE: [u32; NUM_PALETTE_COLOURS] = default_palette();
+
+/// Build the [DEFAULT_PALETTE].
+const fn default_palette() -> [u32; NUM_PALETTE_COLOURS] {
+    const ANSI_COLOURS: [u32; 16] = [
+        0x000000, 0x800000, 0x008000, 0x808000, 0x000080, 0x800080, 0x008080, 0xC0C0C0, 0x808080,
+        0xFF0000, 0x00FF00, 0xFFFF00, 0x0000FF, 0xFF00FF, 0x00FFFF, 0xFFFFFF,
+    ];
+    const CUBE_LEVELS: [u32; 6] = [0x00, 0x5F, 0x87, 0xAF, 0xD7, 0xFF];
+
+    let mut palette = [0; NUM_PALETTE_COLOURS];
+    let mut i = 0;
+    while i < NUM_PALETTE_COLOURS {
+        palette[i] = if i < 16 {
+            ANSI_COLOURS[i]
+        } else if i < 232 {
+            let cube = i - 16;
+            (CUBE_LEVELS[cube / 36] << 16)
+                | (CUBE_LEVELS[(cube / 6) % 6] << 8)
+                | CUBE_LEVELS[cube % 6]
+        } else {
+            let grey = 0x08 + 10 * (i as u32 - 232);
+            (grey << 16) | (grey << 8) | grey
+        };
+        i += 1;
+    }
+    palette
+}
+
+/// Pattern RAM size in bytes. Holds the pixel data of tiles and sprites.
+pub const PATTERN_RAM_SIZE: usize = 0x1_0000;
+
+/// VRAM page register bit: the page to display from the next vertical blanking period onwards.
+const DISPLAY_PAGE_BIT: u8 = 0b0000_0001;
+/// VRAM page register bit: the page accessed by the CPU and the blitter.
+const ACCESS_PAGE_BIT: u8 = 0b0000_0010;
+/// VRAM page register bit: the page currently being displayed. Read-only.
+const DISPLAYED_PAGE_BIT: u8 = 0b0000_0100;
+
+/// Layer control register bit: the tile map layer is drawn.
+const TILE_LAYER_ENABLED_BIT: u8 = 0b0000_0001;
+/// Layer control register bit: sprites are drawn.
+const SPRITES_ENABLED_BIT: u8 = 0b0000_0010;
+/// Layer control register bit: tile map pixels of the colour key are transparent.
+const TILE_COLOUR_KEY_BIT: u8 = 0b0000_0100;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Gpu {
-    /// Video RAM responsible for storing the pixel data of the computer. Each pixel takes up one
-    /// nibble of space.
+    /// Video RAM responsible for storing the pixel data of the computer. How it is displayed
+    /// depends on the video mode. This is the VRAM page accessed by the CPU and the blitter.
     pub vram: [u8; Self::VRAM_SIZE],
+    /// The VRAM page which isn't accessed by the CPU.
+    other_vram: Box<[u8; Self::VRAM_SIZE]>,
+    /// The number of the VRAM page accessed by the CPU, held in [Gpu::vram].
+    access_page: u8,
+    /// The number of the VRAM page being displayed.
+    display_page: u8,
+    /// The number of the VRAM page to display from the next vertical blanking period onwards.
+    next_display_page: u8,
     /// GPU control register. Determines how the GPU acts and interacts with I/O.
     gpu_control_reg: u8,
+    /// The way in which the contents of VRAM are displayed.
+    pub video_mode: VideoMode,
+    /// The colours displayed for each possible pixel value, stored as `0x00RRGGBB`. Modes with
+    /// fewer than 8 bits per pixel only use the first 16 colours.
+    palette: [u32; NUM_PALETTE_COLOURS],
+    /// The pixel data of tiles and sprites, laid out the same way as VRAM.
+    pub pattern_ram: Memory,
+    /// The tile map. Each entry is a little-endian word holding a tile number in bits 0-10, plus
+    /// flip x (bit 14) and flip y (bit 15) flags.
+    pub tile_map: Memory,
+    /// The object attribute memory. Describes the position, size, pattern and colour key of each
+    /// [Sprite].
+    pub oam: Memory,
+    /// Layer control register. Bit 0: tile map layer enabled. Bit 1: sprites enabled. Bit 2: tile
+    /// map colour key enabled. Bits 4-7: tile map colour key.
+    pub layer_control: u8,
+    /// The horizontal scroll of the tile map layer in pixels.
+    pub tile_scroll_x: u16,
+    /// The vertical scroll of the tile map layer in pixels.
+    pub tile_scroll_y: u16,
+    /// The blitter. Copies and fills rectangles of VRAM.
+    pub blitter: Blitter,
+    /// The frame being drawn, stored as one palette index per pixel. Each line is drawn from VRAM
+    /// as the GPU finishes drawing it, so VRAM changes made mid-frame only affect the lines which
+    /// haven't been drawn yet.
+    draw_buffer: Vec<u8>,
+    /// The video mode of the frame being drawn. Changes to the video mode take effect at the
+    /// start of the next frame.
+    frame_mode: VideoMode,
+    /// The most recently presented frame. Kept apart from the frame being drawn so that frames
+    /// which aren't presented never show up in it.
+    frame_buffer: Vec<u8>,
+    /// The video mode of the most recently presented frame.
+    presented_mode: VideoMode,
+    /// The number of cycles since the start of the current frame.
+    frame_cycle: u32,
+    /// The [Interrupt::LineCompare] interrupt is raised when the GPU starts drawing this line.
+    pub line_compare: u16,
+    /// Set when a new frame has been presented and is ready to be displayed by the front end.
+    frame_ready: bool,
+    /// The interrupts raised by the GPU which have yet to be passed on to the interrupt register.
+    interrupts: u8,
 }
 impl Gpu {
     /// This GPU's VRAM size.
     pub const VRAM_SIZE: usize = VRAM_SIZE;
 
+    /// Perform one clock cycle of video timing.
+    ///
+    /// Each visible line is drawn into the draw buffer on the last cycle of that line. Video
+    /// modes with more or fewer lines than [DISPLAY_HEIGHT] draw their lines spread evenly across
+    /// the visible lines.
+    /// When the vertical blanking period starts, the draw buffer is presented as a new frame and
+    /// the [Interrupt::Frame] interrupt is raised. If manual frame updates are enabled, the frame
+    /// is only presented if the frame update flag was set.
+    pub fn cycle(&mut self) {
+        let line = self.scanline();
+        let line_cycle = self.frame_cycle % LINE_CYCLES;
+
+        if line_cycle == 0 {
+            if self.frame_cycle == 0 {
+                self.start_frame();
+            }
+            if line == self.line_compare {
+                self.raise_interrupt(Interrupt::LineCompare);
+            }
+            if line == DISPLAY_HEIGHT as u16 {
+                self.display_page = self.next_display_page;
+                self.raise_interrupt(Interrupt::VBlank);
+                if !self.is_man_frame_enabled() || self.consume_frame_update_flag() {
+                    self.present_frame();
+                }
+            }
+        } else if line_cycle == LINE_CYCLES - 1 && line < DISPLAY_HEIGHT as u16 {
+            let height = self.frame_mode.height();
+            let first_line = line as usize * height / DISPLAY_HEIGHT;
+            let last_line = (line as usize + 1) * height / DISPLAY_HEIGHT;
+            for frame_line in first_line..last_line {
+                self.draw_line(frame_line as u16);
+            }
+        }
+
+        self.frame_cycle = (self.frame_cycle + 1) % FRAME_CYCLES;
+    }
+
+    /// Latch the video mode for the frame which is starting, resizing the draw buffer to match.
+    fn start_frame(&mut self) {
+        self.frame_mode = self.video_mode;
+        self.draw_buffer
+            .resize(self.frame_mode.width() * self.frame_mode.height(), 0x0);
+    }
+
+    /// Present the frame which has just been drawn, swapping it with the previously presented one
+    /// to be drawn over next.
+    fn present_frame(&mut self) {
+        mem::swap(&mut self.draw_buffer, &mut self.frame_buffer);
+        self.presented_mode = self.frame_mode;
+        self.frame_ready = true;
+        self.raise_interrupt(Interrupt::Frame);
+    }
+
+    /// Draw the given line into the draw buffer: first the line of VRAM according to the video
+    /// mode, then the tile map layer and finally the sprites on top.
+    fn draw_line(&mut self, line: u16) {
+        let width = self.frame_mode.width();
+        let line_pixels = &mut self.draw_buffer[(line as usize * width)..][..width];
+        let line_size = self.frame_mode.line_size();
+        let vram = if self.display_page == self.access_page {
+            &self.vram
+        } else {
+            &*self.other_vram
+        };
+        let vram_line = &vram[(line as usize * line_size)..][..line_size];
+        match self.frame_mode {
+            VideoMode::Bitmap => {
+                for (x, pixel) in line_pixels.iter_mut().enumerate() {
+                    *pixel = read_pixel(vram_line, x);
+                }
+            }
+            VideoMode::Text => text::draw_line(line_pixels, line, vram),
+            VideoMode::LowRes => line_pixels.copy_from_slice(vram_line),
+            VideoMode::HighRes => {
+                for (x, pixel) in line_pixels.iter_mut().enumerate() {
+                    *pixel = if vram_line[x / 8] & (0x80 >> (x % 8)) != 0 {
+                        0xF
+                    } else {
+                        0x0
+                    };
+                }
+            }
+        }
+
+        if self.layer_control & TILE_LAYER_ENABLED_BIT != 0 {
+            let colour_key = if self.layer_control & TILE_COLOUR_KEY_BIT != 0 {
+                Some(self.layer_control >> 4)
+            } else {
+                None
+            };
+            tile_map::draw_line(
+                line_pixels,
+                line,
+                &self.tile_map,
+                &self.pattern_ram,
+                (self.tile_scroll_x, self.tile_scroll_y),
+                colour_key,
+            );
+        }
+        if self.layer_control & SPRITES_ENABLED_BIT != 0 {
+            sprite::draw_line(line_pixels, line, &self.oam, &self.pattern_ram);
+        }
+    }
+
+    /// Get the number of cycles since the start of the current frame.
+    pub fn frame_cycle(&self) -> u32 {
+        self.frame_cycle
+    }
+
+    /// Get the line the GPU is currently drawing. Lines at or past [DISPLAY_HEIGHT] are part of
+    /// the vertical blanking period.
+    pub fn scanline(&self) -> u16 {
+        (self.frame_cycle / LINE_CYCLES) as u16
+    }
+
+    /// Get the most recently presented frame, stored as one palette index per pixel, row by row.
+    pub fn frame_buffer(&self) -> &[u8] {
+        &self.frame_buffer
+    }
+
+    /// Get the width and height in pixels of the most recently presented frame, which depend on
+    /// the video mode it was drawn in.
+    pub fn frame_size(&self) -> (usize, usize) {
+        (self.presented_mode.width(), self.presented_mode.height())
+    }
+
+    /// Return whether a new frame has been presented since this was last called, resetting the
+    /// frame-ready state in the process. Front ends should display the frame buffer whenever this
+    /// is true.
+    pub fn consume_frame_ready(&mut self) -> bool {
+        let value = self.frame_ready;
+        self.frame_ready = false;
+        value
+    }
+
+    /// Raise an [Interrupt], to be passed on to the interrupt register.
+    fn raise_interrupt(&mut self, interrupt: Interrupt) {
+        self.interrupts |= 1 << interrupt.into_byte();
+    }
+
+    /// Return the bits of the interrupts raised since this was last called, resetting them in the
+    /// process.
+    pub fn consume_interrupts(&mut self) -> u8 {
+        let value = self.interrupts;
+        self.interrupts = 0;
+        value
+    }
+
+    /// Perform a blitter operation on VRAM, with pixels laid out as in the current video mode. For
+    /// copies, the source slice must hold the bytes of [BlitRegs::src_range].
+    pub fn blit(&mut self, command: BlitCommand, regs: &BlitRegs, src: &[u8]) {
+        let mode = self.video_mode;
+        match command {
+            BlitCommand::Copy => blitter::copy(regs, mode, src, false, &mut self.vram),
+            BlitCommand::Fill => blitter::fill(regs, mode, &mut self.vram),
+            BlitCommand::KeyedCopy => blitter::copy(regs, mode, src, true, &mut self.vram),
+        }
+    }
+
+    /// Get the colours of the palette, stored as `0x00RRGGBB`.
+    pub fn palette(&self) -> &[u32; NUM_PALETTE_COLOURS] {
+        &self.palette
+    }
+
+    /// Overwrite the first colours of the palette with the given colours, stored as `0x00RRGGBB`.
+    pub fn set_palette(&mut self, colours: &[u32]) {
+        self.palette[..colours.len()].copy_from_slice(colours);
+    }
+
+    /// Read a byte from the palette. Each colour is a little-endian double word, so the bytes of
+    /// each colour are blue, green, red, then an unused byte which always reads 0.
+    pub fn read_palette_byte(&self, address: u32) -> u8 {
+        let colour = self.palette[address as usize / 4];
+        (colour >> (8 * (address % 4))) as u8
+    }
+
+    /// Write a byte to the palette. Writes to the unused byte of each colour are
+    /// ignored.
+    pub fn write_palette_byte(&mut self, address: u32, value: u8) {
+        let shift = 8 * (address % 4);
+        if shift == 24 {
+            return;
+        }
+        let colour = &mut self.palette[address as usize / 4];
+        *colour = (*colour & !(0xFF << shift)) | ((value as u32) << shift);
+    }
+
+    /// Read a word from the palette starting at the given address.
+    pub fn read_palette_word(&self, address: u32) -> u16 {
+        combine_u8_le(
+            self.read_palette_byte(address),
+            self.read_palette_byte(address + 1),
+        )
+    }
+
+    /// Write a word to the palette starting at the given address.
+    pub fn write_palette_word(&mut self, address: u32, value: u16) {
+        let (high_byte, low_byte) = split_word(value);
+        self.write_palette_byte(address, low_byte);
+        self.write_palette_byte(address + 1, high_byte);
+    }
+
+    /// Read a double word from the palette starting at the given address.
+    pub fn read_palette_dword(&self, address: u32) -> u32 {
+        combine_u16_le(
+            self.read_palette_word(address),
+            self.read_palette_word(address + 2),
+        )
+    }
+
+    /// Write a double word to the palette starting at the given address.
+    pub fn write_palette_dword(&mut self, address: u32, value: u32) {
+        let (high_word, low_word) = split_dword(value);
+        self.write_palette_word(address, low_word);
+        self.write_palette_word(address + 2, high_word);
+    }
+
+    /// Read the VRAM page register. Bit 0: the page to display from the next vertical blanking
+    /// period onwards. Bit 1: the page accessed by the CPU. Bit 2: the page currently being
+    /// displayed.
+    pub fn vram_page_reg(&self) -> u8 {
+        let mut value = 0;
+        if self.next_display_page == 1 {
+            value |= DISPLAY_PAGE_BIT;
+        }
+        if self.access_page == 1 {
+            value |= ACCESS_PAGE_BIT;
+        }
+        if self.display_page == 1 {
+            value |= DISPLAYED_PAGE_BIT;
+        }
+        value
+    }
+
+    /// Write to the VRAM page register. The page accessed by the CPU changes straight away, but
+    /// the displayed page only changes at the start of the next vertical blanking period.
+    pub fn set_vram_page_reg(&mut self, value: u8) {
+        self.next_display_page = value & DISPLAY_PAGE_BIT;
+        let access_page = (value & ACCESS_PAGE_BIT) >> 1;
+        if access_page != self.access_page {
+            self.vram.swap_with_slice(&mut self.other_vram[..]);
+            self.access_page = access_page;
+        }
+    }
+
     /// Return whether or not manual frame updates are enabled.
     pub fn is_man_frame_enabled(&self) -> bool {
         (self.gpu_control_reg & 0b1) != 0
@@ -85,12 +465,344 @@ impl Gpu {
         self.vram[address as usize]
     }
 }
+
+/// Read the pixel at the given index from a slice of pixel data. Each byte holds two pixels, with
+/// the left pixel in the upper nibble.
+fn read_pixel(pixels: &[u8], index: usize) -> u8 {
+    let byte = pixels[index / 2];
+    if index % 2 == 1 {
+        byte & 0x0F
+    } else {
+        byte >> 4
+    }
+}
+
 impl Default for Gpu {
     /// Default = black screen.
     fn default() -> Self {
         Self {
             vram: [0x00; Self::VRAM_SIZE],
+            other_vram: vec![0x00; Self::VRAM_SIZE]
+                .into_boxed_slice()
+                .try_into()
+                .expect("Failed to create VRAM page: slice with incorrect length"),
+            access_page: 0,
+            display_page: 0,
+            next_display_page: 0,
             gpu_control_reg: <u8>::default(),
+            video_mode: VideoMode::default(),
+            palette: DEFAULT_PALETTE,
+            pattern_ram: Memory::new_empty(PATTERN_RAM_SIZE, true, true),
+            tile_map: Memory::new_empty(TILE_MAP_SIZE, true, true),
+            oam: Memory::new_empty(OAM_SIZE, true, true),
+            layer_control: 0,
+            tile_scroll_x: 0,
+            tile_scroll_y: 0,
+            blitter: Blitter::default(),
+            draw_buffer: vec![0x0; DISPLAY_WIDTH * DISPLAY_HEIGHT],
+            frame_mode: VideoMode::default(),
+            frame_buffer: vec![0x0; DISPLAY_WIDTH * DISPLAY_HEIGHT],
+            presented_mode: VideoMode::default(),
+            frame_cycle: 0,
+            line_compare: u16::MAX,
+            frame_ready: false,
+            interrupts: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_frame_timing() {
+        let mut gpu = Gpu::default();
+
+        for _ in 0..(FRAME_CYCLES - VBLANK_CYCLES) {
+            gpu.cycle();
+        }
+        assert!(!gpu.consume_frame_ready());
+        assert_eq!(gpu.consume_interrupts(), 0);
+
+        gpu.cycle();
+        assert!(gpu.consume_frame_ready());
+        assert!(!gpu.consume_frame_ready());
+        assert_eq!(
+            gpu.consume_interrupts(),
+            (1 << Interrupt::Frame.into_byte()) | (1 << Interrupt::VBlank.into_byte())
+        );
+        assert_eq!(gpu.consume_interrupts(), 0);
+        assert_eq!(gpu.scanline(), DISPLAY_HEIGHT as u16);
+
+        for _ in 0..(VBLANK_CYCLES - 1) {
+            gpu.cycle();
+        }
+        assert_eq!(gpu.frame_cycle(), 0);
+    }
+
+    #[test]
+    fn test_man_frame_timing() {
+        let mut gpu = Gpu::default();
+        gpu.man_frame_enable();
+
+        // No frame is presented unless the frame update flag is set
+        for _ in 0..FRAME_CYCLES {
+            gpu.cycle();
+        }
+        assert!(!gpu.consume_frame_ready());
+        assert_eq!(gpu.consume_interrupts(), 1 << Interrupt::VBlank.into_byte());
+
+        gpu.set_frame_update_flag();
+        for _ in 0..FRAME_CYCLES {
+            gpu.cycle();
+        }
+        assert!(gpu.consume_frame_ready());
+        assert_eq!(
+            gpu.consume_interrupts(),
+            (1 << Interrupt::Frame.into_byte()) | (1 << Interrupt::VBlank.into_byte())
+        );
+        assert!(!gpu.consume_frame_update_flag());
+
+        // Frames which aren't presented are never shown, even partly
+        gpu.vram.fill(0x11);
+        for _ in 0..FRAME_CYCLES {
+            gpu.cycle();
+        }
+        assert!(!gpu.consume_frame_ready());
+        assert!(gpu.frame_buffer().iter().all(|&pixel| pixel == 0x0));
+
+        gpu.set_frame_update_flag();
+        for _ in 0..FRAME_CYCLES {
+            gpu.cycle();
+        }
+        assert!(gpu.consume_frame_ready());
+        assert!(gpu.frame_buffer().iter().all(|&pixel| pixel == 0x1));
+    }
+
+    #[test]
+    fn test_palette_regs() {
+        let mut gpu = Gpu::default();
+
+        assert_eq!(gpu.read_palette_dword(4), 0x0080_0000);
+        gpu.write_palette_dword(4, 0xFF12_3456);
+        assert_eq!(gpu.palette()[1], 0x0012_3456);
+        assert_eq!(gpu.read_palette_byte(4), 0x56);
+        assert_eq!(gpu.read_palette_byte(6), 0x12);
+        assert_eq!(gpu.read_palette_byte(7), 0x00);
+
+        gpu.write_palette_word((PALETTE_SIZE - 4) as u32, 0xABCD);
+        assert_eq!(gpu.palette()[15], 0x00FF_ABCD);
+        assert_eq!(gpu.palette()[0], DEFAULT_PALETTE[0]);
+
+        gpu.write_palette_byte((PALETTE_RAM_SIZE - 2) as u32, 0x12);
+        assert_eq!(gpu.palette()[NUM_PALETTE_COLOURS - 1], 0x0012_EEEE);
+        assert_eq!(DEFAULT_PALETTE[16 + 36 * 5 + 6 * 2 + 1], 0xFF875F);
+
+        gpu.set_palette(&[0x123456; 2]);
+        assert_eq!(
+            gpu.palette()[0..3],
+            [0x123456, 0x123456, DEFAULT_PALETTE[2]]
+        );
+    }
+
+    #[test]
+    fn test_text_mode() {
+        let mut gpu = Gpu::default();
+        gpu.vram[0..2].copy_from_slice(&[b'|', 0x4E]);
+        gpu.video_mode = VideoMode::Text;
+        gpu.start_frame();
+
+        gpu.draw_line(0);
+        assert_eq!(
+            gpu.draw_buffer[0..8],
+            [0x4, 0x4, 0x4, 0xE, 0x4, 0x4, 0x4, 0x4]
+        );
+        assert_eq!(gpu.draw_buffer[8..DISPLAY_WIDTH], [0; DISPLAY_WIDTH - 8]);
+
+        gpu.video_mode = VideoMode::Bitmap;
+        gpu.start_frame();
+        gpu.draw_line(0);
+        assert_eq!(gpu.draw_buffer[0..4], [0x7, 0xC, 0x4, 0xE]);
+    }
+
+    #[test]
+    fn test_tile_layer() {
+        let mut gpu = Gpu::default();
+        gpu.vram[0..(DISPLAY_WIDTH / 2)].fill(0x11);
+        // Tile 1: colour 0x2 with a transparent (0x0) first pixel on each row
+        for row in 0..TILE_SIZE {
+            let start = TILE_PATTERN_SIZE + row * TILE_SIZE / 2;
+            gpu.pattern_ram.contents[start..(start + 4)].copy_from_slice(&[0x02, 0x22, 0x22, 0x22]);
+        }
+        gpu.tile_map.write_word(2, 0x0001);
+        gpu.tile_map.write_word(4, 0x4001);
+
+        gpu.draw_line(0);
+        assert_eq!(gpu.draw_buffer[0..4], [0x1; 4]);
+
+        gpu.layer_control = TILE_LAYER_ENABLED_BIT;
+        gpu.draw_line(0);
+        assert_eq!(gpu.draw_buffer[0..9], [0x0; 9]);
+        assert_eq!(gpu.draw_buffer[9..16], [0x2; 7]);
+        assert_eq!(gpu.draw_buffer[22..24], [0x2, 0x0]);
+
+        gpu.layer_control = TILE_LAYER_ENABLED_BIT | TILE_COLOUR_KEY_BIT;
+        gpu.tile_scroll_x = 4;
+        gpu.tile_scroll_y = (TILE_MAP_HEIGHT * TILE_SIZE) as u16;
+        gpu.draw_line(0);
+        assert_eq!(
+            gpu.draw_buffer[0..8],
+            [0x1, 0x1, 0x1, 0x1, 0x1, 0x2, 0x2, 0x2]
+        );
+    }
+
+    #[test]
+    fn test_sprites() {
+        let mut gpu = Gpu::default();
+        gpu.pattern_ram.contents[0..(8 * 4)].fill(0x33);
+        gpu.pattern_ram.contents[0] = 0x03;
+        gpu.pattern_ram.contents[0x100..(0x100 + 16 * 8)].fill(0x44);
+
+        // Sprite 0: 8x8 at (3, 1), colour 0x0 transparent
+        gpu.oam.contents[0..8].copy_from_slice(&[3, 0, 1, 0, 0x00, 0x00, 0x00, 0x09]);
+        // Sprite 1: 16x16 at (-12, 0), flipped horizontally, drawn underneath sprite 0
+        gpu.oam.contents[8..16].copy_from_slice(&[0xF4, 0xFF, 0, 0, 0x00, 0x01, 0x11, 0x03]);
+        assert_eq!(Sprite::from_bytes(&gpu.oam.contents[8..16]).x, -12);
+
+        const LINE: usize = DISPLAY_WIDTH;
+        gpu.draw_line(1);
+        assert_eq!(gpu.draw_buffer[LINE..(LINE + 14)], [0x0; 14]);
+
+        gpu.layer_control = SPRITES_ENABLED_BIT;
+        gpu.draw_line(0);
+        assert_eq!(
+            gpu.draw_buffer[0..8],
+            [0x4, 0x4, 0x4, 0x4, 0x0, 0x0, 0x0, 0x0]
+        );
+        gpu.draw_line(1);
+        assert_eq!(gpu.draw_buffer[LINE..(LINE + 4)], [0x4; 4]);
+        assert_eq!(gpu.draw_buffer[(LINE + 4)..(LINE + 11)], [0x3; 7]);
+        assert_eq!(gpu.draw_buffer[(LINE + 11)..(LINE + 14)], [0x0; 3]);
+        gpu.draw_line(2);
+        assert_eq!(gpu.draw_buffer[(2 * LINE)..(2 * LINE + 3)], [0x4; 3]);
+        assert_eq!(gpu.draw_buffer[(2 * LINE + 3)..(2 * LINE + 11)], [0x3; 8]);
+    }
+
+    #[test]
+    fn test_line_compare() {
+        let mut gpu = Gpu {
+            line_compare: 2,
+            ..Default::default()
+        };
+
+        for _ in 0..(LINE_CYCLES * 2) {
+            gpu.cycle();
+        }
+        assert_eq!(gpu.consume_interrupts(), 0);
+        gpu.cycle();
+        assert_eq!(gpu.scanline(), 2);
+        assert_eq!(
+            gpu.consume_interrupts(),
+            1 << Interrupt::LineCompare.into_byte()
+        );
+    }
+
+    #[test]
+    fn test_mid_frame_vram_changes() {
+        let mut gpu = Gpu::default();
+
+        // Draw the first line, then change VRAM
+        gpu.vram.fill(0x11);
+        for _ in 0..LINE_CYCLES {
+            gpu.cycle();
+        }
+        gpu.vram.fill(0x22);
+        for _ in LINE_CYCLES..=(FRAME_CYCLES - VBLANK_CYCLES) {
+            gpu.cycle();
+        }
+
+        assert!(gpu.consume_frame_ready());
+        assert!(gpu.frame_buffer()[..DISPLAY_WIDTH]
+            .iter()
+            .all(|&pixel| pixel == 0x1));
+        assert!(gpu.frame_buffer()[DISPLAY_WIDTH..]
+            .iter()
+            .all(|&pixel| pixel == 0x2));
+    }
+
+    #[test]
+    fn test_video_modes() {
+        let mut gpu = Gpu {
+            video_mode: VideoMode::LowRes,
+            ..Default::default()
+        };
+        gpu.vram[0..3].copy_from_slice(&[0xC4, 0x00, 0x1F]);
+        gpu.vram[320] = 0xAB;
+
+        // The video mode takes effect at the start of each frame
+        for _ in 0..=(FRAME_CYCLES - VBLANK_CYCLES) {
+            gpu.cycle();
+        }
+        assert!(gpu.consume_frame_ready());
+        assert_eq!(gpu.frame_size(), (320, 240));
+        assert_eq!(gpu.frame_buffer().len(), 320 * 240);
+        assert_eq!(gpu.frame_buffer()[0..3], [0xC4, 0x00, 0x1F]);
+        assert_eq!(gpu.frame_buffer()[320], 0xAB);
+
+        gpu.video_mode = VideoMode::HighRes;
+        assert_eq!(gpu.frame_size(), (320, 240));
+        for _ in 0..FRAME_CYCLES {
+            gpu.cycle();
+        }
+        assert!(gpu.consume_frame_ready());
+        assert_eq!(gpu.frame_size(), (1280, 960));
+        assert_eq!(gpu.frame_buffer().len(), 1280 * 960);
+        assert_eq!(
+            gpu.frame_buffer()[0..8],
+            [0xF, 0xF, 0x0, 0x0, 0x0, 0xF, 0x0, 0x0]
+        );
+        assert_eq!(
+            gpu.frame_buffer()[(8 * 2)..(8 * 3)],
+            [0x0, 0x0, 0x0, 0xF, 0xF, 0xF, 0xF, 0xF]
+        );
+        assert_eq!(
+            gpu.frame_buffer()[(1280 * 2)..(1280 * 2 + 8)],
+            [0xF, 0x0, 0xF, 0x0, 0xF, 0x0, 0xF, 0xF]
+        );
+    }
+
+    #[test]
+    fn test_page_flip() {
+        let mut gpu = Gpu::default();
+        gpu.vram.fill(0x11);
+
+        // Draw offscreen into page 1, then display it from the next vblank onwards
+        gpu.set_vram_page_reg(ACCESS_PAGE_BIT);
+        assert!(gpu.vram.iter().all(|&byte| byte == 0x00));
+        gpu.vram.fill(0x22);
+        gpu.set_vram_page_reg(ACCESS_PAGE_BIT | DISPLAY_PAGE_BIT);
+        assert_eq!(gpu.vram_page_reg(), ACCESS_PAGE_BIT | DISPLAY_PAGE_BIT);
+
+        for _ in 0..=(FRAME_CYCLES - VBLANK_CYCLES) {
+            gpu.cycle();
+        }
+        assert!(gpu.consume_frame_ready());
+        assert!(gpu.frame_buffer().iter().all(|&pixel| pixel == 0x1));
+        assert_eq!(
+            gpu.vram_page_reg(),
+            ACCESS_PAGE_BIT | DISPLAY_PAGE_BIT | DISPLAYED_PAGE_BIT
+        );
+
+        // Page 1 is displayed while the CPU goes back to drawing into page 0
+        gpu.set_vram_page_reg(DISPLAY_PAGE_BIT);
+        assert!(gpu.vram.iter().all(|&byte| byte == 0x11));
+        for _ in 0..FRAME_CYCLES {
+            gpu.cycle();
         }
+        assert!(gpu.consume_frame_ready());
+        assert!(gpu.frame_buffer().iter().all(|&pixel| pixel == 0x2));
     }
 }
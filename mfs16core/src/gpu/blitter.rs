@@ -0,0 +1,322 @@
+//! The blitter. Copies and fills rectangles of pixels in VRAM without the CPU's help.
+use super::VideoMode;
+
+/// The number of cycles the blitter takes to set up each operation.
+pub const BLIT_SETUP_CYCLES: u32 = 16;
+/// The number of pixels the blitter copies or fills per cycle.
+pub const BLIT_PIXELS_PER_CYCLE: u32 = 8;
+
+/// The operations the blitter can perform. Written to the blitter control register to start an
+/// operation.
+#[repr(u8)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BlitCommand {
+    /// Copy a rectangle of pixels from ROM, RAM or VRAM to VRAM.
+    Copy = 1,
+    /// Fill a rectangle of VRAM with a single colour.
+    Fill = 2,
+    /// Copy a rectangle of pixels from ROM, RAM or VRAM to VRAM, skipping pixels of the colour
+    /// key.
+    KeyedCopy = 3,
+}
+impl BlitCommand {
+    /// Get the [BlitCommand] matching the given register value, returning [None] if no command
+    /// matches.
+    pub fn try_from_u8(val: u8) -> Option<Self> {
+        match val {
+            1 => Some(Self::Copy),
+            2 => Some(Self::Fill),
+            3 => Some(Self::KeyedCopy),
+            _ => None,
+        }
+    }
+}
+
+/// The blitter registers, describing the source and destination rectangles of an operation.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct BlitRegs {
+    /// Address of the source pixel data in ROM, RAM or VRAM.
+    pub src_addr: u32,
+    /// The width of the source pixel data in pixels.
+    pub src_stride: u16,
+    /// X position of the source rectangle within the source pixel data.
+    pub src_x: u16,
+    /// Y position of the source rectangle within the source pixel data.
+    pub src_y: u16,
+    /// X position of the destination rectangle on the screen.
+    pub dst_x: u16,
+    /// Y position of the destination rectangle on the screen.
+    pub dst_y: u16,
+    /// The width of the rectangle in pixels.
+    pub width: u16,
+    /// The height of the rectangle in pixels.
+    pub height: u16,
+    /// The fill colour or colour key. Only the lowest bits, as many as there are bits per pixel
+    /// in the video mode, are used.
+    pub colour: u8,
+}
+impl BlitRegs {
+    /// Get the number of cycles an operation on these registers takes.
+    pub fn cycles(&self) -> u32 {
+        let pixels = self.width as u32 * self.height as u32;
+        BLIT_SETUP_CYCLES + pixels.div_ceil(BLIT_PIXELS_PER_CYCLE)
+    }
+
+    /// Get the range of source addresses read by a copy in the given [VideoMode], or [None] if
+    /// nothing is copied.
+    pub fn src_range(&self, mode: VideoMode) -> Option<(u32, u32)> {
+        if self.width == 0 || self.height == 0 {
+            return None;
+        }
+        let pixels_per_byte = 8 / mode.bits_per_pixel();
+        let first = self.src_pixel(0, 0);
+        let last = self.src_pixel(self.width as usize - 1, self.height as usize - 1);
+        Some((
+            self.src_addr.wrapping_add((first / pixels_per_byte) as u32),
+            self.src_addr
+                .wrapping_add((last / pixels_per_byte) as u32 + 1),
+        ))
+    }
+
+    /// Get the index of the given pixel of the source rectangle, counting from the source address.
+    fn src_pixel(&self, x: usize, y: usize) -> usize {
+        (self.src_y as usize + y) * self.src_stride as usize + self.src_x as usize + x
+    }
+
+    /// Call the given function for each pixel of the rectangle which lies on the screen of the
+    /// given [VideoMode], with the pixel's position in the rectangle and its index in VRAM.
+    fn for_each_dst_pixel(&self, mode: VideoMode, mut f: impl FnMut(usize, usize, usize)) {
+        let (width, height) = (mode.width(), mode.height());
+        for y in 0..self.height as usize {
+            let dst_y = self.dst_y as usize + y;
+            if dst_y >= height {
+                break;
+            }
+            for x in 0..self.width as usize {
+                let dst_x = self.dst_x as usize + x;
+                if dst_x >= width {
+                    break;
+                }
+                f(x, y, dst_y * width + dst_x);
+            }
+        }
+    }
+}
+
+/// Responsible for the blitter operation currently underway.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct Blitter {
+    /// The blitter registers. These are copied when an operation starts, so they can be changed
+    /// while the blitter is busy.
+    pub regs: BlitRegs,
+    /// The operation currently underway, along with the registers it was started with.
+    job: Option<(BlitCommand, BlitRegs)>,
+    /// The number of cycles until the current operation is complete.
+    cycles_remaining: u32,
+}
+impl Blitter {
+    /// Start the given operation. Returns false, doing nothing, if the blitter is busy.
+    pub fn start(&mut self, command: BlitCommand) -> bool {
+        if self.is_busy() {
+            return false;
+        }
+        self.job = Some((command, self.regs));
+        self.cycles_remaining = self.regs.cycles();
+        true
+    }
+
+    /// Check whether an operation is underway.
+    pub fn is_busy(&self) -> bool {
+        self.job.is_some()
+    }
+
+    /// Count down one cycle. Returns the operation once it is due to be performed.
+    pub fn cycle(&mut self) -> Option<(BlitCommand, BlitRegs)> {
+        self.cycles_remaining = self.cycles_remaining.saturating_sub(1);
+        if self.cycles_remaining == 0 {
+            self.job.take()
+        } else {
+            None
+        }
+    }
+}
+
+/// Fill the destination rectangle of VRAM with the fill colour.
+pub(super) fn fill(regs: &BlitRegs, mode: VideoMode, vram: &mut [u8]) {
+    let bits = mode.bits_per_pixel();
+    let colour = regs.colour & pixel_mask(bits);
+    regs.for_each_dst_pixel(mode, |_, _, index| {
+        write_packed_pixel(vram, index, bits, colour)
+    });
+}
+
+/// Copy the source rectangle to the destination rectangle of VRAM. The source slice must hold
+/// the bytes of [BlitRegs::src_range]. If keyed, pixels of the colour key are skipped.
+pub(super) fn copy(regs: &BlitRegs, mode: VideoMode, src: &[u8], keyed: bool, vram: &mut [u8]) {
+    let bits = mode.bits_per_pixel();
+    let colour_key = keyed.then_some(regs.colour & pixel_mask(bits));
+    // The source slice starts at the byte holding the first pixel
+    let pixels_per_byte = 8 / bits;
+    let src_start = regs.src_pixel(0, 0) / pixels_per_byte * pixels_per_byte;
+    regs.for_each_dst_pixel(mode, |x, y, index| {
+        let colour = read_packed_pixel(src, regs.src_pixel(x, y) - src_start, bits);
+        if Some(colour) != colour_key {
+            write_packed_pixel(vram, index, bits, colour);
+        }
+    });
+}
+
+/// Get the mask of the bits of a pixel with the given number of bits.
+fn pixel_mask(bits: usize) -> u8 {
+    ((1u16 << bits) - 1) as u8
+}
+
+/// Get the shift of the pixel at the given index within its byte. The leftmost pixel is held in
+/// the most significant bits.
+fn pixel_shift(index: usize, bits: usize) -> usize {
+    8 - bits * (index % (8 / bits) + 1)
+}
+
+/// Read the pixel at the given index from a slice of pixel data with the given number of bits
+/// per pixel.
+fn read_packed_pixel(pixels: &[u8], index: usize, bits: usize) -> u8 {
+    (pixels[index * bits / 8] >> pixel_shift(index, bits)) & pixel_mask(bits)
+}
+
+/// Write the pixel at the given index to a slice of pixel data with the given number of bits
+/// per pixel.
+fn write_packed_pixel(pixels: &mut [u8], index: usize, bits: usize, colour: u8) {
+    let shift = pixel_shift(index, bits);
+    let mask = pixel_mask(bits) << shift;
+    let byte = &mut pixels[index * bits / 8];
+    *byte = (*byte & !mask) | ((colour << shift) & mask);
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{DISPLAY_HEIGHT, DISPLAY_WIDTH, VRAM_SIZE};
+
+    const LINE: usize = DISPLAY_WIDTH / 2;
+
+    #[test]
+    fn test_fill() {
+        let mut vram = vec![0x00; VRAM_SIZE];
+        let regs = BlitRegs {
+            dst_x: 1,
+            dst_y: 1,
+            width: 4,
+            height: 2,
+            colour: 0xFA,
+            ..Default::default()
+        };
+        fill(&regs, VideoMode::Bitmap, &mut vram);
+        assert_eq!(vram[0..4], [0, 0, 0, 0]);
+        assert_eq!(vram[LINE..(LINE + 4)], [0x0A, 0xAA, 0xA0, 0x00]);
+        assert_eq!(vram[(2 * LINE)..(2 * LINE + 4)], [0x0A, 0xAA, 0xA0, 0x00]);
+        assert_eq!(vram[(3 * LINE)..(3 * LINE + 4)], [0, 0, 0, 0]);
+
+        // Rectangles are clipped at the edges of the screen
+        let regs = BlitRegs {
+            dst_x: (DISPLAY_WIDTH - 1) as u16,
+            dst_y: (DISPLAY_HEIGHT - 1) as u16,
+            width: 8,
+            height: 8,
+            colour: 0x3,
+            ..Default::default()
+        };
+        fill(&regs, VideoMode::Bitmap, &mut vram);
+        assert_eq!(vram[VRAM_SIZE - 2..], [0x00, 0x03]);
+        assert_eq!(regs.cycles(), BLIT_SETUP_CYCLES + 8);
+    }
+
+    #[test]
+    fn test_copy() {
+        let mut vram = vec![0x00; VRAM_SIZE];
+        // 6x2 source image at an odd x position in a 10-pixel wide buffer
+        let src = [
+            0x01, 0x23, 0x45, 0x67, 0x89, //
+            0xFE, 0xDC, 0xBA, 0x98, 0x76,
+        ];
+        let regs = BlitRegs {
+            src_addr: 0x100,
+            src_stride: 10,
+            src_x: 1,
+            dst_x: 2,
+            width: 3,
+            height: 2,
+            colour: 0x2,
+            ..Default::default()
+        };
+        assert_eq!(regs.src_range(VideoMode::Bitmap), Some((0x100, 0x107)));
+
+        copy(&regs, VideoMode::Bitmap, &src, false, &mut vram);
+        assert_eq!(vram[0..3], [0x00, 0x12, 0x30]);
+        assert_eq!(vram[LINE..(LINE + 3)], [0x00, 0xED, 0xC0]);
+
+        let mut vram = vec![0x00; VRAM_SIZE];
+        let regs = BlitRegs { dst_x: 1, ..regs };
+        copy(&regs, VideoMode::Bitmap, &src, true, &mut vram);
+        assert_eq!(vram[0..3], [0x01, 0x03, 0x00]);
+        assert_eq!(vram[LINE..(LINE + 3)], [0x0E, 0xDC, 0x00]);
+    }
+
+    #[test]
+    fn test_video_modes() {
+        // Low resolution: one byte per pixel, 320 pixels per line
+        let mut vram = vec![0x00; VRAM_SIZE];
+        let regs = BlitRegs {
+            dst_x: 318,
+            dst_y: 1,
+            width: 4,
+            height: 1,
+            colour: 0xC4,
+            ..Default::default()
+        };
+        fill(&regs, VideoMode::LowRes, &mut vram);
+        assert_eq!(vram[318..322], [0x00, 0x00, 0x00, 0x00]);
+        assert_eq!(vram[(320 + 318)..(320 + 322)], [0xC4, 0xC4, 0x00, 0x00]);
+
+        let src = [0x11, 0xC4, 0x22, 0x33];
+        let regs = BlitRegs {
+            src_stride: 4,
+            width: 4,
+            height: 1,
+            colour: 0xC4,
+            ..Default::default()
+        };
+        assert_eq!(regs.src_range(VideoMode::LowRes), Some((0, 4)));
+        copy(&regs, VideoMode::LowRes, &src, true, &mut vram);
+        assert_eq!(vram[0..4], [0x11, 0x00, 0x22, 0x33]);
+
+        // High resolution: one bit per pixel, 1280 pixels per line
+        let mut vram = vec![0x00; VRAM_SIZE];
+        let regs = BlitRegs {
+            dst_x: 3,
+            dst_y: 1,
+            width: 7,
+            height: 1,
+            colour: 0xFF,
+            ..Default::default()
+        };
+        fill(&regs, VideoMode::HighRes, &mut vram);
+        assert_eq!(vram[0..2], [0x00, 0x00]);
+        assert_eq!(vram[160..162], [0b0001_1111, 0b1100_0000]);
+
+        let mut vram = vec![0x00; VRAM_SIZE];
+        let src = [0b1010_1100, 0b0110_0000];
+        let regs = BlitRegs {
+            src_stride: 16,
+            src_x: 4,
+            dst_x: 6,
+            width: 6,
+            height: 1,
+            ..Default::default()
+        };
+        assert_eq!(regs.src_range(VideoMode::HighRes), Some((0, 2)));
+        copy(&regs, VideoMode::HighRes, &src, false, &mut vram);
+        assert_eq!(vram[0..2], [0b0000_0011, 0b0001_0000]);
+    }
+}
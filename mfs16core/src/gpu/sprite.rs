@@ -0,0 +1,116 @@
+//! Hardware sprites, drawn from the object attribute memory (OAM) over the rest of the frame.
+use crate::{helpers::combine_u8_le, memory::Memory};
+
+use super::{read_pixel, PATTERN_RAM_SIZE};
+
+/// The number of sprites in the OAM.
+pub const NUM_SPRITES: usize = 64;
+/// The number of OAM bytes describing one sprite.
+pub const SPRITE_ENTRY_SIZE: usize = 8;
+/// OAM size in bytes.
+pub const OAM_SIZE: usize = NUM_SPRITES * SPRITE_ENTRY_SIZE;
+
+/// Sprite widths and heights are multiples of this many pixels.
+const SPRITE_SIZE_UNIT: usize = 8;
+
+/// Sprite attribute bit: the sprite is drawn.
+const ENABLED_BIT: u8 = 0b0000_0001;
+/// Sprite attribute bit: the sprite is flipped horizontally.
+const FLIP_X_BIT: u8 = 0b0000_0010;
+/// Sprite attribute bit: the sprite is flipped vertically.
+const FLIP_Y_BIT: u8 = 0b0000_0100;
+/// Sprite attribute bit: pixels of the colour key are transparent.
+const COLOUR_KEY_BIT: u8 = 0b0000_1000;
+
+/// A single sprite, as described by its OAM entry.
+///
+/// | Byte(s) | Description                                                                  |
+/// | ------- | ---------------------------------------------------------------------------- |
+/// | 0-1     | X position of the left edge (signed little-endian word).                     |
+/// | 2-3     | Y position of the top edge (signed little-endian word).                      |
+/// | 4-5     | Pattern RAM address of the pattern (little-endian word).                     |
+/// | 6       | Size. Bits 0-3: (width / 8) - 1. Bits 4-7: (height / 8) - 1.                 |
+/// | 7       | Attributes. Bit 0: enabled. Bit 1: flip x. Bit 2: flip y. Bit 3: colour key. |
+/// |         | Bits 4-7: colour key.                                                        |
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Sprite {
+    /// X position of the left edge of the sprite.
+    pub x: i16,
+    /// Y position of the top edge of the sprite.
+    pub y: i16,
+    /// Pattern RAM address of the sprite's pattern.
+    pub pattern: u16,
+    /// Width of the sprite in pixels.
+    pub width: usize,
+    /// Height of the sprite in pixels.
+    pub height: usize,
+    /// The attribute byte of the sprite.
+    attributes: u8,
+}
+impl Sprite {
+    /// Decode a [Sprite] from its [SPRITE_ENTRY_SIZE] bytes of OAM.
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        Self {
+            x: combine_u8_le(bytes[0], bytes[1]) as i16,
+            y: combine_u8_le(bytes[2], bytes[3]) as i16,
+            pattern: combine_u8_le(bytes[4], bytes[5]),
+            width: ((bytes[6] & 0x0F) as usize + 1) * SPRITE_SIZE_UNIT,
+            height: ((bytes[6] >> 4) as usize + 1) * SPRITE_SIZE_UNIT,
+            attributes: bytes[7],
+        }
+    }
+
+    /// Check whether the sprite is drawn.
+    pub fn is_enabled(&self) -> bool {
+        self.attributes & ENABLED_BIT != 0
+    }
+
+    /// Get the colour which is transparent in this sprite, if any.
+    pub fn colour_key(&self) -> Option<u8> {
+        if self.attributes & COLOUR_KEY_BIT != 0 {
+            Some(self.attributes >> 4)
+        } else {
+            None
+        }
+    }
+
+    /// Draw the part of this sprite which lies on the given line of the screen.
+    fn draw_line(&self, line_pixels: &mut [u8], line: u16, pattern_ram: &Memory) {
+        let row = line as i32 - self.y as i32;
+        if !self.is_enabled() || row < 0 || row >= self.height as i32 {
+            return;
+        }
+        let row = if self.attributes & FLIP_Y_BIT != 0 {
+            self.height - 1 - row as usize
+        } else {
+            row as usize
+        };
+        let row_start = self.pattern as usize + row * self.width / 2;
+        let colour_key = self.colour_key();
+
+        for col in 0..self.width {
+            let x = self.x as i32 + col as i32;
+            if x < 0 || x >= line_pixels.len() as i32 {
+                continue;
+            }
+            let pattern_col = if self.attributes & FLIP_X_BIT != 0 {
+                self.width - 1 - col
+            } else {
+                col
+            };
+            let byte_index = (row_start + pattern_col / 2) % PATTERN_RAM_SIZE;
+            let colour = read_pixel(&pattern_ram.contents[byte_index..], pattern_col % 2);
+            if Some(colour) != colour_key {
+                line_pixels[x as usize] = colour;
+            }
+        }
+    }
+}
+
+/// Draw every enabled sprite in the OAM onto the given line of the screen. Sprites with lower
+/// OAM indices are drawn on top.
+pub(super) fn draw_line(line_pixels: &mut [u8], line: u16, oam: &Memory, pattern_ram: &Memory) {
+    for entry in oam.contents.chunks_exact(SPRITE_ENTRY_SIZE).rev() {
+        Sprite::from_bytes(entry).draw_line(line_pixels, line, pattern_ram);
+    }
+}
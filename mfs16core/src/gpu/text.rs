@@ -0,0 +1,273 @@
+//! Text mode. Renders a buffer of character cells with the built-in font ROM.
+use crate::{DISPLAY_HEIGHT, DISPLAY_WIDTH};
+
+/// The width and height of a character cell in pixels.
+pub const CHAR_SIZE: usize = 8;
+/// The number of character cells in each row of the screen.
+pub const TEXT_COLUMNS: usize = DISPLAY_WIDTH / CHAR_SIZE;
+/// The number of rows of character cells on the screen.
+pub const TEXT_ROWS: usize = DISPLAY_HEIGHT / CHAR_SIZE;
+/// The number of VRAM bytes making up one character cell: the code point, then the colours.
+pub const TEXT_CELL_SIZE: usize = 2;
+
+/// The first code point in the font ROM.
+const FIRST_CHAR: u8 = 0x20;
+/// The last code point in the font ROM.
+const LAST_CHAR: u8 = 0x7E;
+
+/// The glyphs of the printable ASCII characters, one byte per row with the leftmost pixel in the
+/// highest bit.
+const FONT: [[u8; CHAR_SIZE]; (LAST_CHAR - FIRST_CHAR + 1) as usize] = [
+    // ' '
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+    // '!'
+    [0x10, 0x10, 0x10, 0x10, 0x10, 0x00, 0x10, 0x00],
+    // '"'
+    [0x28, 0x28, 0x28, 0x00, 0x00, 0x00, 0x00, 0x00],
+    // '#'
+    [0x28, 0x28, 0x7C, 0x28, 0x7C, 0x28, 0x28, 0x00],
+    // '$'
+    [0x10, 0x3C, 0x50, 0x38, 0x14, 0x78, 0x10, 0x00],
+    // '%'
+    [0x24, 0x28, 0x08, 0x10, 0x20, 0x28, 0x48, 0x00],
+    // '&'
+    [0x38, 0x40, 0x20, 0x3C, 0x48, 0x44, 0x38, 0x00],
+    // '\''
+    [0x10, 0x10, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00],
+    // '('
+    [0x18, 0x20, 0x20, 0x20, 0x20, 0x20, 0x18, 0x00],
+    // ')'
+    [0x30, 0x08, 0x08, 0x08, 0x08, 0x08, 0x30, 0x00],
+    // '*'
+    [0x28, 0x10, 0x28, 0x00, 0x00, 0x00, 0x00, 0x00],
+    // '+'
+    [0x00, 0x10, 0x10, 0x7C, 0x10, 0x10, 0x00, 0x00],
+    // ','
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x10, 0x20],
+    // '-'
+    [0x00, 0x00, 0x00, 0x38, 0x00, 0x00, 0x00, 0x00],
+    // '.'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x00],
+    // '/'
+    [0x04, 0x08, 0x08, 0x10, 0x20, 0x20, 0x40, 0x00],
+    // '0'
+    [0x38, 0x44, 0x4C, 0x54, 0x64, 0x44, 0x38, 0x00],
+    // '1'
+    [0x10, 0x30, 0x50, 0x10, 0x10, 0x10, 0x7C, 0x00],
+    // '2'
+    [0x38, 0x44, 0x44, 0x08, 0x10, 0x20, 0x7C, 0x00],
+    // '3'
+    [0x7C, 0x08, 0x10, 0x38, 0x04, 0x04, 0x78, 0x00],
+    // '4'
+    [0x28, 0x28, 0x48, 0x7C, 0x08, 0x08, 0x08, 0x00],
+    // '5'
+    [0x7C, 0x40, 0x40, 0x38, 0x04, 0x44, 0x38, 0x00],
+    // '6'
+    [0x38, 0x44, 0x40, 0x78, 0x44, 0x44, 0x38, 0x00],
+    // '7'
+    [0x7C, 0x04, 0x08, 0x7C, 0x10, 0x20, 0x20, 0x00],
+    // '8'
+    [0x38, 0x44, 0x44, 0x38, 0x44, 0x44, 0x38, 0x00],
+    // '9'
+    [0x38, 0x44, 0x44, 0x3C, 0x04, 0x04, 0x78, 0x00],
+    // ':'
+    [0x00, 0x10, 0x10, 0x00, 0x00, 0x10, 0x10, 0x00],
+    // ';'
+    [0x00, 0x10, 0x10, 0x00, 0x00, 0x10, 0x10, 0x20],
+    // '<'
+    [0x04, 0x08, 0x10, 0x20, 0x10, 0x08, 0x04, 0x00],
+    // '='
+    [0x00, 0x00, 0x38, 0x00, 0x38, 0x00, 0x00, 0x00],
+    // '>'
+    [0x40, 0x20, 0x10, 0x08, 0x10, 0x20, 0x40, 0x00],
+    // '?'
+    [0x38, 0x44, 0x44, 0x08, 0x10, 0x00, 0x10, 0x00],
+    // '@'
+    [0x38, 0x44, 0x4C, 0x54, 0x5C, 0x40, 0x3C, 0x00],
+    // 'A'
+    [0x10, 0x28, 0x44, 0x44, 0x7C, 0x44, 0x44, 0x00],
+    // 'B'
+    [0x78, 0x44, 0x44, 0x78, 0x44, 0x44, 0x78, 0x00],
+    // 'C'
+    [0x1C, 0x20, 0x40, 0x40, 0x40, 0x20, 0x1C, 0x00],
+    // 'D'
+    [0x70, 0x48, 0x44, 0x44, 0x44, 0x48, 0x70, 0x00],
+    // 'E'
+    [0x7C, 0x40, 0x40, 0x78, 0x40, 0x40, 0x7C, 0x00],
+    // 'F'
+    [0x7C, 0x40, 0x40, 0x78, 0x40, 0x40, 0x40, 0x00],
+    // 'G'
+    [0x38, 0x44, 0x40, 0x40, 0x4C, 0x44, 0x3C, 0x00],
+    // 'H'
+    [0x44, 0x44, 0x44, 0x7C, 0x44, 0x44, 0x44, 0x00],
+    // 'I'
+    [0x7C, 0x10, 0x10, 0x10, 0x10, 0x10, 0x7C, 0x00],
+    // 'J'
+    [0x7C, 0x08, 0x08, 0x08, 0x08, 0x08, 0x70, 0x00],
+    // 'K'
+    [0x44, 0x48, 0x50, 0x60, 0x50, 0x48, 0x44, 0x00],
+    // 'L'
+    [0x40, 0x40, 0x40, 0x40, 0x40, 0x40, 0x7C, 0x00],
+    // 'M'
+    [0x44, 0x6C, 0x6C, 0x54, 0x54, 0x44, 0x44, 0x00],
+    // 'N'
+    [0x44, 0x44, 0x64, 0x54, 0x4C, 0x44, 0x44, 0x00],
+    // 'O'
+    [0x38, 0x44, 0x44, 0x44, 0x44, 0x44, 0x38, 0x00],
+    // 'P'
+    [0x78, 0x44, 0x44, 0x44, 0x78, 0x40, 0x40, 0x00],
+    // 'Q'
+    [0x38, 0x44, 0x44, 0x44, 0x44, 0x54, 0x38, 0x0C],
+    // 'R'
+    [0x78, 0x44, 0x44, 0x78, 0x50, 0x48, 0x44, 0x00],
+    // 'S'
+    [0x38, 0x44, 0x40, 0x38, 0x04, 0x44, 0x38, 0x00],
+    // 'T'
+    [0x7C, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x00],
+    // 'U'
+    [0x44, 0x44, 0x44, 0x44, 0x44, 0x44, 0x38, 0x00],
+    // 'V'
+    [0x44, 0x44, 0x28, 0x28, 0x28, 0x10, 0x10, 0x00],
+    // 'W'
+    [0x44, 0x44, 0x54, 0x54, 0x6C, 0x6C, 0x44, 0x00],
+    // 'X'
+    [0x44, 0x28, 0x28, 0x10, 0x28, 0x28, 0x44, 0x00],
+    // 'Y'
+    [0x44, 0x44, 0x28, 0x10, 0x10, 0x10, 0x10, 0x00],
+    // 'Z'
+    [0x7C, 0x04, 0x08, 0x10, 0x20, 0x40, 0x7C, 0x00],
+    // '['
+    [0x38, 0x20, 0x20, 0x20, 0x20, 0x20, 0x38, 0x00],
+    // '\\'
+    [0x40, 0x20, 0x20, 0x10, 0x08, 0x08, 0x04, 0x00],
+    // ']'
+    [0x38, 0x08, 0x08, 0x08, 0x08, 0x08, 0x38, 0x00],
+    // '^'
+    [0x10, 0x28, 0x28, 0x00, 0x00, 0x00, 0x00, 0x00],
+    // '_'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x7C],
+    // '`'
+    [0x20, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+    // 'a'
+    [0x00, 0x00, 0x38, 0x04, 0x3C, 0x44, 0x3C, 0x00],
+    // 'b'
+    [0x40, 0x40, 0x78, 0x44, 0x44, 0x44, 0x38, 0x00],
+    // 'c'
+    [0x00, 0x00, 0x3C, 0x40, 0x40, 0x40, 0x3C, 0x00],
+    // 'd'
+    [0x04, 0x04, 0x3C, 0x44, 0x44, 0x44, 0x38, 0x00],
+    // 'e'
+    [0x00, 0x00, 0x38, 0x44, 0x7C, 0x40, 0x3C, 0x00],
+    // 'f'
+    [0x00, 0x0C, 0x10, 0x10, 0x7C, 0x10, 0x10, 0x60],
+    // 'g'
+    [0x00, 0x00, 0x38, 0x44, 0x44, 0x3C, 0x04, 0x38],
+    // 'h'
+    [0x40, 0x40, 0x40, 0x78, 0x44, 0x44, 0x44, 0x00],
+    // 'i'
+    [0x00, 0x10, 0x00, 0x30, 0x10, 0x10, 0x38, 0x00],
+    // 'j'
+    [0x00, 0x10, 0x00, 0x30, 0x10, 0x10, 0x10, 0x60],
+    // 'k'
+    [0x40, 0x40, 0x44, 0x48, 0x50, 0x68, 0x44, 0x00],
+    // 'l'
+    [0x60, 0x20, 0x20, 0x20, 0x20, 0x20, 0x18, 0x00],
+    // 'm'
+    [0x00, 0x00, 0x68, 0x54, 0x54, 0x54, 0x54, 0x00],
+    // 'n'
+    [0x00, 0x00, 0x58, 0x64, 0x44, 0x44, 0x44, 0x00],
+    // 'o'
+    [0x00, 0x00, 0x38, 0x44, 0x44, 0x44, 0x38, 0x00],
+    // 'p'
+    [0x00, 0x00, 0x38, 0x44, 0x44, 0x78, 0x40, 0x40],
+    // 'q'
+    [0x00, 0x00, 0x38, 0x44, 0x44, 0x3C, 0x04, 0x04],
+    // 'r'
+    [0x00, 0x00, 0x58, 0x64, 0x40, 0x40, 0x40, 0x00],
+    // 's'
+    [0x00, 0x00, 0x3C, 0x40, 0x38, 0x04, 0x78, 0x00],
+    // 't'
+    [0x40, 0x40, 0x40, 0x70, 0x40, 0x40, 0x3C, 0x00],
+    // 'u'
+    [0x00, 0x00, 0x44, 0x44, 0x44, 0x4C, 0x34, 0x00],
+    // 'v'
+    [0x00, 0x00, 0x44, 0x44, 0x28, 0x28, 0x10, 0x00],
+    // 'w'
+    [0x00, 0x00, 0x54, 0x54, 0x54, 0x28, 0x28, 0x00],
+    // 'x'
+    [0x00, 0x00, 0x44, 0x28, 0x10, 0x28, 0x44, 0x00],
+    // 'y'
+    [0x00, 0x00, 0x44, 0x44, 0x44, 0x3C, 0x04, 0x78],
+    // 'z'
+    [0x00, 0x00, 0x7C, 0x08, 0x10, 0x20, 0x7C, 0x00],
+    // '{'
+    [0x08, 0x10, 0x10, 0x20, 0x10, 0x10, 0x08, 0x00],
+    // '|'
+    [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x00],
+    // '}'
+    [0x20, 0x10, 0x10, 0x08, 0x10, 0x10, 0x20, 0x00],
+    // '~'
+    [0x00, 0x20, 0x54, 0x08, 0x00, 0x00, 0x00, 0x00],
+];
+
+/// The glyph drawn for code points above [LAST_CHAR].
+const UNKNOWN_GLYPH: [u8; CHAR_SIZE] = [0x7C, 0x7C, 0x7C, 0x7C, 0x7C, 0x7C, 0x7C, 0x7C];
+
+/// Get the given row of the glyph of the given code point. Control characters are blank.
+pub fn glyph_row(code: u8, row: usize) -> u8 {
+    match code {
+        FIRST_CHAR..=LAST_CHAR => FONT[(code - FIRST_CHAR) as usize][row],
+        0x00..FIRST_CHAR => 0x00,
+        _ => UNKNOWN_GLYPH[row],
+    }
+}
+
+/// Draw the given line of the screen from the character cells in VRAM. Each cell is a code point
+/// followed by a colour byte, with the foreground colour in the lower nibble and the background
+/// colour in the upper nibble.
+pub(super) fn draw_line(line_pixels: &mut [u8], line: u16, vram: &[u8]) {
+    let row_start = (line as usize / CHAR_SIZE) * TEXT_COLUMNS * TEXT_CELL_SIZE;
+    let glyph_row_num = line as usize % CHAR_SIZE;
+
+    for (col, cell) in vram[row_start..(row_start + TEXT_COLUMNS * TEXT_CELL_SIZE)]
+        .chunks_exact(TEXT_CELL_SIZE)
+        .enumerate()
+    {
+        let glyph_row = glyph_row(cell[0], glyph_row_num);
+        let (foreground, background) = (cell[1] & 0x0F, cell[1] >> 4);
+        for x in 0..CHAR_SIZE {
+            let colour = if glyph_row & (0x80 >> x) != 0 {
+                foreground
+            } else {
+                background
+            };
+            line_pixels[col * CHAR_SIZE + x] = colour;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_draw_line() {
+        let mut vram = vec![0x00; TEXT_COLUMNS * TEXT_CELL_SIZE * 2];
+        vram[0..4].copy_from_slice(&[b'A', 0x1F, 0x01, 0x1F]);
+        vram[(TEXT_COLUMNS * TEXT_CELL_SIZE)..][..2].copy_from_slice(&[0xFF, 0x20]);
+        let mut line_pixels = vec![0x0; DISPLAY_WIDTH];
+
+        draw_line(&mut line_pixels, 0, &vram);
+        assert_eq!(line_pixels[0..8], [0x1, 0x1, 0x1, 0xF, 0x1, 0x1, 0x1, 0x1]);
+        assert_eq!(line_pixels[8..16], [0x1; 8]);
+        draw_line(&mut line_pixels, 1, &vram);
+        assert_eq!(line_pixels[0..8], [0x1, 0x1, 0xF, 0x1, 0xF, 0x1, 0x1, 0x1]);
+        draw_line(&mut line_pixels, 8, &vram);
+        assert_eq!(
+            line_pixels[0..12],
+            [0x2, 0x0, 0x0, 0x0, 0x0, 0x0, 0x2, 0x2, 0x0, 0x0, 0x0, 0x0]
+        );
+    }
+}
@@ -0,0 +1,62 @@
+//! The scrollable tile map background layer, drawn over VRAM.
+use crate::{helpers::combine_u8_le, memory::Memory};
+
+use super::{read_pixel, PATTERN_RAM_SIZE};
+
+/// The width and height of a tile in pixels.
+pub const TILE_SIZE: usize = 8;
+/// The number of pattern RAM bytes making up one tile.
+pub const TILE_PATTERN_SIZE: usize = TILE_SIZE * TILE_SIZE / 2;
+/// The width of the tile map in tiles.
+pub const TILE_MAP_WIDTH: usize = 128;
+/// The height of the tile map in tiles.
+pub const TILE_MAP_HEIGHT: usize = 64;
+/// Tile map size in bytes. Each tile map entry is one word.
+pub const TILE_MAP_SIZE: usize = TILE_MAP_WIDTH * TILE_MAP_HEIGHT * 2;
+
+/// Tile map entry bits holding the tile number.
+const TILE_NUM_MASK: u16 = 0x07FF;
+/// Tile map entry bit: the tile is flipped horizontally.
+const FLIP_X_BIT: u16 = 0x4000;
+/// Tile map entry bit: the tile is flipped vertically.
+const FLIP_Y_BIT: u16 = 0x8000;
+
+/// Draw the given line of the screen from the tile map, scrolled by the given number of pixels.
+/// The tile map wraps around at its edges. Pixels of the colour key, if any, are transparent.
+pub(super) fn draw_line(
+    line_pixels: &mut [u8],
+    line: u16,
+    tile_map: &Memory,
+    pattern_ram: &Memory,
+    scroll: (u16, u16),
+    colour_key: Option<u8>,
+) {
+    let map_y = (line as usize + scroll.1 as usize) % (TILE_MAP_HEIGHT * TILE_SIZE);
+    let map_row_start = (map_y / TILE_SIZE) * TILE_MAP_WIDTH;
+
+    for (x, pixel) in line_pixels.iter_mut().enumerate() {
+        let map_x = (x + scroll.0 as usize) % (TILE_MAP_WIDTH * TILE_SIZE);
+        let entry_index = (map_row_start + map_x / TILE_SIZE) * 2;
+        let entry = combine_u8_le(
+            tile_map.contents[entry_index],
+            tile_map.contents[entry_index + 1],
+        );
+
+        let mut tile_x = map_x % TILE_SIZE;
+        let mut tile_y = map_y % TILE_SIZE;
+        if entry & FLIP_X_BIT != 0 {
+            tile_x = TILE_SIZE - 1 - tile_x;
+        }
+        if entry & FLIP_Y_BIT != 0 {
+            tile_y = TILE_SIZE - 1 - tile_y;
+        }
+
+        let byte_index = ((entry & TILE_NUM_MASK) as usize * TILE_PATTERN_SIZE
+            + (tile_y * TILE_SIZE + tile_x) / 2)
+            % PATTERN_RAM_SIZE;
+        let colour = read_pixel(&pattern_ram.contents[byte_index..], tile_x % 2);
+        if Some(colour) != colour_key {
+            *pixel = colour;
+        }
+    }
+}
@@ -0,0 +1,85 @@
+//! The video modes of the GPU.
+use crate::{DISPLAY_HEIGHT, DISPLAY_WIDTH, VRAM_SIZE};
+
+use super::{TEXT_CELL_SIZE, TEXT_COLUMNS, TEXT_ROWS};
+
+/// The ways in which the GPU can display the contents of VRAM.
+#[repr(u8)]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum VideoMode {
+    /// 640x480 pixels, 4 bits per pixel.
+    #[default]
+    Bitmap = 0,
+    /// 80x60 character cells, drawn with the built-in font ROM.
+    Text = 1,
+    /// 320x240 pixels, 8 bits per pixel. Each pixel is an index into the full 256-colour palette.
+    LowRes = 2,
+    /// 1280x960 pixels, 1 bit per pixel. Clear pixels use palette colour 0x0 and set pixels use
+    /// palette colour 0xF.
+    HighRes = 3,
+}
+impl VideoMode {
+    /// Get the [VideoMode] matching the given register value, returning [None] if no mode
+    /// matches.
+    pub fn try_from_u8(val: u8) -> Option<Self> {
+        match val {
+            0 => Some(Self::Bitmap),
+            1 => Some(Self::Text),
+            2 => Some(Self::LowRes),
+            3 => Some(Self::HighRes),
+            _ => None,
+        }
+    }
+
+    /// The width of the frames displayed in this mode, in pixels.
+    pub fn width(&self) -> usize {
+        match self {
+            Self::Bitmap | Self::Text => DISPLAY_WIDTH,
+            Self::LowRes => DISPLAY_WIDTH / 2,
+            Self::HighRes => DISPLAY_WIDTH * 2,
+        }
+    }
+
+    /// The height of the frames displayed in this mode, in pixels.
+    pub fn height(&self) -> usize {
+        match self {
+            Self::Bitmap | Self::Text => DISPLAY_HEIGHT,
+            Self::LowRes => DISPLAY_HEIGHT / 2,
+            Self::HighRes => DISPLAY_HEIGHT * 2,
+        }
+    }
+
+    /// The number of bits making up one pixel in the bitmap modes. Text mode counts as 4 bits per
+    /// pixel, the same as [VideoMode::Bitmap].
+    pub(super) fn bits_per_pixel(&self) -> usize {
+        match self {
+            Self::Bitmap | Self::Text => 4,
+            Self::LowRes => 8,
+            Self::HighRes => 1,
+        }
+    }
+
+    /// The number of VRAM bytes making up one line of pixels in the bitmap modes.
+    pub(super) fn line_size(&self) -> usize {
+        match self {
+            Self::Bitmap | Self::Text => self.width() / 2,
+            Self::LowRes => self.width(),
+            Self::HighRes => self.width() / 8,
+        }
+    }
+
+    /// The number of bytes of VRAM which are used in this mode. VRAM past this size can't be
+    /// accessed by the CPU.
+    pub fn vram_size(&self) -> usize {
+        match self {
+            Self::Bitmap => VRAM_SIZE,
+            Self::Text => TEXT_COLUMNS * TEXT_ROWS * TEXT_CELL_SIZE,
+            Self::LowRes | Self::HighRes => self.line_size() * self.height(),
+        }
+    }
+}
+impl From<VideoMode> for u8 {
+    fn from(value: VideoMode) -> Self {
+        value as u8
+    }
+}
@@ -0,0 +1,265 @@
+//! Deterministic recording and replay of all inputs given to a [Computer].
+use std::{fmt::Display, str::FromStr};
+
+use crate::{
+    computer::Computer,
+    gamepad::{GamepadAxis, GamepadButton},
+    keyboard::KbLayout,
+};
+
+/// The first line of every serialized [InputLog].
+const INPUT_LOG_HEADER: &str = "# mfs16 input log v1";
+
+/// A single input given to a [Computer].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum InputAction {
+    /// A keyboard key was pressed.
+    KeyDown(u16),
+    /// A keyboard key was released.
+    KeyUp(u16),
+    /// A character was typed on the host.
+    Text(char),
+    /// A gamepad button was pressed.
+    ButtonDown(GamepadButton),
+    /// A gamepad button was released.
+    ButtonUp(GamepadButton),
+    /// A gamepad axis moved to the given position.
+    AxisMotion(GamepadAxis, i16),
+    /// The host set the keyboard layout, e.g. from its config.
+    KbLayout(KbLayout),
+}
+impl InputAction {
+    /// Give this input to the given [Computer].
+    pub fn apply(self, computer: &mut Computer) {
+        match self {
+            Self::KeyDown(code) => computer.key_down(code),
+            Self::KeyUp(code) => computer.key_up(code),
+            Self::Text(ch) => computer.text_input(ch.encode_utf8(&mut [0; 4])),
+            Self::ButtonDown(button) => computer.button_down(button),
+            Self::ButtonUp(button) => computer.button_up(button),
+            Self::AxisMotion(axis, value) => computer.axis_motion(axis, value),
+            Self::KbLayout(layout) => computer.set_kb_layout(layout),
+        }
+    }
+}
+impl Display for InputAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::KeyDown(code) => write!(f, "key_down {code}"),
+            Self::KeyUp(code) => write!(f, "key_up {code}"),
+            Self::Text(ch) => write!(f, "text {}", *ch as u32),
+            Self::ButtonDown(button) => write!(f, "button_down {}", *button as u8),
+            Self::ButtonUp(button) => write!(f, "button_up {}", *button as u8),
+            Self::AxisMotion(axis, value) => write!(f, "axis_motion {} {value}", *axis as u8),
+            Self::KbLayout(layout) => write!(f, "kb_layout {}", u8::from(*layout)),
+        }
+    }
+}
+impl FromStr for InputAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens: Vec<&str> = s.split_whitespace().collect();
+        let num = |i: usize| -> Result<i64, String> {
+            tokens
+                .get(i)
+                .ok_or(format!("Missing argument in input action \"{s}\"."))?
+                .parse::<i64>()
+                .map_err(|e| format!("Invalid argument in input action \"{s}\": {e}"))
+        };
+        let button = |val: i64| {
+            u8::try_from(val)
+                .ok()
+                .and_then(GamepadButton::try_from_u8)
+                .ok_or(format!("Invalid gamepad button in input action \"{s}\"."))
+        };
+
+        match tokens.first() {
+            Some(&"key_down") => Ok(Self::KeyDown(num(1)? as u16)),
+            Some(&"key_up") => Ok(Self::KeyUp(num(1)? as u16)),
+            Some(&"text") => char::from_u32(num(1)? as u32)
+                .map(Self::Text)
+                .ok_or(format!("Invalid character in input action \"{s}\".")),
+            Some(&"button_down") => Ok(Self::ButtonDown(button(num(1)?)?)),
+            Some(&"button_up") => Ok(Self::ButtonUp(button(num(1)?)?)),
+            Some(&"axis_motion") => {
+                let axis = u8::try_from(num(1)?)
+                    .ok()
+                    .and_then(GamepadAxis::try_from_u8)
+                    .ok_or(format!("Invalid gamepad axis in input action \"{s}\"."))?;
+                Ok(Self::AxisMotion(axis, num(2)? as i16))
+            }
+            Some(&"kb_layout") => u8::try_from(num(1)?)
+                .ok()
+                .and_then(KbLayout::try_from_u8)
+                .map(Self::KbLayout)
+                .ok_or(format!("Invalid keyboard layout in input action \"{s}\".")),
+            _ => Err(format!("Unknown input action \"{s}\".")),
+        }
+    }
+}
+
+/// An [InputAction] along with the value of [Computer::cycles] when it was given.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct InputRecord {
+    /// The cycle count at which the input was given.
+    pub cycle: u128,
+    /// The input.
+    pub action: InputAction,
+}
+
+/// An ordered log of every input given to a [Computer]. Serializes to a plain-text format with one
+/// [InputRecord] per line.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InputLog {
+    /// The recorded inputs, oldest first.
+    pub records: Vec<InputRecord>,
+}
+impl InputLog {
+    /// Add a new [InputRecord] to the end of the log.
+    pub fn record(&mut self, cycle: u128, action: InputAction) {
+        self.records.push(InputRecord { cycle, action });
+    }
+}
+impl Display for InputLog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{INPUT_LOG_HEADER}")?;
+        for record in &self.records {
+            writeln!(f, "{} {}", record.cycle, record.action)?;
+        }
+        Ok(())
+    }
+}
+impl FromStr for InputLog {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut log = Self::default();
+        for (line_num, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (cycle, action) = line
+                .split_once(char::is_whitespace)
+                .ok_or(format!("Line {}: Missing input action.", line_num + 1))?;
+            let cycle = cycle
+                .parse::<u128>()
+                .map_err(|e| format!("Line {}: Invalid cycle count: {e}", line_num + 1))?;
+            if log.records.last().is_some_and(|last| last.cycle > cycle) {
+                return Err(format!(
+                    "Line {}: Input records are out of order.",
+                    line_num + 1
+                ));
+            }
+            let action = action
+                .parse::<InputAction>()
+                .map_err(|e| format!("Line {}: {e}", line_num + 1))?;
+            log.record(cycle, action);
+        }
+        Ok(log)
+    }
+}
+
+/// Replays an [InputLog], giving each input to a [Computer] at the exact cycle it was recorded.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InputReplay {
+    /// Every recorded input, oldest first.
+    records: Vec<InputRecord>,
+    /// The index of the next input to give.
+    next: usize,
+}
+impl InputReplay {
+    /// Create a new [InputReplay] of the given [InputLog].
+    pub fn new(log: InputLog) -> Self {
+        Self {
+            records: log.records,
+            next: 0,
+        }
+    }
+
+    /// Give the [Computer] every input recorded at or before its current cycle count. Call this
+    /// once after every [Computer::cycle].
+    pub fn apply(&mut self, computer: &mut Computer) {
+        while let Some(record) = self.records.get(self.next) {
+            if record.cycle > computer.cycles {
+                break;
+            }
+            record.action.apply(computer);
+            self.next += 1;
+        }
+    }
+
+    /// Continue the replay from the given cycle count, e.g. after the [Computer] was rewound to
+    /// it. Inputs recorded at or before it are treated as given.
+    pub fn seek(&mut self, cycle: u128) {
+        self.next = self.records.partition_point(|record| record.cycle <= cycle);
+    }
+
+    /// Check whether all inputs have been given.
+    pub fn is_done(&self) -> bool {
+        self.next >= self.records.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn test_log() -> InputLog {
+        let mut log = InputLog::default();
+        log.record(2, InputAction::KeyDown(4));
+        log.record(2, InputAction::Text('é'));
+        log.record(3, InputAction::KbLayout(KbLayout::Us));
+        log.record(5, InputAction::ButtonDown(GamepadButton::Start));
+        log.record(6, InputAction::AxisMotion(GamepadAxis::LeftY, -300));
+        log.record(9, InputAction::KeyUp(4));
+        log.record(9, InputAction::ButtonUp(GamepadButton::Start));
+        log
+    }
+
+    #[test]
+    fn test_serialize() {
+        let log = test_log();
+        let serialized = log.to_string();
+
+        assert!(serialized.starts_with(INPUT_LOG_HEADER));
+        assert!(serialized.contains("2 key_down 4\n"));
+        assert!(serialized.contains("6 axis_motion 1 -300\n"));
+        assert!(serialized.contains("3 kb_layout 1\n"));
+        assert_eq!(serialized.parse::<InputLog>(), Ok(log));
+
+        assert!("3 key_down 4\n2 key_up 4".parse::<InputLog>().is_err());
+        assert!("3 jump".parse::<InputLog>().is_err());
+        assert!("3 button_down 12".parse::<InputLog>().is_err());
+        assert!("3 kb_layout 9".parse::<InputLog>().is_err());
+    }
+
+    #[test]
+    fn test_record_replay() {
+        let mut computer = Computer::new(false);
+        computer.input_log = Some(InputLog::default());
+        let mut replay = InputReplay::new(test_log());
+
+        while !replay.is_done() {
+            computer.cycle();
+            replay.apply(&mut computer);
+        }
+        assert_eq!(computer.cycles, 9);
+        assert_eq!(computer.mmu.text_input_queue.pop(), Some(0xE9));
+        assert_eq!(computer.mmu.gamepad_reg.axis(GamepadAxis::LeftY), -300);
+        assert_eq!(computer.mmu.kb_layout, KbLayout::Us);
+
+        // The replayed inputs were recorded at the same cycles
+        assert_eq!(computer.input_log, Some(test_log()));
+
+        replay.seek(5);
+        assert!(!replay.is_done());
+        assert_eq!(replay.records[replay.next].cycle, 6);
+        replay.seek(1);
+        assert_eq!(replay.next, 0);
+    }
+}
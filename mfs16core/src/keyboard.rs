@@ -1,4 +1,4 @@
-use std::default::Default;
+use std::{collections::VecDeque, default::Default};
 
 use crate::{
     helpers::{combine_u16_le, combine_u8_le, split_dword, split_word},
@@ -6,14 +6,27 @@ use crate::{
 };
 
 mod kb_code;
+mod keymap;
+mod script;
 
 use super::mmu::print_warning_message;
 
 pub use kb_code::KbCode;
+pub use keymap::KbLayout;
+pub use script::{Keystroke, KeystrokeScript, DEFAULT_KEYSTROKE_CYCLES};
 
 /// Size of the keyboard register.
 pub const KB_REG_SIZE: usize = 0x0000_0040;
 
+/// The maximum number of events the keyboard event queue can hold.
+pub const KB_EVENT_QUEUE_SIZE: usize = 16;
+
+/// The maximum number of characters the text input queue can hold.
+pub const TEXT_INPUT_QUEUE_SIZE: usize = 32;
+
+/// Set in an input queue status byte when values were dropped due to a full queue.
+const INPUT_QUEUE_OVERFLOW_BIT: u8 = 0b1000_0000;
+
 /// The register storing the state of the keyboard keys. Each byte corresponds to a key.
 /// 1 = pressed, 0 = not pressed.
 #[derive(Debug, Clone, PartialEq)]
@@ -104,6 +117,24 @@ impl KbReg {
         }
     }
 
+    /// Get the [KbModifiers] bits of the currently-held modifier keys.
+    pub fn modifiers(&self) -> u8 {
+        let mut result = 0;
+        if self.key(KbCode::LShift) || self.key(KbCode::RShift) {
+            result |= KbModifiers::SHIFT;
+        }
+        if self.key(KbCode::LCtrl) || self.key(KbCode::RCtrl) {
+            result |= KbModifiers::CTRL;
+        }
+        if self.key(KbCode::LAlt) || self.key(KbCode::RAlt) {
+            result |= KbModifiers::ALT;
+        }
+        if self.key(KbCode::LGui) || self.key(KbCode::RGui) {
+            result |= KbModifiers::GUI;
+        }
+        result
+    }
+
     /// Get the indicies of the byte and bit corresponding to the given index.
     fn byte_and_bit_indicies<C: Into<u16> + Copy>(index: C) -> (u8, u8) {
         let quotient = index.into() / (<u8>::BITS as u16);
@@ -120,6 +151,141 @@ impl Default for KbReg {
     }
 }
 
+/// The bits denoting the modifier keys held during a [KbEvent].
+pub struct KbModifiers;
+impl KbModifiers {
+    /// Either shift key is held.
+    pub const SHIFT: u8 = 0b0001;
+    /// Either control key is held.
+    pub const CTRL: u8 = 0b0010;
+    /// Either alt key is held.
+    pub const ALT: u8 = 0b0100;
+    /// Either GUI key is held.
+    pub const GUI: u8 = 0b1000;
+}
+
+/// A single key press or release.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct KbEvent {
+    /// The scancode of the key.
+    pub code: u16,
+    /// True if the key was pressed, false if it was released.
+    pub pressed: bool,
+    /// The [KbModifiers] held when the event occurred.
+    pub modifiers: u8,
+}
+impl KbEvent {
+    /// Pack this [KbEvent] into the word read by programs. Bits 0-8 hold the scancode, bit 9 is
+    /// set on key press, and bits 10-13 hold the [KbModifiers].
+    pub fn into_word(self) -> u16 {
+        (self.code & 0x01FF)
+            | ((self.pressed as u16) << 9)
+            | (((self.modifiers & 0x0F) as u16) << 10)
+    }
+
+    /// Unpack a [KbEvent] from its word representation.
+    pub fn from_word(word: u16) -> Self {
+        Self {
+            code: word & 0x01FF,
+            pressed: (word & (1 << 9)) != 0,
+            modifiers: ((word >> 10) & 0x0F) as u8,
+        }
+    }
+}
+
+/// An ordered, fixed-capacity queue of input values waiting to be read by the running program.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputQueue<T, const N: usize> {
+    /// The queued values, oldest first.
+    values: VecDeque<T>,
+    /// Set when a value is dropped because the queue is full.
+    overflow: bool,
+}
+impl<T, const N: usize> InputQueue<T, N> {
+    /// Add a value to the back of the queue. If the queue is full, the value is dropped and the
+    /// overflow flag is set.
+    pub fn push(&mut self, value: T) {
+        if self.values.len() >= N {
+            self.overflow = true;
+            return;
+        }
+        self.values.push_back(value);
+    }
+
+    /// Remove and return the oldest value in the queue.
+    pub fn pop(&mut self) -> Option<T> {
+        self.values.pop_front()
+    }
+
+    /// Get the oldest value in the queue without removing it.
+    pub fn peek(&self) -> Option<&T> {
+        self.values.front()
+    }
+
+    /// Get the number of queued values.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Check whether the queue is empty.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Get the status byte. The lower bits hold the number of queued values and the highest bit
+    /// is the overflow flag.
+    pub fn status(&self) -> u8 {
+        let mut status = self.values.len() as u8;
+        if self.overflow {
+            status |= INPUT_QUEUE_OVERFLOW_BIT;
+        }
+        status
+    }
+
+    /// Consume the status byte, resetting the overflow flag.
+    pub fn consume_status(&mut self) -> u8 {
+        let status = self.status();
+        self.overflow = false;
+        status
+    }
+}
+impl<T, const N: usize> Default for InputQueue<T, N> {
+    fn default() -> Self {
+        Self {
+            values: VecDeque::with_capacity(N),
+            overflow: false,
+        }
+    }
+}
+
+/// The ordered queue of [KbEvent]s waiting to be read by the running program.
+pub type KbEventQueue = InputQueue<KbEvent, KB_EVENT_QUEUE_SIZE>;
+impl KbEventQueue {
+    /// Pop the oldest [KbEvent] as a word, returning 0 if the queue is empty.
+    pub fn pop_word(&mut self) -> u16 {
+        self.pop().map_or(0, KbEvent::into_word)
+    }
+
+    /// Get the oldest [KbEvent] as a word without removing it, returning 0 if the queue is empty.
+    pub fn peek_word(&self) -> u16 {
+        self.peek().copied().map_or(0, KbEvent::into_word)
+    }
+}
+
+/// The ordered queue of typed characters waiting to be read by the running program.
+pub type TextInputQueue = InputQueue<u8, TEXT_INPUT_QUEUE_SIZE>;
+impl TextInputQueue {
+    /// Pop the oldest character, returning 0 if the queue is empty.
+    pub fn pop_byte(&mut self) -> u8 {
+        self.pop().unwrap_or(0)
+    }
+
+    /// Get the oldest character without removing it, returning 0 if the queue is empty.
+    pub fn peek_byte(&self) -> u8 {
+        self.peek().copied().unwrap_or(0)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
@@ -175,4 +341,55 @@ mod tests {
         kbr.key_up(A);
         assert!(!kbr.key(A));
     }
+
+    #[test]
+    fn test_modifiers() {
+        let mut kbr = KbReg::new(true);
+
+        assert_eq!(kbr.modifiers(), 0);
+        kbr.key_down(RShift);
+        kbr.key_down(LCtrl);
+        assert_eq!(kbr.modifiers(), KbModifiers::SHIFT | KbModifiers::CTRL);
+        kbr.key_up(RShift);
+        kbr.key_down(RGui);
+        assert_eq!(kbr.modifiers(), KbModifiers::CTRL | KbModifiers::GUI);
+    }
+
+    #[test]
+    fn test_event_queue() {
+        let mut queue = KbEventQueue::default();
+
+        assert_eq!(queue.pop_word(), 0);
+        assert_eq!(queue.consume_status(), 0);
+
+        let press = KbEvent {
+            code: A as u16,
+            pressed: true,
+            modifiers: KbModifiers::SHIFT,
+        };
+        let release = KbEvent {
+            code: A as u16,
+            pressed: false,
+            modifiers: 0,
+        };
+        assert_eq!(press.into_word(), 0b0000_0110_0000_0100);
+        assert_eq!(KbEvent::from_word(press.into_word()), press);
+
+        queue.push(press);
+        queue.push(release);
+        assert_eq!(queue.consume_status(), 2);
+        assert_eq!(queue.pop(), Some(press));
+        assert_eq!(queue.pop(), Some(release));
+        assert!(queue.is_empty());
+
+        for _ in 0..=KB_EVENT_QUEUE_SIZE {
+            queue.push(press);
+        }
+        assert_eq!(queue.len(), KB_EVENT_QUEUE_SIZE);
+        assert_eq!(
+            queue.consume_status(),
+            KB_EVENT_QUEUE_SIZE as u8 | INPUT_QUEUE_OVERFLOW_BIT
+        );
+        assert_eq!(queue.consume_status(), KB_EVENT_QUEUE_SIZE as u8);
+    }
 }
@@ -3,6 +3,7 @@ use std::fmt::Display;
 use KbCode::*;
 
 /// The different keys of the keyboard. Corresponds to SDL2 scancodes.
+#[allow(missing_docs)]
 #[repr(u16)]
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub enum KbCode {
@@ -0,0 +1,249 @@
+//! Translation of keyboard scancodes into characters.
+use std::fmt::Display;
+
+use super::{KbCode, KbModifiers};
+
+use KbCode::*;
+
+/// The keyboard layouts used to translate key presses into characters.
+#[repr(u8)]
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub enum KbLayout {
+    /// Printable characters are supplied by the host's own text input. Only control characters
+    /// are translated from key presses.
+    #[default]
+    Host = 0,
+    /// US QWERTY.
+    Us = 1,
+    /// UK QWERTY.
+    Uk = 2,
+    /// US Dvorak.
+    Dvorak = 3,
+}
+impl KbLayout {
+    /// Get the [KbLayout] matching the given register value, returning [None] if no layout
+    /// matches.
+    pub fn try_from_u8(val: u8) -> Option<Self> {
+        match val {
+            0 => Some(Self::Host),
+            1 => Some(Self::Us),
+            2 => Some(Self::Uk),
+            3 => Some(Self::Dvorak),
+            _ => None,
+        }
+    }
+
+    /// Get the [KbLayout] matching the given name, returning [None] if no layout matches.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "host" => Some(Self::Host),
+            "us" => Some(Self::Us),
+            "uk" => Some(Self::Uk),
+            "dvorak" => Some(Self::Dvorak),
+            _ => None,
+        }
+    }
+
+    /// Translate a pressed key into a Latin-1 character according to this layout, returning
+    /// [None] if the key doesn't produce a character.
+    ///
+    /// Control keys (return, backspace, tab, escape, delete) are translated under every layout.
+    /// Printable characters are only produced when neither control, alt nor GUI is held.
+    pub fn translate(&self, code: KbCode, modifiers: u8) -> Option<u8> {
+        if let Some(ch) = control_char(code) {
+            return Some(ch);
+        }
+        if modifiers & (KbModifiers::CTRL | KbModifiers::ALT | KbModifiers::GUI) != 0 {
+            return None;
+        }
+
+        let (normal, shifted) = match self {
+            Self::Host => return None,
+            Self::Us => us_chars(code)?,
+            Self::Uk => uk_chars(code)?,
+            Self::Dvorak => us_chars(dvorak_position(code))?,
+        };
+        if modifiers & KbModifiers::SHIFT != 0 {
+            Some(shifted)
+        } else {
+            Some(normal)
+        }
+    }
+}
+impl From<KbLayout> for u8 {
+    fn from(value: KbLayout) -> Self {
+        value as u8
+    }
+}
+impl Display for KbLayout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Host => "host",
+                Self::Us => "us",
+                Self::Uk => "uk",
+                Self::Dvorak => "dvorak",
+            }
+        )
+    }
+}
+
+/// Get the control character produced by the given key, if any.
+fn control_char(code: KbCode) -> Option<u8> {
+    match code {
+        Return | KpEnter => Some(0x0A),
+        Backspace => Some(0x08),
+        Tab => Some(0x09),
+        Escape => Some(0x1B),
+        Delete => Some(0x7F),
+        _ => None,
+    }
+}
+
+/// Get the (unshifted, shifted) characters of the given key on a US QWERTY keyboard.
+fn us_chars(code: KbCode) -> Option<(u8, u8)> {
+    let code_val = code as u16;
+    if (A as u16..=Z as u16).contains(&code_val) {
+        let letter = b'a' + (code_val - A as u16) as u8;
+        return Some((letter, letter.to_ascii_uppercase()));
+    }
+
+    Some(match code {
+        Num1 => (b'1', b'!'),
+        Num2 => (b'2', b'@'),
+        Num3 => (b'3', b'#'),
+        Num4 => (b'4', b'$'),
+        Num5 => (b'5', b'%'),
+        Num6 => (b'6', b'^'),
+        Num7 => (b'7', b'&'),
+        Num8 => (b'8', b'*'),
+        Num9 => (b'9', b'('),
+        Num0 => (b'0', b')'),
+        Space => (b' ', b' '),
+        Minus => (b'-', b'_'),
+        Equals => (b'=', b'+'),
+        LeftBracket => (b'[', b'{'),
+        RightBracket => (b']', b'}'),
+        Backslash => (b'\\', b'|'),
+        Semicolon => (b';', b':'),
+        Apostrophe => (b'\'', b'"'),
+        Grave => (b'`', b'~'),
+        Comma => (b',', b'<'),
+        Period => (b'.', b'>'),
+        Slash => (b'/', b'?'),
+        KpDivide => (b'/', b'/'),
+        KpMultiply => (b'*', b'*'),
+        KpMinus => (b'-', b'-'),
+        KpPlus => (b'+', b'+'),
+        KpPeriod => (b'.', b'.'),
+        KpEquals => (b'=', b'='),
+        Kp0 => (b'0', b'0'),
+        Kp1 => (b'1', b'1'),
+        Kp2 => (b'2', b'2'),
+        Kp3 => (b'3', b'3'),
+        Kp4 => (b'4', b'4'),
+        Kp5 => (b'5', b'5'),
+        Kp6 => (b'6', b'6'),
+        Kp7 => (b'7', b'7'),
+        Kp8 => (b'8', b'8'),
+        Kp9 => (b'9', b'9'),
+        _ => return None,
+    })
+}
+
+/// Get the (unshifted, shifted) characters of the given key on a UK QWERTY keyboard.
+fn uk_chars(code: KbCode) -> Option<(u8, u8)> {
+    match code {
+        Num2 => Some((b'2', b'"')),
+        // '£'
+        Num3 => Some((b'3', 0xA3)),
+        Apostrophe => Some((b'\'', b'@')),
+        NonUsHash => Some((b'#', b'~')),
+        NonUsBackslash => Some((b'\\', b'|')),
+        // '¬'
+        Grave => Some((b'`', 0xAC)),
+        _ => us_chars(code),
+    }
+}
+
+/// Get the key at the same position on a US QWERTY keyboard that produces the characters of the
+/// given key on a US Dvorak keyboard.
+fn dvorak_position(code: KbCode) -> KbCode {
+    match code {
+        Minus => LeftBracket,
+        Equals => RightBracket,
+        Q => Apostrophe,
+        W => Comma,
+        E => Period,
+        R => P,
+        T => Y,
+        Y => F,
+        U => G,
+        I => C,
+        O => R,
+        P => L,
+        LeftBracket => Slash,
+        RightBracket => Equals,
+        S => O,
+        D => E,
+        F => U,
+        G => I,
+        H => D,
+        J => H,
+        K => T,
+        L => N,
+        Semicolon => S,
+        Apostrophe => Minus,
+        Z => Semicolon,
+        X => Q,
+        C => J,
+        V => K,
+        B => X,
+        N => B,
+        Comma => W,
+        Period => V,
+        Slash => Z,
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_layouts() {
+        let shift = KbModifiers::SHIFT;
+
+        assert_eq!(KbLayout::Us.translate(A, 0), Some(b'a'));
+        assert_eq!(KbLayout::Us.translate(A, shift), Some(b'A'));
+        assert_eq!(KbLayout::Us.translate(Num2, shift), Some(b'@'));
+        assert_eq!(KbLayout::Us.translate(A, KbModifiers::CTRL), None);
+        assert_eq!(KbLayout::Us.translate(F1, 0), None);
+
+        assert_eq!(KbLayout::Uk.translate(Num2, shift), Some(b'"'));
+        assert_eq!(KbLayout::Uk.translate(Num3, shift), Some(0xA3));
+        assert_eq!(KbLayout::Uk.translate(Apostrophe, shift), Some(b'@'));
+        assert_eq!(KbLayout::Uk.translate(Z, 0), Some(b'z'));
+
+        assert_eq!(KbLayout::Dvorak.translate(Q, 0), Some(b'\''));
+        assert_eq!(KbLayout::Dvorak.translate(S, 0), Some(b'o'));
+        assert_eq!(KbLayout::Dvorak.translate(Z, shift), Some(b':'));
+        assert_eq!(KbLayout::Dvorak.translate(Minus, 0), Some(b'['));
+        assert_eq!(KbLayout::Dvorak.translate(A, shift), Some(b'A'));
+
+        assert_eq!(KbLayout::Host.translate(A, 0), None);
+        for layout in [KbLayout::Host, KbLayout::Us, KbLayout::Uk, KbLayout::Dvorak] {
+            assert_eq!(layout.translate(Return, 0), Some(0x0A));
+            assert_eq!(layout.translate(Backspace, KbModifiers::CTRL), Some(0x08));
+            assert_eq!(KbLayout::try_from_u8(layout.into()), Some(layout));
+            assert_eq!(KbLayout::from_name(&layout.to_string()), Some(layout));
+        }
+        assert_eq!(KbLayout::try_from_u8(4), None);
+        assert_eq!(KbLayout::from_name("azerty"), None);
+    }
+}
@@ -0,0 +1,290 @@
+//! Scripted keyboard input. Types out text as a timed sequence of key presses and releases.
+use std::collections::VecDeque;
+
+use crate::{computer::Computer, CLOCK_FREQ};
+
+use super::{KbCode, KbLayout, KbModifiers};
+
+/// The default number of cycles between two scripted key presses/releases.
+pub const DEFAULT_KEYSTROKE_CYCLES: u32 = CLOCK_FREQ / 30;
+
+/// The keys which can be typed by a [KeystrokeScript], in order of preference.
+const TYPEABLE_KEYS: [KbCode; 55] = {
+    use KbCode::*;
+    [
+        A,
+        B,
+        C,
+        D,
+        E,
+        F,
+        G,
+        H,
+        I,
+        J,
+        K,
+        L,
+        M,
+        N,
+        O,
+        P,
+        Q,
+        R,
+        S,
+        T,
+        U,
+        V,
+        W,
+        X,
+        Y,
+        Z,
+        Num1,
+        Num2,
+        Num3,
+        Num4,
+        Num5,
+        Num6,
+        Num7,
+        Num8,
+        Num9,
+        Num0,
+        Return,
+        Backspace,
+        Tab,
+        Space,
+        Minus,
+        Equals,
+        LeftBracket,
+        RightBracket,
+        Backslash,
+        NonUsHash,
+        Semicolon,
+        Apostrophe,
+        Grave,
+        Comma,
+        Period,
+        Slash,
+        NonUsBackslash,
+        Escape,
+        Delete,
+    ]
+};
+
+/// A single scripted key press or release.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Keystroke {
+    /// The key.
+    pub code: KbCode,
+    /// True if the key is pressed, false if it is released.
+    pub pressed: bool,
+}
+impl Keystroke {
+    /// Create a new [Keystroke].
+    pub fn new(code: KbCode, pressed: bool) -> Self {
+        Self { code, pressed }
+    }
+}
+
+/// A timed sequence of [Keystroke]s which types out a piece of text. Call
+/// [KeystrokeScript::cycle] once per [Computer] cycle to apply the keystrokes as they come due.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeystrokeScript {
+    /// The keystrokes which have yet to be applied.
+    keystrokes: VecDeque<Keystroke>,
+    /// The layout of the keyboard the text is typed on.
+    layout: KbLayout,
+    /// The number of cycles between two keystrokes.
+    interval: u32,
+    /// The number of cycles until the next keystroke is applied.
+    cycles_remaining: u32,
+    /// The characters of the text which couldn't be typed with the chosen layout.
+    skipped: Vec<char>,
+}
+impl KeystrokeScript {
+    /// Create a new, empty [KeystrokeScript] for a keyboard with the given [KbLayout], with the
+    /// given number of cycles between keystrokes.
+    ///
+    /// [KbLayout::Host] is typed as [KbLayout::Us].
+    pub fn new(layout: KbLayout, interval: u32) -> Self {
+        Self {
+            keystrokes: VecDeque::new(),
+            layout: match layout {
+                KbLayout::Host => KbLayout::Us,
+                other => other,
+            },
+            interval,
+            cycles_remaining: interval,
+            skipped: Vec::new(),
+        }
+    }
+
+    /// Create a new [KeystrokeScript] typing the given text. See [KeystrokeScript::new] and
+    /// [KeystrokeScript::push_text].
+    pub fn from_text(text: &str, layout: KbLayout, interval: u32) -> Self {
+        let mut script = Self::new(layout, interval);
+        script.push_text(text);
+        script
+    }
+
+    /// Add the keystrokes typing the given text to the end of the script. Uppercase letters and
+    /// symbols are typed while holding shift. Characters which can't be typed are skipped.
+    pub fn push_text(&mut self, text: &str) {
+        // Treat CRLF line endings as a single newline
+        for ch in text.replace("\r\n", "\n").chars() {
+            match Self::key_for_char(ch, self.layout) {
+                Some((code, shift)) => {
+                    if shift {
+                        self.keystrokes
+                            .push_back(Keystroke::new(KbCode::LShift, true));
+                    }
+                    self.keystrokes.push_back(Keystroke::new(code, true));
+                    self.keystrokes.push_back(Keystroke::new(code, false));
+                    if shift {
+                        self.keystrokes
+                            .push_back(Keystroke::new(KbCode::LShift, false));
+                    }
+                }
+                None => self.skipped.push(ch),
+            }
+        }
+    }
+
+    /// Add a single keystroke to the end of the script.
+    pub fn push_keystroke(&mut self, keystroke: Keystroke) {
+        self.keystrokes.push_back(keystroke);
+    }
+
+    /// Find the key, and whether shift must be held, to type the given character on the given
+    /// [KbLayout]. [KbLayout::Host] is typed as [KbLayout::Us].
+    pub fn key_for_char(ch: char, layout: KbLayout) -> Option<(KbCode, bool)> {
+        let layout = match layout {
+            KbLayout::Host => KbLayout::Us,
+            other => other,
+        };
+        let byte = u8::try_from(ch).ok()?;
+        for shift in [false, true] {
+            let modifiers = if shift { KbModifiers::SHIFT } else { 0 };
+            if let Some(code) = TYPEABLE_KEYS
+                .iter()
+                .find(|code| layout.translate(**code, modifiers) == Some(byte))
+            {
+                return Some((*code, shift));
+            }
+        }
+        None
+    }
+
+    /// Count down one cycle, applying the next keystroke to the [Computer] once it is due.
+    /// Returns the keystroke which was applied, if any.
+    pub fn cycle(&mut self, computer: &mut Computer) -> Option<Keystroke> {
+        if self.cycles_remaining > 0 {
+            self.cycles_remaining -= 1;
+            return None;
+        }
+
+        let keystroke = self.keystrokes.pop_front();
+        if let Some(keystroke) = keystroke {
+            if keystroke.pressed {
+                computer.key_down(keystroke.code);
+            } else {
+                computer.key_up(keystroke.code);
+            }
+        }
+        self.cycles_remaining = self.interval;
+        keystroke
+    }
+
+    /// Get the keystrokes which have yet to be applied.
+    pub fn keystrokes(&self) -> &VecDeque<Keystroke> {
+        &self.keystrokes
+    }
+
+    /// Get the characters which couldn't be typed.
+    pub fn skipped(&self) -> &[char] {
+        &self.skipped
+    }
+
+    /// Check whether all keystrokes have been applied.
+    pub fn is_done(&self) -> bool {
+        self.keystrokes.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::mmu::Interrupt;
+
+    use KbCode::{Apostrophe, Escape, LShift, Num2, Return, A, B, S};
+
+    #[test]
+    fn test_from_text() {
+        let script = KeystrokeScript::from_text("aB\u{2603}\n", KbLayout::Host, 1);
+
+        assert_eq!(
+            script.keystrokes().iter().copied().collect::<Vec<_>>(),
+            vec![
+                Keystroke::new(A, true),
+                Keystroke::new(A, false),
+                Keystroke::new(LShift, true),
+                Keystroke::new(B, true),
+                Keystroke::new(B, false),
+                Keystroke::new(LShift, false),
+                Keystroke::new(Return, true),
+                Keystroke::new(Return, false),
+            ]
+        );
+        assert_eq!(script.skipped(), &['\u{2603}']);
+
+        assert_eq!(
+            KeystrokeScript::key_for_char('@', KbLayout::Us),
+            Some((Num2, true))
+        );
+        assert_eq!(
+            KeystrokeScript::key_for_char('@', KbLayout::Uk),
+            Some((Apostrophe, true))
+        );
+        assert_eq!(
+            KeystrokeScript::key_for_char('o', KbLayout::Dvorak),
+            Some((S, false))
+        );
+    }
+
+    #[test]
+    fn test_cycle() {
+        let mut computer = Computer::new(false);
+        computer.mmu.kb_layout = KbLayout::Us;
+        let mut script = KeystrokeScript::from_text("Hi", KbLayout::Us, 2);
+
+        let mut cycles = 0;
+        while !script.is_done() {
+            script.cycle(&mut computer);
+            cycles += 1;
+        }
+        assert_eq!(cycles, 18);
+        assert!(!computer.mmu.kb_reg.key(LShift));
+        assert_eq!(computer.mmu.text_input_queue.pop(), Some(b'H'));
+        assert_eq!(computer.mmu.text_input_queue.pop(), Some(b'i'));
+        assert_eq!(computer.mmu.kb_event_queue.len(), 6);
+
+        script.push_keystroke(Keystroke::new(Escape, true));
+        assert_eq!(script.cycle(&mut computer), None);
+        assert_eq!(script.cycle(&mut computer), None);
+        assert_eq!(
+            script.cycle(&mut computer),
+            Some(Keystroke::new(Escape, true))
+        );
+        assert!(script.is_done());
+        assert!(computer.mmu.kb_reg.key(Escape));
+
+        // Releases raise the keyboard interrupt as well as presses
+        computer.mmu.interrupt_register = 0;
+        computer.key_up(Escape);
+        assert_eq!(
+            computer.mmu.interrupt_register,
+            1 << Interrupt::Keyboard.into_byte()
+        );
+    }
+}
@@ -1,24 +1,55 @@
 //! Core library for backend mfs16 functionality.
 #![warn(missing_docs)]
 
+mod break_condition;
 mod computer;
 mod cpu;
+mod disassembler;
 mod drive;
+mod gamepad;
 mod gpu;
 pub mod helpers;
+mod input_log;
 mod keyboard;
+mod line_table;
 mod memory;
 mod mmu;
+mod rewind;
+mod symbols;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
+mod trace;
+mod video_recorder;
 
 // Re-exports
+pub use break_condition::BreakCondition;
 pub use computer::{
-    Computer, CLOCK_FREQ, DISPLAY_HEIGHT, DISPLAY_WIDTH, RAM_OFFSET, RAM_SIZE, ROM_OFFSET,
-    ROM_SIZE, VRAM_OFFSET, VRAM_SIZE,
+    Computer, CLOCK_FREQ, DISPLAY_HEIGHT, DISPLAY_WIDTH, OAM_OFFSET, PALETTE_RAM_OFFSET,
+    PATTERN_RAM_OFFSET, RAM_OFFSET, RAM_SIZE, ROM_OFFSET, ROM_SIZE, TILE_MAP_OFFSET, VRAM_OFFSET,
+    VRAM_SIZE,
 };
 pub use cpu::{
     Addr, AsLargerType, Cpu, Flag, Flags, HasMax, Instruction, Msb, NMinus1Mask, NumBits, Oneable,
     Reg, Reg16, Reg32, Reg8, WrappingAdd, WrappingSub, Zeroable,
 };
+pub use disassembler::{default_entry_points, DecodedInstruction, DisassembledItem, Disassembly};
 pub use drive::{DriveFlag, DRIVE_FLAGS_ADDR};
+pub use gamepad::{GamepadAxis, GamepadButton};
+pub use gpu::{
+    glyph_row, BlitCommand, BlitRegs, Blitter, Sprite, VideoMode, BLIT_PIXELS_PER_CYCLE,
+    BLIT_SETUP_CYCLES, CHAR_SIZE, DEFAULT_PALETTE, FRAME_CYCLES, FRAME_LINES, LINE_CYCLES,
+    NUM_PALETTE_COLOURS, NUM_SPRITES, OAM_SIZE, PALETTE_RAM_SIZE, PATTERN_RAM_SIZE,
+    SPRITE_ENTRY_SIZE, TEXT_CELL_SIZE, TEXT_COLUMNS, TEXT_ROWS, TILE_MAP_HEIGHT, TILE_MAP_SIZE,
+    TILE_MAP_WIDTH, TILE_PATTERN_SIZE, TILE_SIZE, VBLANK_CYCLES, VBLANK_LINES,
+};
+pub use input_log::{InputAction, InputLog, InputRecord, InputReplay};
+pub use keyboard::{KbCode, KbLayout, Keystroke, KeystrokeScript, DEFAULT_KEYSTROKE_CYCLES};
+pub use line_table::{LineTable, SourceLine};
 pub use memory::{MemReadable, MemWritable, Memory};
-pub use mmu::{Interrupt, Mmu};
+pub use mmu::{AccessKind, Interrupt, MemAccess, Mmu, WatchKind, Watchpoint};
+pub use rewind::{RewindBuffer, Snapshot};
+pub use symbols::SymbolMap;
+pub use trace::{
+    FieldDifference, TraceDivergence, TraceFormat, TraceRange, TraceRecord, Tracer, TRACE_FIELDS,
+};
+pub use video_recorder::{VideoFormat, VideoRecorder};
@@ -0,0 +1,228 @@
+//! Line tables relating the bytes of an assembled program back to the mfs16asm source lines which
+//! produced them.
+use std::{fmt::Display, ops::Range, str::FromStr};
+
+use camino::{Utf8Path, Utf8PathBuf};
+
+/// The first line of every serialized [LineTable].
+const LINE_TABLE_HEADER: &str = "# mfs16 line table v1";
+
+/// A source file and 1-indexed line number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceLine<'a> {
+    /// The path of the source file, as given to the assembler.
+    pub file: &'a str,
+    /// The line number within the source file, starting from 1.
+    pub line: u32,
+}
+impl Display for SourceLine<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.file, self.line)
+    }
+}
+
+/// A range of assembled bytes which came from a single source line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct LineEntry {
+    /// The addresses of the bytes.
+    addrs: Range<u32>,
+    /// The index of the source file in [LineTable::files].
+    file: usize,
+    /// The line number within the source file, starting from 1.
+    line: u32,
+}
+
+/// The source file and line of every range of bytes in an assembled program. Serializes to a
+/// plain-text format with one entry per line.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LineTable {
+    /// The source file paths.
+    files: Vec<String>,
+    /// The entries, sorted by address.
+    entries: Vec<LineEntry>,
+}
+impl LineTable {
+    /// Get the default path of the line table of the binary at the given path.
+    pub fn path_for(bin_path: &Utf8Path) -> Utf8PathBuf {
+        Utf8PathBuf::from(format!("{bin_path}.lines"))
+    }
+
+    /// Record that the bytes at the given addresses came from the given source line. Empty ranges
+    /// are ignored.
+    pub fn add(&mut self, addrs: Range<u32>, file: &str, line: u32) {
+        if addrs.is_empty() {
+            return;
+        }
+        let file = match self.files.iter().position(|f| f == file) {
+            Some(index) => index,
+            None => {
+                self.files.push(file.to_owned());
+                self.files.len() - 1
+            }
+        };
+        let index = self
+            .entries
+            .partition_point(|entry| entry.addrs.start <= addrs.start);
+        self.entries.insert(index, LineEntry { addrs, file, line });
+    }
+
+    /// Get the source line which produced the byte at the given address.
+    pub fn lookup(&self, address: u32) -> Option<SourceLine<'_>> {
+        let index = self
+            .entries
+            .partition_point(|entry| entry.addrs.start <= address);
+        let entry = self.entries.get(index.checked_sub(1)?)?;
+        if !entry.addrs.contains(&address) {
+            return None;
+        }
+        Some(SourceLine {
+            file: &self.files[entry.file],
+            line: entry.line,
+        })
+    }
+
+    /// Get the address ranges produced by the given source line, in order.
+    pub fn addrs_of(&self, file: &str, line: u32) -> Vec<Range<u32>> {
+        let Some(file) = self.files.iter().position(|f| f == file) else {
+            return Vec::new();
+        };
+        self.entries
+            .iter()
+            .filter(|entry| entry.file == file && entry.line == line)
+            .map(|entry| entry.addrs.clone())
+            .collect()
+    }
+}
+impl Display for LineTable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{LINE_TABLE_HEADER}")?;
+        for (index, file) in self.files.iter().enumerate() {
+            writeln!(f, "file {index} {file}")?;
+        }
+        for entry in &self.entries {
+            writeln!(
+                f,
+                "{:#010X} {:#010X} {} {}",
+                entry.addrs.start, entry.addrs.end, entry.file, entry.line
+            )?;
+        }
+        Ok(())
+    }
+}
+impl FromStr for LineTable {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut files: Vec<String> = Vec::new();
+        let mut entries = Vec::new();
+        for (line_num, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = || {
+                format!(
+                    "Line {}: Invalid line table entry \"{line}\".",
+                    line_num + 1
+                )
+            };
+
+            if let Some(file) = line.strip_prefix("file ") {
+                let (index, path) = file.split_once(' ').ok_or_else(invalid)?;
+                if index.parse::<usize>().ok() != Some(files.len()) {
+                    return Err(format!("Line {}: Files are out of order.", line_num + 1));
+                }
+                files.push(path.to_owned());
+                continue;
+            }
+
+            let addr = |s: &str| u32::from_str_radix(s.trim_start_matches("0x"), 16).ok();
+            let [start, end, file, source_line] =
+                line.split_whitespace().collect::<Vec<&str>>()[..]
+            else {
+                return Err(invalid());
+            };
+            let entry = LineEntry {
+                addrs: addr(start).ok_or_else(invalid)?..addr(end).ok_or_else(invalid)?,
+                file: file.parse().map_err(|_| invalid())?,
+                line: source_line.parse().map_err(|_| invalid())?,
+            };
+            if entry.file >= files.len() {
+                return Err(format!(
+                    "Line {}: Unknown file index {}.",
+                    line_num + 1,
+                    entry.file
+                ));
+            }
+            entries.push(entry);
+        }
+
+        let mut table = Self {
+            files,
+            entries: Vec::with_capacity(entries.len()),
+        };
+        for entry in entries {
+            let file = table.files[entry.file].clone();
+            table.add(entry.addrs, &file, entry.line);
+        }
+        Ok(table)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn test_table() -> LineTable {
+        let mut table = LineTable::default();
+        table.add(0x0000..0x0006, "pong/main.mfs16", 12);
+        table.add(0x0008..0x000C, "pong/kb_lib.mfs16", 3);
+        table.add(0x0006..0x0008, "pong/main.mfs16", 13);
+        table.add(0x000C..0x000C, "pong/main.mfs16", 14);
+        table.add(0x000C..0x0010, "pong/main.mfs16", 12);
+        table
+    }
+
+    #[test]
+    fn test_lookup() {
+        let table = test_table();
+        assert_eq!(
+            table.lookup(0x0005),
+            Some(SourceLine {
+                file: "pong/main.mfs16",
+                line: 12
+            })
+        );
+        assert_eq!(
+            table.lookup(0x0006).unwrap().to_string(),
+            "pong/main.mfs16:13"
+        );
+        assert_eq!(
+            table.lookup(0x000B).unwrap().to_string(),
+            "pong/kb_lib.mfs16:3"
+        );
+        assert_eq!(table.lookup(0x0010), None);
+        assert_eq!(
+            table.addrs_of("pong/main.mfs16", 12),
+            [0x0000..0x0006, 0x000C..0x0010]
+        );
+        assert_eq!(table.addrs_of("pong/main.mfs16", 14), []);
+    }
+
+    #[test]
+    fn test_serialize() {
+        let table = test_table();
+        let serialized = table.to_string();
+
+        assert!(serialized.starts_with(LINE_TABLE_HEADER));
+        assert!(serialized.contains("file 1 pong/kb_lib.mfs16\n"));
+        assert!(serialized.contains("0x00000008 0x0000000C 1 3\n"));
+        assert_eq!(serialized.parse::<LineTable>(), Ok(table));
+
+        assert!("0x00 0x02 0 1".parse::<LineTable>().is_err());
+        assert!("file 1 a.mfs16".parse::<LineTable>().is_err());
+        assert!("file 0 a.mfs16\n0x00 0x02 0".parse::<LineTable>().is_err());
+    }
+}
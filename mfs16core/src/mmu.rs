@@ -1,17 +1,25 @@
 //! Memory management unit. Responsible for memory reads and writes across all components of the
 //! system.
-use std::{default::Default, fmt::Display};
+use std::{default::Default, fmt::Display, mem};
 
 use crate::{
     computer::{BLOCK_SIZE, DMA_BYTES_PER_CYCLE},
     drive::Drive,
-    gpu::Gpu,
-    keyboard::{KbReg, KB_REG_SIZE},
+    gamepad::{GamepadReg, GAMEPAD_REG_SIZE},
+    gpu::{
+        BlitCommand, BlitRegs, Gpu, VideoMode, OAM_SIZE, PALETTE_RAM_SIZE, PALETTE_SIZE,
+        PATTERN_RAM_SIZE, TILE_MAP_SIZE,
+    },
+    keyboard::{KbEventQueue, KbLayout, KbReg, TextInputQueue, KB_REG_SIZE},
     memory::Memory,
-    DriveFlag, DRIVE_FLAGS_ADDR, RAM_OFFSET, RAM_SIZE, ROM_OFFSET, ROM_SIZE, VRAM_OFFSET,
-    VRAM_SIZE,
+    DriveFlag, DRIVE_FLAGS_ADDR, OAM_OFFSET, PALETTE_RAM_OFFSET, PATTERN_RAM_OFFSET, RAM_OFFSET,
+    RAM_SIZE, ROM_OFFSET, ROM_SIZE, TILE_MAP_OFFSET, VRAM_OFFSET, VRAM_SIZE,
 };
 
+mod mem_access;
+
+pub use mem_access::{AccessKind, MemAccess, WatchKind, Watchpoint};
+
 /// This byte is returned when the memory can't be read for any reason.
 pub const NOT_READABLE_BYTE: u8 = 0xFF;
 
@@ -21,6 +29,83 @@ pub const DMA_TRANSFER_CYCLES: usize = BLOCK_SIZE / DMA_BYTES_PER_CYCLE;
 const ROM_END: usize = ROM_OFFSET + ROM_SIZE;
 const RAM_END: usize = RAM_OFFSET + RAM_SIZE;
 const VRAM_END: usize = VRAM_OFFSET + VRAM_SIZE;
+const PATTERN_RAM_END: usize = PATTERN_RAM_OFFSET + PATTERN_RAM_SIZE;
+const TILE_MAP_END: usize = TILE_MAP_OFFSET + TILE_MAP_SIZE;
+const OAM_END: usize = OAM_OFFSET + OAM_SIZE;
+const PALETTE_RAM_END: usize = PALETTE_RAM_OFFSET + PALETTE_RAM_SIZE;
+
+/// Address of the VRAM page register. Selects the page of VRAM accessed by the CPU and the page
+/// displayed by the GPU.
+pub const VRAM_PAGE_ADDR: usize = VIDEO_MODE_ADDR - 1;
+/// Address of the video mode register. Selects the [VideoMode] used to display VRAM.
+pub const VIDEO_MODE_ADDR: usize = BLIT_SRC_ADDR - 1;
+
+/// Start address of the blitter source address register (little-endian double word). Can also be
+/// written as two words.
+pub const BLIT_SRC_ADDR: usize = BLIT_SRC_STRIDE_ADDR - 4;
+const BLIT_SRC_ADDR_HIGH: usize = BLIT_SRC_ADDR + 2;
+/// Address of the blitter source stride register (little-endian word). Holds the width of the
+/// source pixel data in pixels.
+pub const BLIT_SRC_STRIDE_ADDR: usize = BLIT_SRC_X_ADDR - 2;
+/// Address of the blitter source x position register (little-endian word).
+pub const BLIT_SRC_X_ADDR: usize = BLIT_SRC_Y_ADDR - 2;
+/// Address of the blitter source y position register (little-endian word).
+pub const BLIT_SRC_Y_ADDR: usize = BLIT_DST_X_ADDR - 2;
+/// Address of the blitter destination x position register (little-endian word).
+pub const BLIT_DST_X_ADDR: usize = BLIT_DST_Y_ADDR - 2;
+/// Address of the blitter destination y position register (little-endian word).
+pub const BLIT_DST_Y_ADDR: usize = BLIT_WIDTH_ADDR - 2;
+/// Address of the blitter width register (little-endian word).
+pub const BLIT_WIDTH_ADDR: usize = BLIT_HEIGHT_ADDR - 2;
+/// Address of the blitter height register (little-endian word).
+pub const BLIT_HEIGHT_ADDR: usize = BLIT_COLOUR_ADDR - 2;
+/// Address of the blitter colour register. Holds the fill colour or colour key.
+pub const BLIT_COLOUR_ADDR: usize = BLIT_CONTROL_ADDR - 1;
+/// Address of the blitter control register. Write a [BlitCommand] to start a blitter operation.
+/// Reads 1 while an operation is underway, 0 otherwise.
+pub const BLIT_CONTROL_ADDR: usize = LAYER_CONTROL_ADDR - 1;
+
+/// Address of the layer control register. Bit 0 enables the tile map layer, bit 1 enables
+/// sprites, bit 2 enables the tile map colour key, and bits 4-7 hold the tile map colour key.
+pub const LAYER_CONTROL_ADDR: usize = TILE_SCROLL_Y_ADDR - 1;
+/// Address of the tile map vertical scroll register (little-endian word).
+pub const TILE_SCROLL_Y_ADDR: usize = TILE_SCROLL_X_ADDR - 2;
+/// Address of the tile map horizontal scroll register (little-endian word).
+pub const TILE_SCROLL_X_ADDR: usize = PALETTE_START - 2;
+
+/// Start address of the palette registers. Each of the 16 colours is a little-endian double word
+/// of the form `0x00RRGGBB`.
+pub const PALETTE_START: usize = PALETTE_END + 1 - PALETTE_SIZE;
+
+const PALETTE_END: usize = LINE_COMPARE_ADDR - 1;
+
+/// Address of the line compare register (little-endian word). The [Interrupt::LineCompare]
+/// interrupt is triggered when the GPU starts drawing this line.
+pub const LINE_COMPARE_ADDR: usize = SCANLINE_ADDR - 2;
+/// Address of the scanline register (little-endian word). Holds the line the GPU is currently
+/// drawing.
+pub const SCANLINE_ADDR: usize = KB_LAYOUT_ADDR - 2;
+
+/// Address of the keyboard layout register. Selects the [KbLayout] used to translate key presses
+/// into characters for the text input queue.
+pub const KB_LAYOUT_ADDR: usize = TEXT_INPUT_ADDR - 1;
+/// Read from this address to pop the oldest character from the text input queue.
+pub const TEXT_INPUT_ADDR: usize = TEXT_INPUT_STATUS_ADDR - 1;
+/// Address of the text input queue status. Holds the number of queued characters, plus an
+/// overflow bit which is reset when read.
+pub const TEXT_INPUT_STATUS_ADDR: usize = KB_EVENT_ADDR - 1;
+
+/// Read a word from this address to pop the oldest event from the keyboard event queue. Byte
+/// reads are illegal.
+pub const KB_EVENT_ADDR: usize = KB_EVENT_STATUS_ADDR - 2;
+/// Address of the keyboard event queue status. Holds the number of queued events, plus an
+/// overflow bit which is reset when read.
+pub const KB_EVENT_STATUS_ADDR: usize = GAMEPAD_REG_START - 1;
+
+/// Start address of the gamepad register.
+pub const GAMEPAD_REG_START: usize = GAMEPAD_REG_END + 1 - GAMEPAD_REG_SIZE;
+
+const GAMEPAD_REG_END: usize = DMA_R_INIT_ADDR - 1;
 
 const DMA_R_RAM_ADDR_SIZE: usize = 4;
 /// Write to this address to initiate a drive DMA read.
@@ -106,6 +191,15 @@ pub struct Mmu {
     /// The keyboard I/O register. 256 bits. Bits are toggled on/off then their respective keys are
     /// pressed/released.
     pub kb_reg: KbReg,
+    /// The keyboard event queue. Stores key presses and releases in the order they happened.
+    pub kb_event_queue: KbEventQueue,
+    /// The text input queue. Stores typed characters in the order they were typed.
+    pub text_input_queue: TextInputQueue,
+    /// The keyboard layout used to translate key presses into characters.
+    pub kb_layout: KbLayout,
+    /// The gamepad I/O register. Holds the gamepad button states followed by the positions of
+    /// the analog axes.
+    pub gamepad_reg: GamepadReg,
     /// The interrupt enable register. Serves as a bitmask for the interrupt register.
     pub ie_register: u8,
     /// The interrupt register. Denotes which interrupts have been triggered.
@@ -120,6 +214,8 @@ pub struct Mmu {
     pub current_dma_block: [u8; BLOCK_SIZE],
     /// If true, print debug messages to stderr.
     pub debug: bool,
+    /// Every read and write since the log was last taken, or [None] if accesses aren't logged.
+    pub access_log: Option<Vec<MemAccess>>,
 }
 impl Mmu {
     /// Create a new memory management unit.
@@ -183,6 +279,10 @@ impl Mmu {
         } else if self.dma_write_cycles_remaining > 0 {
             self.dma_write_cycles_remaining -= 1;
         }
+
+        if let Some((command, regs)) = self.gpu.blitter.cycle() {
+            self.finish_blit(command, regs);
+        }
     }
 
     /// Check if the MMU is locked.
@@ -197,6 +297,42 @@ impl Mmu {
         self.rom.debug = true;
         self.ram.debug = true;
         self.kb_reg.debug = true;
+        self.gamepad_reg.debug = true;
+    }
+
+    /// Start or stop logging every read and write. Logging costs nothing while stopped.
+    pub fn log_accesses(&mut self, enabled: bool) {
+        self.access_log = enabled.then(Vec::new);
+    }
+
+    /// Take the reads and writes logged since the last call, oldest first.
+    pub fn take_accesses(&mut self) -> Vec<MemAccess> {
+        self.access_log.as_mut().map(mem::take).unwrap_or_default()
+    }
+
+    /// Run the given function without logging its reads and writes, e.g. to inspect memory on
+    /// behalf of a debugger.
+    pub fn without_access_log<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+        let access_log = self.access_log.take();
+        let result = f(self);
+        self.access_log = access_log;
+        result
+    }
+
+    /// Read a byte on behalf of a debugger. Unlike a program's reads, this has no side effects:
+    /// it doesn't raise an error interrupt, consume the error register or an input queue, or get
+    /// logged. Bytes of the keyboard event register show the oldest queued event.
+    pub fn inspect_byte(&self, address: u32) -> u8 {
+        match address.try_into().unwrap() {
+            KB_EVENT_ADDR..KB_EVENT_STATUS_ADDR => {
+                let offset = address - KB_EVENT_ADDR as u32;
+                (self.kb_event_queue.peek_word() >> (offset * 8)) as u8
+            }
+            _ => self
+                .peek_byte(address)
+                .or_else(|| self.peek_register_byte(address))
+                .unwrap_or(<u8>::ERR_VAL),
+        }
     }
 
     /// Set an [Interrupt].
@@ -232,34 +368,86 @@ impl Mmu {
         self.dma_write_cycles_remaining = DMA_TRANSFER_CYCLES + 1;
     }
 
+    /// Start a blitter operation. Unknown commands, and commands given while the blitter is busy,
+    /// are an illegal write.
+    fn start_blit(&mut self, address: u32, value: u8) {
+        match BlitCommand::try_from_u8(value) {
+            Some(command) if self.gpu.blitter.start(command) => {}
+            _ => self.illegal_write(address, "start a blitter operation"),
+        }
+    }
+
+    /// Perform a blitter operation once its cycles have passed, then trigger an
+    /// [Interrupt::Blitter] interrupt. Copies from outside ROM, RAM or VRAM are an illegal read,
+    /// and copy nothing, but still trigger the interrupt.
+    fn finish_blit(&mut self, command: BlitCommand, regs: BlitRegs) {
+        let src = match (command, regs.src_range(self.gpu.video_mode)) {
+            (BlitCommand::Copy | BlitCommand::KeyedCopy, Some((start, end))) => {
+                let (start, end) = (start as usize, end as usize);
+                let src = match start {
+                    ROM_OFFSET..ROM_END if end <= ROM_END => {
+                        &self.rom.contents[(start - ROM_OFFSET)..(end - ROM_OFFSET)]
+                    }
+                    RAM_OFFSET..RAM_END if end <= RAM_END => {
+                        &self.ram.contents[(start - RAM_OFFSET)..(end - RAM_OFFSET)]
+                    }
+                    VRAM_OFFSET..VRAM_END if end <= VRAM_END => {
+                        &self.gpu.vram[(start - VRAM_OFFSET)..(end - VRAM_OFFSET)]
+                    }
+                    _ => {
+                        self.illegal_read::<u8>(start as u32, "blit from outside memory");
+                        self.set_interrupt(Interrupt::Blitter);
+                        return;
+                    }
+                };
+                src.to_vec()
+            }
+            _ => Vec::new(),
+        };
+        self.gpu.blit(command, &regs, &src);
+        self.set_interrupt(Interrupt::Blitter);
+    }
+
+    /// Return whether an access of the given number of bytes at the given address lies entirely
+    /// within the part of VRAM used by the current [VideoMode].
+    fn is_active_vram(&self, address: u32, len: usize) -> bool {
+        address as usize + len <= VRAM_OFFSET + self.gpu.video_mode.vram_size()
+    }
+
     /// Read a byte from a given address.
     pub fn read_byte(&mut self, address: u32) -> u8 {
+        let value = self.route_read_byte(address);
+        self.log_read(address, 1, value as u32);
+        value
+    }
+
+    fn route_read_byte(&mut self, address: u32) -> u8 {
         if self.is_locked() {
             return self.illegal_read(address, "read a byte while locked");
         }
 
         match address.try_into().unwrap() {
-            ROM_OFFSET..ROM_END if self.rom.is_readable() => {
-                self.rom.read_byte(address - ROM_OFFSET as u32)
-            }
-            RAM_OFFSET..RAM_END if self.ram.is_readable() => {
-                self.ram.read_byte(address - RAM_OFFSET as u32)
-            }
-            VRAM_OFFSET..VRAM_END => self.gpu.read_byte(address - VRAM_OFFSET as u32),
-            DMA_R_DRIVE_NUM_ADDR => self.dma_r_drive_num_reg,
-            DMA_R_BLOCK_ADDR => self.dma_r_block_num_reg,
-            DMA_W_DRIVE_NUM_ADDR => self.dma_w_drive_num_reg,
-            DMA_W_BLOCK_ADDR => self.dma_w_block_num_reg,
             ERR_REG_ADDR => self.consume_err_reg(),
-            KB_REG_START..KB_REG_END => self.kb_reg.read_byte(address - KB_REG_START as u32),
-            IE_REGISTER_ADDR => self.ie_register,
-            INTERRUPT_REGISTER_ADDR => self.interrupt_register,
-            _ => self.illegal_read(address, "read a byte"),
+            TEXT_INPUT_ADDR => self.text_input_queue.pop_byte(),
+            TEXT_INPUT_STATUS_ADDR => self.text_input_queue.consume_status(),
+            KB_EVENT_STATUS_ADDR => self.kb_event_queue.consume_status(),
+            _ => match self
+                .peek_byte(address)
+                .or_else(|| self.peek_register_byte(address))
+            {
+                Some(byte) => byte,
+                None => self.illegal_read(address, "read a byte"),
+            },
         }
     }
 
     /// Write a byte to a given address.
     pub fn write_byte(&mut self, address: u32, value: u8) {
+        self.log_write(address, 1, value as u32);
+        self.route_write_byte(address, value);
+    }
+
+    fn route_write_byte(&mut self, address: u32, value: u8) {
         if self.is_locked() {
             return self.illegal_write(address, "write a byte while locked");
         }
@@ -271,13 +459,36 @@ impl Mmu {
             RAM_OFFSET..RAM_END if self.ram.is_writable() => {
                 self.ram.write_byte(address - RAM_OFFSET as u32, value)
             }
-            VRAM_OFFSET..VRAM_END => self.gpu.write_byte(address - VRAM_OFFSET as u32, value),
+            VRAM_OFFSET..VRAM_END if self.is_active_vram(address, 1) => {
+                self.gpu.write_byte(address - VRAM_OFFSET as u32, value)
+            }
+            PATTERN_RAM_OFFSET..PATTERN_RAM_END => self
+                .gpu
+                .pattern_ram
+                .write_byte(address - PATTERN_RAM_OFFSET as u32, value),
+            TILE_MAP_OFFSET..TILE_MAP_END => self
+                .gpu
+                .tile_map
+                .write_byte(address - TILE_MAP_OFFSET as u32, value),
+            OAM_OFFSET..OAM_END => self.gpu.oam.write_byte(address - OAM_OFFSET as u32, value),
+            PALETTE_RAM_OFFSET..PALETTE_RAM_END => self
+                .gpu
+                .write_palette_byte(address - PALETTE_RAM_OFFSET as u32, value),
             DMA_R_INIT_ADDR => self.dma_read(),
             DMA_R_DRIVE_NUM_ADDR => self.dma_r_drive_num_reg = value,
             DMA_R_BLOCK_ADDR => self.dma_r_block_num_reg = value,
             DMA_W_INIT_ADDR => self.dma_write(),
             DMA_W_DRIVE_NUM_ADDR => self.dma_w_drive_num_reg = value,
             DMA_W_BLOCK_ADDR => self.dma_w_block_num_reg = value,
+            PALETTE_START..=PALETTE_END => self
+                .gpu
+                .write_palette_byte(address - PALETTE_START as u32, value),
+            VRAM_PAGE_ADDR => self.gpu.set_vram_page_reg(value),
+            VIDEO_MODE_ADDR => self.set_video_mode(address, value),
+            BLIT_CONTROL_ADDR => self.start_blit(address, value),
+            BLIT_COLOUR_ADDR => self.gpu.blitter.regs.colour = value,
+            LAYER_CONTROL_ADDR => self.gpu.layer_control = value,
+            KB_LAYOUT_ADDR => self.set_kb_layout(address, value),
             MAN_FRAME_UPDATE_ADDR => self.gpu.set_frame_update_flag(),
             MAN_FRAME_DISABLE_ADDR => self.gpu.man_frame_disable(),
             MAN_FRAME_ENABLE_ADDR => self.gpu.man_frame_enable(),
@@ -289,6 +500,12 @@ impl Mmu {
 
     /// Read a word starting at a given address.
     pub fn read_word(&mut self, address: u32) -> u16 {
+        let value = self.route_read_word(address);
+        self.log_read(address, 2, value as u32);
+        value
+    }
+
+    fn route_read_word(&mut self, address: u32) -> u16 {
         if self.is_locked() {
             return self.illegal_read(address, "read a word while locked");
         }
@@ -300,7 +517,50 @@ impl Mmu {
             RAM_OFFSET..RAM_END if self.ram.is_readable() => {
                 self.ram.read_word(address - RAM_OFFSET as u32)
             }
-            VRAM_OFFSET..VRAM_END => self.gpu.read_word(address - VRAM_OFFSET as u32),
+            VRAM_OFFSET..VRAM_END if self.is_active_vram(address, 2) => {
+                self.gpu.read_word(address - VRAM_OFFSET as u32)
+            }
+            PATTERN_RAM_OFFSET..PATTERN_RAM_END => self
+                .gpu
+                .pattern_ram
+                .read_word(address - PATTERN_RAM_OFFSET as u32),
+            TILE_MAP_OFFSET..TILE_MAP_END => self
+                .gpu
+                .tile_map
+                .read_word(address - TILE_MAP_OFFSET as u32),
+            OAM_OFFSET..OAM_END => self.gpu.oam.read_word(address - OAM_OFFSET as u32),
+            PALETTE_RAM_OFFSET..PALETTE_RAM_END => self
+                .gpu
+                .read_palette_word(address - PALETTE_RAM_OFFSET as u32),
+            PALETTE_START..=PALETTE_END => {
+                self.gpu.read_palette_word(address - PALETTE_START as u32)
+            }
+            VRAM_PAGE_ADDR => self.gpu.vram_page_reg() as u16,
+            VIDEO_MODE_ADDR => u8::from(self.gpu.video_mode) as u16,
+            BLIT_SRC_ADDR => self.gpu.blitter.regs.src_addr as u16,
+            BLIT_SRC_ADDR_HIGH => (self.gpu.blitter.regs.src_addr >> 16) as u16,
+            BLIT_SRC_STRIDE_ADDR => self.gpu.blitter.regs.src_stride,
+            BLIT_SRC_X_ADDR => self.gpu.blitter.regs.src_x,
+            BLIT_SRC_Y_ADDR => self.gpu.blitter.regs.src_y,
+            BLIT_DST_X_ADDR => self.gpu.blitter.regs.dst_x,
+            BLIT_DST_Y_ADDR => self.gpu.blitter.regs.dst_y,
+            BLIT_WIDTH_ADDR => self.gpu.blitter.regs.width,
+            BLIT_HEIGHT_ADDR => self.gpu.blitter.regs.height,
+            BLIT_COLOUR_ADDR => self.gpu.blitter.regs.colour as u16,
+            BLIT_CONTROL_ADDR => self.gpu.blitter.is_busy() as u16,
+            TILE_SCROLL_X_ADDR => self.gpu.tile_scroll_x,
+            TILE_SCROLL_Y_ADDR => self.gpu.tile_scroll_y,
+            LAYER_CONTROL_ADDR => self.gpu.layer_control as u16,
+            LINE_COMPARE_ADDR => self.gpu.line_compare,
+            SCANLINE_ADDR => self.gpu.scanline(),
+            KB_LAYOUT_ADDR => u8::from(self.kb_layout) as u16,
+            TEXT_INPUT_ADDR => self.text_input_queue.pop_byte() as u16,
+            TEXT_INPUT_STATUS_ADDR => self.text_input_queue.consume_status() as u16,
+            KB_EVENT_ADDR => self.kb_event_queue.pop_word(),
+            KB_EVENT_STATUS_ADDR => self.kb_event_queue.consume_status() as u16,
+            GAMEPAD_REG_START..=GAMEPAD_REG_END => self
+                .gamepad_reg
+                .read_word(address - GAMEPAD_REG_START as u32),
             ERR_REG_ADDR => self.consume_err_reg() as u16,
             IE_REGISTER_ADDR => self.ie_register as u16,
             INTERRUPT_REGISTER_ADDR => self.interrupt_register as u16,
@@ -310,6 +570,11 @@ impl Mmu {
 
     /// Write a word to a given address.
     pub fn write_word(&mut self, address: u32, value: u16) {
+        self.log_write(address, 2, value as u32);
+        self.route_write_word(address, value);
+    }
+
+    fn route_write_word(&mut self, address: u32, value: u16) {
         if self.is_locked() {
             return self.illegal_write(address, "write a word while locked");
         }
@@ -321,7 +586,48 @@ impl Mmu {
             RAM_OFFSET..RAM_END if self.ram.is_writable() => {
                 self.ram.write_word(address - RAM_OFFSET as u32, value)
             }
-            VRAM_OFFSET..VRAM_END => self.gpu.write_word(address - VRAM_OFFSET as u32, value),
+            VRAM_OFFSET..VRAM_END if self.is_active_vram(address, 2) => {
+                self.gpu.write_word(address - VRAM_OFFSET as u32, value)
+            }
+            PATTERN_RAM_OFFSET..PATTERN_RAM_END => self
+                .gpu
+                .pattern_ram
+                .write_word(address - PATTERN_RAM_OFFSET as u32, value),
+            TILE_MAP_OFFSET..TILE_MAP_END => self
+                .gpu
+                .tile_map
+                .write_word(address - TILE_MAP_OFFSET as u32, value),
+            OAM_OFFSET..OAM_END => self.gpu.oam.write_word(address - OAM_OFFSET as u32, value),
+            PALETTE_RAM_OFFSET..PALETTE_RAM_END => self
+                .gpu
+                .write_palette_word(address - PALETTE_RAM_OFFSET as u32, value),
+            PALETTE_START..=PALETTE_END => self
+                .gpu
+                .write_palette_word(address - PALETTE_START as u32, value),
+            VRAM_PAGE_ADDR => self.gpu.set_vram_page_reg(value as u8),
+            VIDEO_MODE_ADDR => self.set_video_mode(address, value as u8),
+            BLIT_SRC_ADDR => {
+                let src_addr = &mut self.gpu.blitter.regs.src_addr;
+                *src_addr = (*src_addr & 0xFFFF_0000) | value as u32;
+            }
+            BLIT_SRC_ADDR_HIGH => {
+                let src_addr = &mut self.gpu.blitter.regs.src_addr;
+                *src_addr = (*src_addr & 0x0000_FFFF) | ((value as u32) << 16);
+            }
+            BLIT_SRC_STRIDE_ADDR => self.gpu.blitter.regs.src_stride = value,
+            BLIT_SRC_X_ADDR => self.gpu.blitter.regs.src_x = value,
+            BLIT_SRC_Y_ADDR => self.gpu.blitter.regs.src_y = value,
+            BLIT_DST_X_ADDR => self.gpu.blitter.regs.dst_x = value,
+            BLIT_DST_Y_ADDR => self.gpu.blitter.regs.dst_y = value,
+            BLIT_WIDTH_ADDR => self.gpu.blitter.regs.width = value,
+            BLIT_HEIGHT_ADDR => self.gpu.blitter.regs.height = value,
+            BLIT_COLOUR_ADDR => self.gpu.blitter.regs.colour = value as u8,
+            BLIT_CONTROL_ADDR => self.start_blit(address, value as u8),
+            TILE_SCROLL_X_ADDR => self.gpu.tile_scroll_x = value,
+            TILE_SCROLL_Y_ADDR => self.gpu.tile_scroll_y = value,
+            LAYER_CONTROL_ADDR => self.gpu.layer_control = value as u8,
+            LINE_COMPARE_ADDR => self.gpu.line_compare = value,
+            KB_LAYOUT_ADDR => self.set_kb_layout(address, value as u8),
             MAN_FRAME_UPDATE_ADDR => self.gpu.set_frame_update_flag(),
             MAN_FRAME_DISABLE_ADDR => self.gpu.man_frame_disable(),
             MAN_FRAME_ENABLE_ADDR => self.gpu.man_frame_enable(),
@@ -333,6 +639,12 @@ impl Mmu {
 
     /// Read a double word starting at a given address.
     pub fn read_dword(&mut self, address: u32) -> u32 {
+        let value = self.route_read_dword(address);
+        self.log_read(address, 4, value);
+        value
+    }
+
+    fn route_read_dword(&mut self, address: u32) -> u32 {
         if self.is_locked() {
             return self.illegal_read(address, "read a double word while locked");
         }
@@ -344,7 +656,28 @@ impl Mmu {
             RAM_OFFSET..RAM_END if self.ram.is_readable() => {
                 self.ram.read_dword(address - RAM_OFFSET as u32)
             }
-            VRAM_OFFSET..VRAM_END => self.gpu.read_dword(address - VRAM_OFFSET as u32),
+            VRAM_OFFSET..VRAM_END if self.is_active_vram(address, 4) => {
+                self.gpu.read_dword(address - VRAM_OFFSET as u32)
+            }
+            PATTERN_RAM_OFFSET..PATTERN_RAM_END => self
+                .gpu
+                .pattern_ram
+                .read_dword(address - PATTERN_RAM_OFFSET as u32),
+            TILE_MAP_OFFSET..TILE_MAP_END => self
+                .gpu
+                .tile_map
+                .read_dword(address - TILE_MAP_OFFSET as u32),
+            OAM_OFFSET..OAM_END => self.gpu.oam.read_dword(address - OAM_OFFSET as u32),
+            PALETTE_RAM_OFFSET..PALETTE_RAM_END => self
+                .gpu
+                .read_palette_dword(address - PALETTE_RAM_OFFSET as u32),
+            PALETTE_START..=PALETTE_END => {
+                self.gpu.read_palette_dword(address - PALETTE_START as u32)
+            }
+            BLIT_SRC_ADDR => self.gpu.blitter.regs.src_addr,
+            GAMEPAD_REG_START..=GAMEPAD_REG_END => self
+                .gamepad_reg
+                .read_dword(address - GAMEPAD_REG_START as u32),
             DMA_R_RAM_ADDR_START => self.dma_r_ram_start_reg,
             DMA_W_RAM_ADDR_START => self.dma_w_ram_start_reg,
             ERR_REG_ADDR => self.consume_err_reg() as u32,
@@ -356,6 +689,11 @@ impl Mmu {
 
     /// Write a double word to a given address.
     pub fn write_dword(&mut self, address: u32, value: u32) {
+        self.log_write(address, 4, value);
+        self.route_write_dword(address, value);
+    }
+
+    fn route_write_dword(&mut self, address: u32, value: u32) {
         if self.is_locked() {
             return self.illegal_write(address, "write a double word while locked");
         }
@@ -367,7 +705,25 @@ impl Mmu {
             RAM_OFFSET..RAM_END if self.ram.is_writable() => {
                 self.ram.write_dword(address - RAM_OFFSET as u32, value)
             }
-            VRAM_OFFSET..VRAM_END => self.gpu.write_dword(address - VRAM_OFFSET as u32, value),
+            VRAM_OFFSET..VRAM_END if self.is_active_vram(address, 4) => {
+                self.gpu.write_dword(address - VRAM_OFFSET as u32, value)
+            }
+            PATTERN_RAM_OFFSET..PATTERN_RAM_END => self
+                .gpu
+                .pattern_ram
+                .write_dword(address - PATTERN_RAM_OFFSET as u32, value),
+            TILE_MAP_OFFSET..TILE_MAP_END => self
+                .gpu
+                .tile_map
+                .write_dword(address - TILE_MAP_OFFSET as u32, value),
+            OAM_OFFSET..OAM_END => self.gpu.oam.write_dword(address - OAM_OFFSET as u32, value),
+            PALETTE_RAM_OFFSET..PALETTE_RAM_END => self
+                .gpu
+                .write_palette_dword(address - PALETTE_RAM_OFFSET as u32, value),
+            PALETTE_START..=PALETTE_END => self
+                .gpu
+                .write_palette_dword(address - PALETTE_START as u32, value),
+            BLIT_SRC_ADDR => self.gpu.blitter.regs.src_addr = value,
             DMA_R_RAM_ADDR_START => self.dma_r_ram_start_reg = value,
             DMA_W_RAM_ADDR_START => self.dma_w_ram_start_reg = value,
             MAN_FRAME_UPDATE_ADDR => self.gpu.set_frame_update_flag(),
@@ -381,16 +737,132 @@ impl Mmu {
 
     /// Write a double word to VRAM only.
     pub fn write_dword_vram(&mut self, address: u32, value: u32) {
+        self.log_write(address, 4, value);
+        self.route_write_dword_vram(address, value);
+    }
+
+    fn route_write_dword_vram(&mut self, address: u32, value: u32) {
         if self.is_locked() {
             return self.illegal_write(address, "VRAM write while locked");
         }
 
         match address.try_into().unwrap() {
-            VRAM_OFFSET..VRAM_END => self.gpu.write_dword(address - VRAM_OFFSET as u32, value),
+            VRAM_OFFSET..VRAM_END if self.is_active_vram(address, 4) => {
+                self.gpu.write_dword(address - VRAM_OFFSET as u32, value)
+            }
             _ => self.illegal_write(address, "VRAM write outside of VRAM"),
         }
     }
 
+    /// Set the video mode from its register value. Unknown modes are an illegal write.
+    fn set_video_mode(&mut self, address: u32, value: u8) {
+        match VideoMode::try_from_u8(value) {
+            Some(mode) => self.gpu.video_mode = mode,
+            None => self.illegal_write(address, "set an unknown video mode"),
+        }
+    }
+
+    /// Set the keyboard layout from its register value. Unknown layouts are an illegal write.
+    fn set_kb_layout(&mut self, address: u32, value: u8) {
+        match KbLayout::try_from_u8(value) {
+            Some(layout) => self.kb_layout = layout,
+            None => self.illegal_write(address, "set an unknown keyboard layout"),
+        }
+    }
+
+    #[inline(always)]
+    fn log_read(&mut self, address: u32, width: u32, value: u32) {
+        if let Some(log) = &mut self.access_log {
+            log.push(MemAccess {
+                kind: AccessKind::Read,
+                address,
+                width,
+                value,
+                old_value: None,
+            });
+        }
+    }
+
+    #[inline(always)]
+    fn log_write(&mut self, address: u32, width: u32, value: u32) {
+        if self.access_log.is_none() {
+            return;
+        }
+        let old_value = (0..width).try_fold(0, |old_value, offset| {
+            let byte = self.peek_byte(address.wrapping_add(offset))?;
+            Some(old_value | ((byte as u32) << (offset * 8)))
+        });
+        if let Some(log) = &mut self.access_log {
+            log.push(MemAccess {
+                kind: AccessKind::Write,
+                address,
+                width,
+                value,
+                old_value,
+            });
+        }
+    }
+
+    /// Read a byte of ROM, RAM or VRAM without any side effects.
+    fn peek_byte(&self, address: u32) -> Option<u8> {
+        match address as usize {
+            ROM_OFFSET..ROM_END if self.rom.is_readable() => {
+                Some(self.rom.read_byte(address - ROM_OFFSET as u32))
+            }
+            RAM_OFFSET..RAM_END if self.ram.is_readable() => {
+                Some(self.ram.read_byte(address - RAM_OFFSET as u32))
+            }
+            VRAM_OFFSET..VRAM_END if self.is_active_vram(address, 1) => {
+                Some(self.gpu.read_byte(address - VRAM_OFFSET as u32))
+            }
+            _ => None,
+        }
+    }
+
+    /// Read a byte of a register or of memory other than ROM, RAM or VRAM without any side
+    /// effects. Input queues are peeked and read-to-clear registers aren't cleared.
+    fn peek_register_byte(&self, address: u32) -> Option<u8> {
+        let byte = match address.try_into().unwrap() {
+            PATTERN_RAM_OFFSET..PATTERN_RAM_END => self
+                .gpu
+                .pattern_ram
+                .read_byte(address - PATTERN_RAM_OFFSET as u32),
+            TILE_MAP_OFFSET..TILE_MAP_END => self
+                .gpu
+                .tile_map
+                .read_byte(address - TILE_MAP_OFFSET as u32),
+            OAM_OFFSET..OAM_END => self.gpu.oam.read_byte(address - OAM_OFFSET as u32),
+            PALETTE_RAM_OFFSET..PALETTE_RAM_END => self
+                .gpu
+                .read_palette_byte(address - PALETTE_RAM_OFFSET as u32),
+            DMA_R_DRIVE_NUM_ADDR => self.dma_r_drive_num_reg,
+            DMA_R_BLOCK_ADDR => self.dma_r_block_num_reg,
+            DMA_W_DRIVE_NUM_ADDR => self.dma_w_drive_num_reg,
+            DMA_W_BLOCK_ADDR => self.dma_w_block_num_reg,
+            ERR_REG_ADDR => self.err_reg,
+            KB_REG_START..KB_REG_END => self.kb_reg.read_byte(address - KB_REG_START as u32),
+            PALETTE_START..=PALETTE_END => {
+                self.gpu.read_palette_byte(address - PALETTE_START as u32)
+            }
+            VRAM_PAGE_ADDR => self.gpu.vram_page_reg(),
+            VIDEO_MODE_ADDR => self.gpu.video_mode.into(),
+            BLIT_CONTROL_ADDR => self.gpu.blitter.is_busy() as u8,
+            BLIT_COLOUR_ADDR => self.gpu.blitter.regs.colour,
+            LAYER_CONTROL_ADDR => self.gpu.layer_control,
+            KB_LAYOUT_ADDR => self.kb_layout.into(),
+            TEXT_INPUT_ADDR => self.text_input_queue.peek_byte(),
+            TEXT_INPUT_STATUS_ADDR => self.text_input_queue.status(),
+            KB_EVENT_STATUS_ADDR => self.kb_event_queue.status(),
+            GAMEPAD_REG_START..=GAMEPAD_REG_END => self
+                .gamepad_reg
+                .read_byte(address - GAMEPAD_REG_START as u32),
+            IE_REGISTER_ADDR => self.ie_register,
+            INTERRUPT_REGISTER_ADDR => self.interrupt_register,
+            _ => return None,
+        };
+        Some(byte)
+    }
+
     /// What to do when an illegal write is performed.
     fn illegal_write(&mut self, address: u32, msg: &'static str) {
         self.set_error(MfsError::IllegalWrite);
@@ -418,12 +890,17 @@ impl Default for Mmu {
             dma_w_ram_start_reg: 0x0000_0000,
             err_reg: 0x00,
             kb_reg: KbReg::default(),
+            kb_event_queue: KbEventQueue::default(),
+            text_input_queue: TextInputQueue::default(),
+            kb_layout: KbLayout::default(),
+            gamepad_reg: GamepadReg::default(),
             ie_register: 0x00,
             interrupt_register: 0x00,
             dma_read_cycles_remaining: 0,
             dma_write_cycles_remaining: 0,
             current_dma_block: [0x00; BLOCK_SIZE],
             debug: false,
+            access_log: None,
         }
     }
 }
@@ -495,10 +972,19 @@ impl Display for MfsError {
 pub enum Interrupt {
     /// This interrupt is regularly activated after the amount of cycles in one frame have passed.
     Frame,
-    /// This interrupt is activated if any keyboard keys are pressed.
+    /// This interrupt is activated if any keyboard keys are pressed or released.
     Keyboard,
     /// This interrupt is activated if any errors occur.
     Error,
+    /// This interrupt is activated if any gamepad buttons are pressed.
+    Gamepad,
+    /// This interrupt is activated when the GPU starts drawing the line in the line compare
+    /// register.
+    LineCompare,
+    /// This interrupt is activated at the start of each vertical blanking period.
+    VBlank,
+    /// This interrupt is activated when a blitter operation is complete.
+    Blitter,
 }
 impl Interrupt {
     /// Get the [Interrupt] matching the given byte, panicking if an invalid number is given.
@@ -507,6 +993,10 @@ impl Interrupt {
             0 => Self::Frame,
             1 => Self::Keyboard,
             2 => Self::Error,
+            3 => Self::Gamepad,
+            4 => Self::LineCompare,
+            5 => Self::VBlank,
+            6 => Self::Blitter,
             _ => panic!("{byte} does not match a valid Interrupt variant."),
         }
     }
@@ -517,6 +1007,10 @@ impl Interrupt {
             Self::Frame => 0,
             Self::Keyboard => 1,
             Self::Error => 2,
+            Self::Gamepad => 3,
+            Self::LineCompare => 4,
+            Self::VBlank => 5,
+            Self::Blitter => 6,
         }
     }
 }
@@ -529,6 +1023,10 @@ impl Display for Interrupt {
                 Self::Frame => "Frame",
                 Self::Keyboard => "Keyboard",
                 Self::Error => "Error",
+                Self::Gamepad => "Gamepad",
+                Self::LineCompare => "LineCompare",
+                Self::VBlank => "VBlank",
+                Self::Blitter => "Blitter",
             }
         )
     }
@@ -539,6 +1037,10 @@ mod tests {
     use pretty_assertions::assert_eq;
 
     use super::*;
+    use crate::{
+        keyboard::{KbEvent, TEXT_INPUT_QUEUE_SIZE},
+        GamepadAxis, GamepadButton, BLIT_SETUP_CYCLES, DISPLAY_WIDTH, LINE_CYCLES,
+    };
 
     macro_rules! impl_checks {
         ($chk_name:ident, $t:ty, $mmu_w_fn:ident, $mmu_r_fn:ident) => {
@@ -687,6 +1189,280 @@ mod tests {
         assert_eq!(mmu.interrupt_register, 0b0000_0011);
     }
 
+    #[test]
+    fn test_gamepad_reg() {
+        let mut mmu = Mmu::default();
+
+        mmu.gamepad_reg.button_down(GamepadButton::Right);
+        mmu.gamepad_reg.set_axis(GamepadAxis::LeftY, -1);
+        assert_eq!(mmu.read_word(GAMEPAD_REG_START as u32), 0b1000);
+        assert_eq!(mmu.read_word((GAMEPAD_REG_START + 4) as u32), 0xFFFF);
+        assert_eq!(mmu.read_byte((GAMEPAD_REG_START + 4) as u32), 0xFF);
+        assert_eq!(mmu.err_reg, 0);
+
+        // Read-only
+        mmu.write_byte(GAMEPAD_REG_START as u32, 0x00);
+        assert!(mmu.consume_err_reg() != 0);
+        assert_eq!(mmu.read_word(GAMEPAD_REG_START as u32), 0b1000);
+    }
+
+    #[test]
+    fn test_kb_event_queue() {
+        let mut mmu = Mmu::default();
+        let event = KbEvent {
+            code: 4,
+            pressed: true,
+            modifiers: 0,
+        };
+
+        mmu.kb_event_queue.push(event);
+        assert_eq!(mmu.read_byte(KB_EVENT_STATUS_ADDR as u32), 1);
+        assert_eq!(mmu.read_word(KB_EVENT_ADDR as u32), event.into_word());
+        assert_eq!(mmu.read_byte(KB_EVENT_STATUS_ADDR as u32), 0);
+        assert_eq!(mmu.read_word(KB_EVENT_ADDR as u32), 0);
+        assert_eq!(mmu.err_reg, 0);
+
+        // The event register can only be read as a word
+        mmu.kb_event_queue.push(event);
+        mmu.read_byte(KB_EVENT_ADDR as u32);
+        assert!(mmu.consume_err_reg() != 0);
+        assert_eq!(mmu.read_byte(KB_EVENT_STATUS_ADDR as u32), 1);
+    }
+
+    #[test]
+    fn test_palette_regs() {
+        let mut mmu = Mmu::default();
+
+        mmu.write_dword((PALETTE_START + 8) as u32, 0x00AB_CDEF);
+        assert_eq!(mmu.gpu.palette()[2], 0x00AB_CDEF);
+        assert_eq!(mmu.read_dword((PALETTE_START + 8) as u32), 0x00AB_CDEF);
+        mmu.write_word((PALETTE_START + 10) as u32, 0x0012);
+        assert_eq!(mmu.read_word((PALETTE_START + 8) as u32), 0xCDEF);
+        assert_eq!(mmu.read_byte((PALETTE_START + 10) as u32), 0x12);
+        mmu.write_byte(PALETTE_END as u32, 0xFF);
+        assert_eq!(mmu.read_byte(PALETTE_END as u32), 0x00);
+
+        // Palette RAM holds the whole palette, starting with the colours of the palette registers
+        assert_eq!(mmu.read_dword((PALETTE_RAM_OFFSET + 8) as u32), 0x0012_CDEF);
+        mmu.write_dword((PALETTE_RAM_END - 4) as u32, 0x0034_5678);
+        assert_eq!(mmu.gpu.palette()[255], 0x0034_5678);
+        assert_eq!(mmu.err_reg, 0);
+    }
+
+    #[test]
+    fn test_layer_regs() {
+        let mut mmu = Mmu::default();
+
+        mmu.write_dword(PATTERN_RAM_OFFSET as u32, 0x1234_5678);
+        assert_eq!(mmu.gpu.pattern_ram.contents[0..4], [0x78, 0x56, 0x34, 0x12]);
+        mmu.write_word((TILE_MAP_END - 2) as u32, 0xABCD);
+        assert_eq!(mmu.read_word((TILE_MAP_END - 2) as u32), 0xABCD);
+        mmu.write_byte(OAM_OFFSET as u32 + 7, 0x01);
+        assert!(mmu.gpu.oam.contents[7] == 0x01);
+
+        mmu.write_word(TILE_SCROLL_X_ADDR as u32, 300);
+        mmu.write_word(TILE_SCROLL_Y_ADDR as u32, 20);
+        mmu.write_byte(LAYER_CONTROL_ADDR as u32, 0b11);
+        assert_eq!(mmu.gpu.tile_scroll_x, 300);
+        assert_eq!(mmu.read_word(TILE_SCROLL_Y_ADDR as u32), 20);
+        assert_eq!(mmu.read_byte(LAYER_CONTROL_ADDR as u32), 0b11);
+        assert_eq!(mmu.err_reg, 0);
+    }
+
+    #[test]
+    fn test_video_mode_reg() {
+        let mut mmu = Mmu::default();
+        assert_eq!(mmu.read_byte(VIDEO_MODE_ADDR as u32), 0);
+
+        mmu.write_word(VIDEO_MODE_ADDR as u32, VideoMode::Text as u16);
+        assert_eq!(mmu.gpu.video_mode, VideoMode::Text);
+        assert_eq!(mmu.err_reg, 0);
+
+        mmu.write_byte(VIDEO_MODE_ADDR as u32, 0xFF);
+        assert!(mmu.consume_err_reg() != 0);
+        assert_eq!(
+            mmu.read_word(VIDEO_MODE_ADDR as u32),
+            VideoMode::Text as u16
+        );
+
+        // Only the part of VRAM used by the video mode can be accessed
+        let text_end = (VRAM_OFFSET + VideoMode::Text.vram_size()) as u32;
+        mmu.write_word(text_end - 2, 0x1234);
+        assert_eq!(mmu.read_word(text_end - 2), 0x1234);
+        assert_eq!(mmu.err_reg, 0);
+        mmu.write_byte(text_end, 0x56);
+        assert!(mmu.consume_err_reg() != 0);
+        assert_eq!(mmu.gpu.vram[VideoMode::Text.vram_size()], 0x00);
+        // Accesses which start inside the used part but end past it are illegal too
+        mmu.write_word(text_end - 1, 0x5678);
+        assert!(mmu.consume_err_reg() != 0);
+        mmu.write_dword(text_end - 2, 0x1234_5678);
+        assert!(mmu.consume_err_reg() != 0);
+        assert_eq!(mmu.gpu.vram[VideoMode::Text.vram_size()], 0x00);
+        assert_eq!(mmu.read_word(text_end - 2), 0x1234);
+
+        mmu.write_byte(VIDEO_MODE_ADDR as u32, VideoMode::LowRes as u8);
+        mmu.write_byte(text_end, 0x56);
+        assert_eq!(mmu.read_byte(text_end), 0x56);
+        mmu.read_byte((VRAM_OFFSET + 320 * 240) as u32);
+        assert!(mmu.consume_err_reg() != 0);
+    }
+
+    #[test]
+    fn test_vram_page_reg() {
+        let mut mmu = Mmu::default();
+        mmu.write_byte(VRAM_OFFSET as u32, 0x12);
+
+        mmu.write_word(VRAM_PAGE_ADDR as u32, 0b11);
+        assert_eq!(mmu.read_byte(VRAM_PAGE_ADDR as u32), 0b11);
+        assert_eq!(mmu.read_byte(VRAM_OFFSET as u32), 0x00);
+        mmu.write_byte(VRAM_OFFSET as u32, 0x34);
+
+        mmu.write_byte(VRAM_PAGE_ADDR as u32, 0b01);
+        assert_eq!(mmu.read_word(VRAM_PAGE_ADDR as u32), 0b01);
+        assert_eq!(mmu.read_byte(VRAM_OFFSET as u32), 0x12);
+        mmu.write_byte(VRAM_PAGE_ADDR as u32, 0b10);
+        assert_eq!(mmu.read_byte(VRAM_OFFSET as u32), 0x34);
+        assert_eq!(mmu.err_reg, 0);
+    }
+
+    #[test]
+    fn test_blitter() {
+        let mut mmu = Mmu::default();
+        mmu.ram.contents[0..4].copy_from_slice(&[0x12, 0x34, 0x56, 0x78]);
+
+        mmu.write_word(BLIT_SRC_ADDR as u32, RAM_OFFSET as u16);
+        mmu.write_word(BLIT_SRC_ADDR_HIGH as u32, (RAM_OFFSET >> 16) as u16);
+        assert_eq!(mmu.read_dword(BLIT_SRC_ADDR as u32), RAM_OFFSET as u32);
+        mmu.write_word(BLIT_SRC_STRIDE_ADDR as u32, 8);
+        mmu.write_word(BLIT_SRC_X_ADDR as u32, 1);
+        mmu.write_word(BLIT_DST_X_ADDR as u32, 3);
+        mmu.write_word(BLIT_DST_Y_ADDR as u32, 1);
+        mmu.write_word(BLIT_WIDTH_ADDR as u32, 5);
+        mmu.write_word(BLIT_HEIGHT_ADDR as u32, 1);
+        mmu.write_byte(BLIT_CONTROL_ADDR as u32, BlitCommand::Copy as u8);
+        assert_eq!(mmu.read_byte(BLIT_CONTROL_ADDR as u32), 1);
+
+        // Registers can be changed while the blitter is busy, but new operations can't be started
+        mmu.write_word(BLIT_DST_Y_ADDR as u32, 0);
+        mmu.write_byte(BLIT_CONTROL_ADDR as u32, BlitCommand::Fill as u8);
+        assert!(mmu.consume_err_reg() != 0);
+        mmu.interrupt_register = 0;
+
+        let mut drives = [];
+        for _ in 0..(BLIT_SETUP_CYCLES + 1) {
+            assert_eq!(mmu.interrupt_register, 0);
+            mmu.cycle(&mut drives);
+        }
+        assert_eq!(mmu.read_byte(BLIT_CONTROL_ADDR as u32), 0);
+        assert_eq!(mmu.interrupt_register, 1 << Interrupt::Blitter.into_byte());
+        let line = (VRAM_OFFSET + DISPLAY_WIDTH / 2) as u32;
+        assert_eq!(mmu.read_dword(line), 0x5634_0200);
+
+        // Copies from outside of memory are an illegal read, but still finish
+        mmu.write_word(BLIT_SRC_ADDR_HIGH as u32, 0xFFFF);
+        mmu.write_byte(BLIT_CONTROL_ADDR as u32, BlitCommand::KeyedCopy as u8);
+        mmu.interrupt_register = 0;
+        for _ in 0..(BLIT_SETUP_CYCLES + 1) {
+            mmu.cycle(&mut drives);
+        }
+        assert!(mmu.consume_err_reg() != 0);
+        assert_eq!(mmu.read_byte(BLIT_CONTROL_ADDR as u32), 0);
+        assert_ne!(
+            mmu.interrupt_register & (1 << Interrupt::Blitter.into_byte()),
+            0
+        );
+        assert_eq!(mmu.read_dword(line), 0x5634_0200);
+    }
+
+    #[test]
+    fn test_scanline_regs() {
+        let mut mmu = Mmu::default();
+
+        for _ in 0..(LINE_CYCLES * 3) {
+            mmu.gpu.cycle();
+        }
+        assert_eq!(mmu.read_word(SCANLINE_ADDR as u32), 3);
+        mmu.write_word(LINE_COMPARE_ADDR as u32, 0x0123);
+        assert_eq!(mmu.gpu.line_compare, 0x0123);
+        assert_eq!(mmu.read_word(LINE_COMPARE_ADDR as u32), 0x0123);
+        assert_eq!(mmu.err_reg, 0);
+
+        // Read-only
+        mmu.write_word(SCANLINE_ADDR as u32, 0x0000);
+        assert!(mmu.consume_err_reg() != 0);
+    }
+
+    #[test]
+    fn test_text_input() {
+        let mut mmu = Mmu::default();
+
+        mmu.text_input_queue.push(b'h');
+        mmu.text_input_queue.push(b'i');
+        assert_eq!(mmu.read_byte(TEXT_INPUT_STATUS_ADDR as u32), 2);
+        assert_eq!(mmu.read_byte(TEXT_INPUT_ADDR as u32), b'h');
+        assert_eq!(mmu.read_word(TEXT_INPUT_ADDR as u32), b'i' as u16);
+        assert_eq!(mmu.read_word(TEXT_INPUT_ADDR as u32), 0);
+        assert_eq!(mmu.err_reg, 0);
+
+        assert_eq!(mmu.read_byte(KB_LAYOUT_ADDR as u32), KbLayout::Host as u8);
+        mmu.write_byte(KB_LAYOUT_ADDR as u32, KbLayout::Dvorak as u8);
+        assert_eq!(mmu.kb_layout, KbLayout::Dvorak);
+        mmu.write_word(KB_LAYOUT_ADDR as u32, KbLayout::Uk as u16);
+        assert_eq!(mmu.read_word(KB_LAYOUT_ADDR as u32), KbLayout::Uk as u16);
+        assert_eq!(mmu.err_reg, 0);
+
+        mmu.write_byte(KB_LAYOUT_ADDR as u32, 0xFF);
+        assert!(mmu.consume_err_reg() != 0);
+        assert_eq!(mmu.kb_layout, KbLayout::Uk);
+    }
+
+    #[test]
+    fn test_inspect_byte() {
+        let mut mmu = Mmu::default();
+        let event = KbEvent {
+            code: 4,
+            pressed: true,
+            modifiers: 0,
+        };
+        for _ in 0..=TEXT_INPUT_QUEUE_SIZE {
+            mmu.text_input_queue.push(b'h');
+        }
+        mmu.kb_event_queue.push(event);
+        mmu.log_accesses(true);
+
+        // Inspecting doesn't consume the input queues or reset their overflow flags
+        for _ in 0..2 {
+            assert_eq!(mmu.inspect_byte(TEXT_INPUT_ADDR as u32), b'h');
+            assert_eq!(
+                mmu.inspect_byte(TEXT_INPUT_STATUS_ADDR as u32),
+                TEXT_INPUT_QUEUE_SIZE as u8 | 0b1000_0000
+            );
+            assert_eq!(
+                u16::from_le_bytes([
+                    mmu.inspect_byte(KB_EVENT_ADDR as u32),
+                    mmu.inspect_byte(KB_EVENT_ADDR as u32 + 1)
+                ]),
+                event.into_word()
+            );
+            assert_eq!(mmu.inspect_byte(KB_EVENT_STATUS_ADDR as u32), 1);
+        }
+        assert_eq!(mmu.text_input_queue.len(), TEXT_INPUT_QUEUE_SIZE);
+        assert_eq!(mmu.kb_event_queue.len(), 1);
+
+        // Nor does it clear the error register or raise an error
+        mmu.set_error(MfsError::IllegalWrite);
+        let (err_reg, interrupt_register) = (mmu.err_reg, mmu.interrupt_register);
+        assert_eq!(mmu.inspect_byte(ERR_REG_ADDR as u32), err_reg);
+        assert_eq!(mmu.inspect_byte(VRAM_END as u32), <u8>::ERR_VAL);
+        assert_eq!(mmu.err_reg, err_reg);
+        assert_eq!(mmu.interrupt_register, interrupt_register);
+        assert_eq!(mmu.take_accesses(), []);
+
+        assert_eq!(mmu.read_byte(TEXT_INPUT_ADDR as u32), b'h');
+        assert_eq!(mmu.text_input_queue.len(), TEXT_INPUT_QUEUE_SIZE - 1);
+    }
+
     #[test]
     fn test_mmu() {
         let mut mmu = Mmu::default();
@@ -706,4 +1482,50 @@ mod tests {
             }
         }
     }
+    #[test]
+    fn test_access_log() {
+        let mut mmu = Mmu::default();
+        let address = RAM_OFFSET as u32;
+        mmu.write_byte(address, 0x12);
+        assert_eq!(mmu.take_accesses(), []);
+
+        mmu.log_accesses(true);
+        mmu.write_word(address, 0x3456);
+        mmu.read_byte(address + 1);
+        mmu.write_byte(IE_REGISTER_ADDR as u32, 0x01);
+        assert_eq!(
+            mmu.take_accesses(),
+            [
+                MemAccess {
+                    kind: AccessKind::Write,
+                    address,
+                    width: 2,
+                    value: 0x3456,
+                    old_value: Some(0x0012),
+                },
+                MemAccess {
+                    kind: AccessKind::Read,
+                    address: address + 1,
+                    width: 1,
+                    value: 0x34,
+                    old_value: None,
+                },
+                MemAccess {
+                    kind: AccessKind::Write,
+                    address: IE_REGISTER_ADDR as u32,
+                    width: 1,
+                    value: 0x01,
+                    old_value: None,
+                },
+            ]
+        );
+        assert_eq!(mmu.take_accesses(), []);
+
+        mmu.without_access_log(|mmu| mmu.read_byte(address));
+        assert_eq!(mmu.take_accesses(), []);
+
+        mmu.log_accesses(false);
+        mmu.read_dword(address);
+        assert_eq!(mmu.access_log, None);
+    }
 }
@@ -0,0 +1,130 @@
+//! Memory accesses observed by the [Mmu](super::Mmu), and the watchpoints built on them.
+use serde::{Deserialize, Serialize};
+
+/// Whether a memory access read or wrote.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AccessKind {
+    /// The memory was read.
+    Read,
+    /// The memory was written.
+    Write,
+}
+
+/// A single read or write through the [Mmu](super::Mmu).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemAccess {
+    /// Whether the memory was read or written.
+    pub kind: AccessKind,
+    /// The address of the first byte accessed.
+    pub address: u32,
+    /// The number of bytes accessed: 1, 2 or 4.
+    pub width: u32,
+    /// The value read or written.
+    pub value: u32,
+    /// For writes to ROM, RAM or VRAM, the value in memory before the write.
+    pub old_value: Option<u32>,
+}
+impl MemAccess {
+    /// Get the byte at the given address accessed by this [MemAccess], along with the byte there
+    /// before a write. Returns [None] if the address wasn't accessed.
+    fn byte_at(&self, address: u32) -> Option<(u8, Option<u8>)> {
+        let offset = address.wrapping_sub(self.address);
+        if offset >= self.width {
+            return None;
+        }
+        let byte = |value: u32| (value >> (offset * 8)) as u8;
+        Some((byte(self.value), self.old_value.map(byte)))
+    }
+}
+
+/// The kind of access which triggers a [Watchpoint].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WatchKind {
+    /// Any read of the watched memory.
+    Read,
+    /// Any write to the watched memory.
+    Write,
+    /// Any write which changes the watched memory. Writes to memory other than ROM, RAM or VRAM
+    /// always count as changes.
+    Change,
+    /// Any read of or write to the watched memory.
+    Access,
+}
+
+/// Watches a range start..end of memory for reads or writes.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Watchpoint {
+    /// The kind of access to watch for.
+    pub kind: WatchKind,
+    /// Inclusive start address of the watched range.
+    pub start: u32,
+    /// Exclusive end address of the watched range.
+    pub end: u32,
+}
+impl Watchpoint {
+    /// Check whether the given [MemAccess] triggers this [Watchpoint].
+    pub fn is_triggered_by(&self, access: &MemAccess) -> bool {
+        let mut bytes = (self.start..self.end).filter_map(|address| access.byte_at(address));
+        match (self.kind, access.kind) {
+            (WatchKind::Read, AccessKind::Read)
+            | (WatchKind::Write, AccessKind::Write)
+            | (WatchKind::Access, _) => bytes.next().is_some(),
+            (WatchKind::Change, AccessKind::Write) => {
+                bytes.any(|(new, old)| old.is_none_or(|old| old != new))
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn write(address: u32, width: u32, value: u32, old_value: Option<u32>) -> MemAccess {
+        MemAccess {
+            kind: AccessKind::Write,
+            address,
+            width,
+            value,
+            old_value,
+        }
+    }
+
+    #[test]
+    fn test_watchpoint() {
+        let watchpoint = |kind| Watchpoint {
+            kind,
+            start: 0x80_0002,
+            end: 0x80_0004,
+        };
+        let read = MemAccess {
+            kind: AccessKind::Read,
+            ..write(0x80_0000, 4, 0x1234_5678, None)
+        };
+        assert!(watchpoint(WatchKind::Read).is_triggered_by(&read));
+        assert!(!watchpoint(WatchKind::Write).is_triggered_by(&read));
+        assert!(watchpoint(WatchKind::Access).is_triggered_by(&read));
+        assert!(!watchpoint(WatchKind::Read).is_triggered_by(&MemAccess {
+            address: 0x80_0004,
+            ..read
+        }));
+        assert!(!watchpoint(WatchKind::Read).is_triggered_by(&MemAccess { width: 2, ..read }));
+
+        // Only the watched bytes are compared
+        let unchanged = write(0x80_0001, 2, 0x34FF, Some(0x3400));
+        assert!(watchpoint(WatchKind::Write).is_triggered_by(&unchanged));
+        assert!(!watchpoint(WatchKind::Change).is_triggered_by(&unchanged));
+        assert!(watchpoint(WatchKind::Access).is_triggered_by(&unchanged));
+        let changed = write(0x80_0003, 1, 0xAB, Some(0xCD));
+        assert!(watchpoint(WatchKind::Change).is_triggered_by(&changed));
+        let unknown = write(0x80_0003, 1, 0xAB, None);
+        assert!(watchpoint(WatchKind::Change).is_triggered_by(&unknown));
+
+        assert_eq!(read.byte_at(0x80_0001), Some((0x56, None)));
+        assert_eq!(unchanged.byte_at(0x80_0002), Some((0x34, Some(0x34))));
+        assert_eq!(unchanged.byte_at(0x80_0003), None);
+    }
+}
@@ -0,0 +1,198 @@
+//! Snapshots of a [Computer] taken as it runs, so that it can be rewound.
+use std::collections::VecDeque;
+
+use crate::{computer::Computer, cpu::Cpu, mmu::Mmu};
+
+/// The state of a [Computer] at a given cycle, which it can be restored to. Drives aren't
+/// included.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    cpu: Cpu,
+    mmu: Mmu,
+    cycles: u128,
+}
+impl Snapshot {
+    /// Take a [Snapshot] of the given [Computer].
+    pub fn take(computer: &Computer) -> Self {
+        let mut mmu = computer.mmu.clone();
+        mmu.access_log = None;
+        Self {
+            cpu: computer.cpu.clone(),
+            mmu,
+            cycles: computer.cycles,
+        }
+    }
+
+    /// Restore the given [Computer] to this [Snapshot]. Inputs recorded after the snapshot was
+    /// taken are removed from its input log, and its access log is emptied.
+    pub fn restore(&self, computer: &mut Computer) {
+        let is_logging = computer.mmu.access_log.is_some();
+        computer.cpu = self.cpu.clone();
+        computer.mmu = self.mmu.clone();
+        computer.mmu.log_accesses(is_logging);
+        computer.cycles = self.cycles;
+        if let Some(input_log) = &mut computer.input_log {
+            input_log
+                .records
+                .retain(|record| record.cycle <= self.cycles);
+        }
+    }
+
+    /// The cycle count of the [Computer] when this [Snapshot] was taken.
+    pub fn cycles(&self) -> u128 {
+        self.cycles
+    }
+}
+
+/// A ring buffer of [Snapshot]s taken at regular intervals. Combined with a replay of the inputs
+/// given since, the [Computer] can be rewound to any cycle after the oldest snapshot.
+#[derive(Debug, Clone)]
+pub struct RewindBuffer {
+    /// The snapshots, oldest first.
+    snapshots: VecDeque<Snapshot>,
+    /// The most snapshots to keep.
+    capacity: usize,
+    /// The number of cycles between snapshots.
+    interval: u128,
+}
+impl RewindBuffer {
+    /// Create a new [RewindBuffer] which keeps up to `capacity` snapshots taken `interval` cycles
+    /// apart. Each snapshot takes about 17 MiB.
+    pub fn new(capacity: usize, interval: u128) -> Self {
+        Self {
+            snapshots: VecDeque::with_capacity(capacity),
+            capacity: capacity.max(1),
+            interval,
+        }
+    }
+
+    /// Take a snapshot of the given [Computer] if it's been at least the interval since the
+    /// newest one. Call this between instructions, so that the computer is never restored to
+    /// the middle of one.
+    pub fn record(&mut self, computer: &Computer) {
+        if self
+            .snapshots
+            .back()
+            .is_some_and(|newest| computer.cycles < newest.cycles + self.interval)
+        {
+            return;
+        }
+        self.push(Snapshot::take(computer));
+    }
+
+    /// Take a snapshot of the given [Computer] now, discarding any taken at or after its cycle
+    /// count. Call this when something other than running the computer changes it, e.g. a
+    /// debugger writing memory, as restoring a snapshot taken before would lose the change.
+    pub fn branch(&mut self, computer: &Computer) {
+        while self
+            .snapshots
+            .back()
+            .is_some_and(|newest| newest.cycles >= computer.cycles)
+        {
+            self.snapshots.pop_back();
+        }
+        self.push(Snapshot::take(computer));
+    }
+
+    fn push(&mut self, snapshot: Snapshot) {
+        if self.snapshots.len() >= self.capacity {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back(snapshot);
+    }
+
+    /// Get the newest [Snapshot] taken before the given cycle.
+    pub fn before(&self, cycle: u128) -> Option<&Snapshot> {
+        self.snapshots
+            .iter()
+            .rev()
+            .find(|snapshot| snapshot.cycles < cycle)
+    }
+
+    /// Get the oldest [Snapshot].
+    pub fn oldest(&self) -> Option<&Snapshot> {
+        self.snapshots.front()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::{input_log::InputLog, test_utils::load_counter, Reg16};
+
+    use super::*;
+
+    /// Make a computer which increments A forever.
+    fn counter() -> Computer {
+        let mut computer = Computer::new(false);
+        load_counter(&mut computer);
+        computer
+    }
+
+    fn run_instructions(computer: &mut Computer, buffer: &mut RewindBuffer, count: usize) {
+        for _ in 0..count {
+            buffer.record(computer);
+            computer.cycle();
+            while !computer.cpu.instr_is_done() {
+                computer.cycle();
+            }
+        }
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let mut computer = counter();
+        computer.input_log = Some(InputLog::default());
+        computer.mmu.log_accesses(true);
+        for _ in 0..100 {
+            computer.cycle();
+        }
+        let snapshot = Snapshot::take(&computer);
+        let (cpu, cycles) = (computer.cpu.clone(), computer.cycles);
+
+        for _ in 0..100 {
+            computer.cycle();
+        }
+        computer.key_down(4_u16);
+        assert_ne!(computer.cpu, cpu);
+
+        snapshot.restore(&mut computer);
+        assert_eq!(computer.cpu, cpu);
+        assert_eq!(computer.cycles, cycles);
+        assert_eq!(computer.input_log, Some(InputLog::default()));
+        assert_eq!(computer.mmu.access_log, Some(Vec::new()));
+        assert!(!computer.mmu.kb_reg.key(4_u16));
+    }
+
+    #[test]
+    fn test_rewind_buffer() {
+        let mut computer = counter();
+        let mut buffer = RewindBuffer::new(3, 20);
+        assert!(buffer.before(u128::MAX).is_none());
+
+        run_instructions(&mut computer, &mut buffer, 30);
+        let cycles: Vec<u128> = buffer.snapshots.iter().map(Snapshot::cycles).collect();
+        assert_eq!(cycles.len(), 3);
+        assert!(cycles.windows(2).all(|pair| pair[1] >= pair[0] + 20));
+        assert_eq!(buffer.oldest().map(Snapshot::cycles), Some(cycles[0]));
+        assert_eq!(
+            buffer.before(cycles[2]).map(Snapshot::cycles),
+            Some(cycles[1])
+        );
+        assert_eq!(buffer.before(cycles[0]).map(Snapshot::cycles), None);
+
+        // Snapshots in the computer's future are replaced
+        buffer
+            .before(cycles[2])
+            .unwrap()
+            .clone()
+            .restore(&mut computer);
+        computer.cpu.regs.set_reg(Reg16::A, 0x1234);
+        buffer.branch(&computer);
+        let newest = buffer.snapshots.back().unwrap();
+        assert_eq!(newest.cycles(), cycles[1]);
+        assert_eq!(newest.cpu.reg(Reg16::A), 0x1234);
+        assert_eq!(buffer.snapshots.len(), 2);
+    }
+}
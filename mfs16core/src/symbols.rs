@@ -0,0 +1,180 @@
+//! Symbol maps relating the addresses of an assembled program back to its mfs16asm source.
+use std::{fmt::Display, str::FromStr};
+
+use camino::{Utf8Path, Utf8PathBuf};
+
+/// The first line of every serialized [SymbolMap].
+const SYMBOL_MAP_HEADER: &str = "# mfs16 symbol map v1";
+
+/// The label names and variable values of an assembled program. Serializes to a plain-text format
+/// with one symbol per line.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SymbolMap {
+    /// The labels, sorted by address.
+    labels: Vec<(u32, String)>,
+    /// The final value of each variable, formatted as mfs16asm.
+    variables: Vec<(String, String)>,
+    /// The address just past the end of the program. Addresses at or past it aren't symbolised.
+    end: Option<u32>,
+}
+impl SymbolMap {
+    /// Get the default path of the symbol map of the binary at the given path.
+    pub fn path_for(bin_path: &Utf8Path) -> Utf8PathBuf {
+        Utf8PathBuf::from(format!("{bin_path}.sym"))
+    }
+
+    /// Add a label pointing to the given address.
+    pub fn add_label<S: Into<String>>(&mut self, name: S, address: u32) {
+        let index = self.labels.partition_point(|(addr, _)| *addr <= address);
+        self.labels.insert(index, (address, name.into()));
+    }
+
+    /// Add a variable with the given value.
+    pub fn add_variable<S: Into<String>, V: Display>(&mut self, name: S, value: V) {
+        self.variables.push((name.into(), value.to_string()));
+    }
+
+    /// Set the address just past the end of the program.
+    pub fn set_end(&mut self, end: u32) {
+        self.end = Some(end);
+    }
+
+    /// The labels, sorted by address.
+    pub fn labels(&self) -> &[(u32, String)] {
+        &self.labels
+    }
+
+    /// The variables and their values, in the order they were added.
+    pub fn variables(&self) -> &[(String, String)] {
+        &self.variables
+    }
+
+    /// Get the names of all labels pointing to exactly the given address.
+    pub fn labels_at(&self, address: u32) -> impl Iterator<Item = &str> {
+        let start = self.labels.partition_point(|(addr, _)| *addr < address);
+        self.labels[start..]
+            .iter()
+            .take_while(move |(addr, _)| *addr == address)
+            .map(|(_, name)| name.as_str())
+    }
+
+    /// Get the address of the label with the given name.
+    pub fn address_of(&self, name: &str) -> Option<u32> {
+        self.labels
+            .iter()
+            .find(|(_, label)| label == name)
+            .map(|(address, _)| *address)
+    }
+
+    /// Get the closest label at or before the given address, along with the address' offset from
+    /// that label.
+    pub fn nearest(&self, address: u32) -> Option<(&str, u32)> {
+        if self.end.is_some_and(|end| address >= end) {
+            return None;
+        }
+        let index = self.labels.partition_point(|(addr, _)| *addr <= address);
+        let label_addr = self.labels.get(index.checked_sub(1)?)?.0;
+        let name = self.labels_at(label_addr).next()?;
+        Some((name, address - label_addr))
+    }
+
+    /// Format the given address relative to its nearest label, e.g. `check_scancode+0x12`. Falls
+    /// back to the bare address if there is no such label.
+    pub fn format_addr(&self, address: u32) -> String {
+        match self.nearest(address) {
+            Some((name, 0)) => name.to_owned(),
+            Some((name, offset)) => format!("{name}+{offset:#X}"),
+            None => format!("{address:#010X}"),
+        }
+    }
+}
+impl Display for SymbolMap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{SYMBOL_MAP_HEADER}")?;
+        for (address, name) in &self.labels {
+            writeln!(f, "label {address:#010X} {name}")?;
+        }
+        for (name, value) in &self.variables {
+            writeln!(f, "var {name} {value}")?;
+        }
+        if let Some(end) = self.end {
+            writeln!(f, "end {end:#010X}")?;
+        }
+        Ok(())
+    }
+}
+impl FromStr for SymbolMap {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let addr = |line_num: usize, s: &str| -> Result<u32, String> {
+            u32::from_str_radix(s.trim_start_matches("0x"), 16)
+                .map_err(|e| format!("Line {}: Invalid address \"{s}\": {e}", line_num + 1))
+        };
+
+        let mut map = Self::default();
+        for (line_num, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            match line.split_whitespace().collect::<Vec<&str>>()[..] {
+                ["label", address, name] => map.add_label(name, addr(line_num, address)?),
+                ["var", name, value] => map.add_variable(name, value),
+                ["end", address] => map.set_end(addr(line_num, address)?),
+                _ => return Err(format!("Line {}: Invalid symbol \"{line}\".", line_num + 1)),
+            }
+        }
+        Ok(map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn test_map() -> SymbolMap {
+        let mut map = SymbolMap::default();
+        map.add_label("loop", 0x0A40);
+        map.add_label("check_scancode", 0x0A22);
+        map.add_label("main", 0x0000);
+        map.add_label("start", 0x0000);
+        map.add_variable("SCREEN_WIDTH", "0x0140:w");
+        map.set_end(0x1000);
+        map
+    }
+
+    #[test]
+    fn test_lookup() {
+        let map = test_map();
+        assert_eq!(
+            map.labels_at(0x0000).collect::<Vec<&str>>(),
+            ["main", "start"]
+        );
+        assert_eq!(map.nearest(0x0A34), Some(("check_scancode", 0x12)));
+        assert_eq!(map.address_of("loop"), Some(0x0A40));
+        assert_eq!(map.address_of("nowhere"), None);
+        assert_eq!(map.format_addr(0x0A34), "check_scancode+0x12");
+        assert_eq!(map.format_addr(0x0A40), "loop");
+        assert_eq!(map.format_addr(0x0004), "main+0x4");
+        assert_eq!(map.format_addr(0x1000), "0x00001000");
+    }
+
+    #[test]
+    fn test_serialize() {
+        let map = test_map();
+        let serialized = map.to_string();
+
+        assert!(serialized.starts_with(SYMBOL_MAP_HEADER));
+        assert!(serialized.contains("label 0x00000A22 check_scancode\n"));
+        assert!(serialized.contains("var SCREEN_WIDTH 0x0140:w\n"));
+        assert_eq!(serialized.parse::<SymbolMap>(), Ok(map));
+
+        assert!("label 0x12".parse::<SymbolMap>().is_err());
+        assert!("label 0xZZ foo".parse::<SymbolMap>().is_err());
+        assert!("func 0x12 foo".parse::<SymbolMap>().is_err());
+    }
+}
@@ -0,0 +1,44 @@
+//! Programs shared between the tests of this crate and the crates which depend on it. Only
+//! available with the `test-utils` feature.
+use crate::{Addr, Computer, Instruction, Reg16, SymbolMap};
+
+use Instruction::*;
+
+/// Assemble the given instructions and immediate values.
+pub fn assemble(program: &[(Instruction, u32)]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for &(instruction, immediate) in program {
+        bytes.extend(instruction.into_opcode().to_le_bytes());
+        bytes.extend(&immediate.to_le_bytes()[..instruction.imm_size()]);
+    }
+    bytes
+}
+
+/// Load a program which loads A, calls a subroutine which increments it, increments it again
+/// and stops, returning its labels.
+pub fn load_test_program(computer: &mut Computer) -> SymbolMap {
+    let mut bytes = assemble(&[
+        (LdRaImm16(Reg16::A), 0x0001), // 0x00
+        (CallImm32, 0x0010),           // 0x04
+        (IncRa(Reg16::A), 0),          // 0x0A
+        (Stop, 0),                     // 0x0C
+    ]);
+    bytes.resize(0x10, 0);
+    bytes.extend(assemble(&[
+        (IncRa(Reg16::A), 0), // 0x10
+        (Ret, 0),             // 0x12
+    ]));
+    computer.direct_write(Addr::new_default_range(0x00), &bytes);
+
+    let mut symbols = SymbolMap::default();
+    symbols.add_label("main", 0x00);
+    symbols.add_label("sub", 0x10);
+    symbols.set_end(bytes.len() as u32);
+    symbols
+}
+
+/// Load a program which increments A forever.
+pub fn load_counter(computer: &mut Computer) {
+    let bytes = assemble(&[(IncRa(Reg16::A), 0), (JpImm32, 0x0000)]);
+    computer.direct_write(Addr::new_default_range(0x00), &bytes);
+}
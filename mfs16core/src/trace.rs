@@ -0,0 +1,435 @@
+//! Structured traces of the instructions executed by a [Computer], to be diffed between emulator
+//! versions or analysed by scripts.
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    mem,
+    str::FromStr,
+};
+
+use camino::Utf8Path;
+use serde::{Deserialize, Serialize};
+
+use crate::{AccessKind, Computer, DecodedInstruction, MemAccess, Reg16};
+
+mod diff;
+
+pub use diff::{FieldDifference, TraceDivergence, TRACE_FIELDS};
+
+/// The header row of a CSV trace.
+const CSV_HEADER: &str = "cycle,pc,opcode,instruction,a,b,c,d,e,h,l,sp,flags,accesses";
+
+/// The file formats which traces can be written to.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TraceFormat {
+    /// One JSON object per line.
+    JsonLines,
+    /// Comma-separated values with a header row.
+    Csv,
+}
+impl TraceFormat {
+    /// Get the [TraceFormat] matching the extension of the given path, returning [None] if no
+    /// format matches.
+    pub fn from_path(path: &Utf8Path) -> Option<Self> {
+        match path.extension()?.to_ascii_lowercase().as_str() {
+            "jsonl" | "ndjson" => Some(Self::JsonLines),
+            "csv" => Some(Self::Csv),
+            _ => None,
+        }
+    }
+}
+
+/// A range start..end of addresses. Only the instructions inside it are traced.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TraceRange {
+    /// Inclusive start address of the range.
+    pub start: u32,
+    /// Exclusive end address of the range.
+    pub end: u32,
+}
+impl TraceRange {
+    /// Check whether the given address is inside this range.
+    pub fn contains(&self, address: u32) -> bool {
+        (self.start..self.end).contains(&address)
+    }
+}
+impl FromStr for TraceRange {
+    type Err = String;
+
+    /// Parse a range written as `start..end`, e.g. `0x100..0x200`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (start, end) = s
+            .split_once("..")
+            .ok_or_else(|| format!("Range `{s}` is not written as `start..end`."))?;
+        let parse = |address: &str| {
+            let address = address.trim().replace('_', "");
+            match address.strip_prefix("0x") {
+                Some(hex) => u32::from_str_radix(hex, 16),
+                None => address.parse(),
+            }
+            .map_err(|_| format!("Invalid address `{address}` in range `{s}`."))
+        };
+        Ok(Self {
+            start: parse(start)?,
+            end: parse(end)?,
+        })
+    }
+}
+
+/// A single executed instruction, along with the state of the CPU once it was done.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceRecord {
+    /// The number of cycles performed by the computer once the instruction was done.
+    pub cycle: u128,
+    /// The address of the instruction.
+    pub pc: u32,
+    /// The opcode and immediate bytes of the instruction.
+    pub opcode: Vec<u8>,
+    /// The instruction, written as mfs16asm.
+    pub instruction: String,
+    /// Register A.
+    pub a: u16,
+    /// Register B.
+    pub b: u16,
+    /// Register C.
+    pub c: u16,
+    /// Register D.
+    pub d: u16,
+    /// Register E.
+    pub e: u16,
+    /// Register H.
+    pub h: u16,
+    /// Register L.
+    pub l: u16,
+    /// The stack pointer.
+    pub sp: u32,
+    /// The flags, formatted the same way as [Flags](crate::Flags), e.g. `Zcopn`.
+    pub flags: String,
+    /// The reads and writes made by the instruction, oldest first.
+    pub accesses: Vec<MemAccess>,
+}
+impl TraceRecord {
+    /// Create a [TraceRecord] of the instruction which the given [Computer] just finished, which
+    /// made the given accesses.
+    pub fn from_computer(computer: &Computer, accesses: Vec<MemAccess>) -> Self {
+        let instruction = computer.cpu.instr;
+        let pc = computer.cpu.instr_addr;
+        let opcode = fetched_bytes(computer, pc, 2 + instruction.imm_size(), &accesses);
+        let immediate = opcode[2..]
+            .iter()
+            .rev()
+            .fold(0_u32, |acc, &byte| (acc << 8) | byte as u32);
+        let cpu = &computer.cpu;
+        Self {
+            cycle: computer.cycles,
+            pc,
+            opcode,
+            instruction: DecodedInstruction {
+                instruction,
+                immediate,
+            }
+            .to_string(),
+            a: cpu.reg(Reg16::A),
+            b: cpu.reg(Reg16::B),
+            c: cpu.reg(Reg16::C),
+            d: cpu.reg(Reg16::D),
+            e: cpu.reg(Reg16::E),
+            h: cpu.reg(Reg16::H),
+            l: cpu.reg(Reg16::L),
+            sp: cpu.sp.address(),
+            flags: cpu.flags.to_string(),
+            accesses,
+        }
+    }
+
+    /// Parse a JSON Lines trace. Blank lines are skipped.
+    pub fn from_json_lines(text: &str) -> Result<Vec<Self>, String> {
+        text.lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| serde_json::from_str(line).map_err(|e| format!("Line {}: {e}", i + 1)))
+            .collect()
+    }
+
+    /// Format the opcode bytes, e.g. `30 1D`.
+    fn format_opcode(&self) -> String {
+        self.opcode
+            .iter()
+            .map(|byte| format!("{byte:02X}"))
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+
+    /// Format the accesses, e.g. `R:0x00000010=0x1D30 W:0x00FFFFFC=0x0000000A`. With
+    /// `old_values`, writes which know the value memory held before them show it too, e.g.
+    /// `W:0x00FFFFFC=0x00000005->0x0000000A`.
+    fn format_accesses(&self, old_values: bool) -> String {
+        self.accesses
+            .iter()
+            .map(|access| {
+                let kind = match access.kind {
+                    AccessKind::Read => 'R',
+                    AccessKind::Write => 'W',
+                };
+                let digits = 2 + 2 * access.width as usize;
+                match access.old_value {
+                    Some(old_value) if old_values => format!(
+                        "{kind}:{:#010X}={old_value:#0digits$X}->{:#0digits$X}",
+                        access.address, access.value
+                    ),
+                    _ => format!(
+                        "{kind}:{:#010X}={:#0digits$X}",
+                        access.address, access.value
+                    ),
+                }
+            })
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+
+    /// Write this [TraceRecord] as a row of a CSV trace. Each access is written as e.g.
+    /// `W:0x00FFFFFC=0x0000000A`, separated by spaces.
+    fn write_csv<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let opcode = self.format_opcode();
+        let accesses = self.format_accesses(false);
+        writeln!(
+            writer,
+            "{},{:#010X},{opcode},\"{}\",{:#06X},{:#06X},{:#06X},{:#06X},{:#06X},{:#06X},{:#06X},{:#010X},{},{accesses}",
+            self.cycle,
+            self.pc,
+            self.instruction.replace('"', "\"\""),
+            self.a,
+            self.b,
+            self.c,
+            self.d,
+            self.e,
+            self.h,
+            self.l,
+            self.sp,
+            self.flags,
+        )
+    }
+}
+
+/// Get the opcode and immediate bytes of the instruction at `pc` as they were fetched by the CPU,
+/// so that code which modifies itself is traced correctly. Bytes whose fetch wasn't logged are
+/// read from memory instead.
+fn fetched_bytes(computer: &Computer, pc: u32, len: usize, accesses: &[MemAccess]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(len);
+    for access in accesses {
+        if bytes.len() >= len {
+            break;
+        }
+        if access.kind == AccessKind::Read && access.address == pc.wrapping_add(bytes.len() as u32)
+        {
+            bytes.extend(&access.value.to_le_bytes()[..access.width as usize]);
+        }
+    }
+    bytes.truncate(len);
+    while bytes.len() < len {
+        let address = pc.wrapping_add(bytes.len() as u32);
+        bytes.push(computer.mmu.inspect_byte(address));
+    }
+    bytes
+}
+
+/// Writes a [TraceRecord] for every instruction executed by a [Computer].
+///
+/// The memory accesses of each instruction are taken from the [Mmu](crate::Mmu), so they're only
+/// traced while it's logging accesses.
+pub struct Tracer<W: Write> {
+    format: TraceFormat,
+    writer: W,
+    /// Only trace the instructions in these ranges. Trace everything if empty.
+    ranges: Vec<TraceRange>,
+    /// The number of CPU cycles performed when the last instruction was traced.
+    last_total_cycles: u128,
+    /// Accesses which happened between instructions, e.g. when handling an interrupt.
+    pending: Vec<MemAccess>,
+}
+impl Tracer<BufWriter<File>> {
+    /// Create a new [Tracer] writing to the file at the given path. The format is chosen by the
+    /// extension of the path.
+    pub fn create(path: &Utf8Path, ranges: Vec<TraceRange>) -> io::Result<Self> {
+        let format = TraceFormat::from_path(path).ok_or(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("\"{path}\" does not end with .jsonl, .ndjson or .csv."),
+        ))?;
+        Self::new(BufWriter::new(File::create(path)?), format, ranges)
+    }
+}
+impl<W: Write> Tracer<W> {
+    /// Create a new [Tracer] writing the given format to the given writer.
+    pub fn new(mut writer: W, format: TraceFormat, ranges: Vec<TraceRange>) -> io::Result<Self> {
+        if format == TraceFormat::Csv {
+            writeln!(writer, "{CSV_HEADER}")?;
+        }
+        Ok(Self {
+            format,
+            writer,
+            ranges,
+            last_total_cycles: 0,
+            pending: Vec::new(),
+        })
+    }
+
+    /// Trace the instruction which the given [Computer] just finished. Call this every cycle on
+    /// which `computer.cpu.instr_is_done()`, with the accesses taken from the [Mmu](crate::Mmu)
+    /// since the last call.
+    pub fn record(&mut self, computer: &mut Computer, accesses: Vec<MemAccess>) -> io::Result<()> {
+        // While halted or handling an interrupt, no new instruction is executed
+        if computer.cpu.total_cycles == self.last_total_cycles {
+            self.pending.extend(accesses);
+            return Ok(());
+        }
+        self.last_total_cycles = computer.cpu.total_cycles;
+
+        let mut all_accesses = mem::take(&mut self.pending);
+        all_accesses.extend(accesses);
+        let pc = computer.cpu.instr_addr;
+        if !self.ranges.is_empty() && !self.ranges.iter().any(|range| range.contains(pc)) {
+            return Ok(());
+        }
+
+        let record = TraceRecord::from_computer(computer, all_accesses);
+        match self.format {
+            TraceFormat::JsonLines => {
+                serde_json::to_writer(&mut self.writer, &record)?;
+                writeln!(self.writer)
+            }
+            TraceFormat::Csv => record.write_csv(&mut self.writer),
+        }
+    }
+
+    /// Flush the trace to the writer.
+    pub fn finish(mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use crate::{
+        test_utils::{assemble, load_test_program},
+        Addr,
+    };
+
+    use super::*;
+
+    use crate::Instruction::*;
+
+    /// Trace the test program until it stops.
+    fn trace(format: TraceFormat, ranges: Vec<TraceRange>) -> String {
+        let mut computer = Computer::new(false);
+        load_test_program(&mut computer);
+
+        computer.mmu.log_accesses(true);
+        let mut output = Vec::new();
+        let mut tracer = Tracer::new(&mut output, format, ranges).unwrap();
+        while !computer.cpu.is_stopped {
+            computer.cycle();
+            if computer.cpu.instr_is_done() {
+                let accesses = computer.mmu.take_accesses();
+                tracer.record(&mut computer, accesses).unwrap();
+            }
+        }
+        tracer.finish().unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_json_lines() {
+        let output = trace(TraceFormat::JsonLines, Vec::new());
+        let records: Vec<TraceRecord> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        let summary: Vec<(u32, &str, u16)> = records
+            .iter()
+            .map(|record| (record.pc, record.instruction.as_str(), record.a))
+            .collect();
+        // The computer stops partway through STOP, so it's never done
+        assert_eq!(
+            summary,
+            [
+                (0x00, "LD A,0x0001:w;", 1),
+                (0x04, "CALL 0x0000_0010:d;", 1),
+                (0x10, "INC A;", 2),
+                (0x12, "RET;", 2),
+                (0x0A, "INC A;", 3),
+            ]
+        );
+
+        let call = &records[1];
+        assert_eq!(call.opcode[2..], [0x10, 0x00, 0x00, 0x00]);
+        assert_eq!(call.sp, 0xFFFFFC);
+        assert_eq!(
+            call.accesses.last(),
+            Some(&MemAccess {
+                kind: AccessKind::Write,
+                address: 0xFFFFFC,
+                width: 4,
+                value: 0x0A,
+                old_value: Some(0x00),
+            })
+        );
+        assert!(records.windows(2).all(|w| w[0].cycle < w[1].cycle));
+    }
+
+    #[test]
+    fn test_csv() {
+        let output = trace(TraceFormat::Csv, vec!["0x10..0x12".parse().unwrap()]);
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines[0], CSV_HEADER);
+        assert_eq!(lines.len(), 2);
+        assert!(lines[1].contains(
+            ",0x00000010,30 1D,\"INC A;\",0x0002,0x0000,0x0000,0x0000,0x0000,0x0000,0x0000,0x00FFFFFC,zcoPn,R:0x00000010=0x1D30"
+        ), "{}", lines[1]);
+    }
+
+    #[test]
+    fn test_self_modifying_code() {
+        let mut computer = Computer::new(false);
+        let bytes = assemble(&[(LdRaImm16(Reg16::A), 0x1234)]);
+        computer.direct_write(Addr::new_default_range(0x00), &bytes);
+
+        computer.mmu.log_accesses(true);
+        computer.cycle();
+        while !computer.cpu.instr_is_done() {
+            computer.cycle();
+        }
+        let accesses = computer.mmu.take_accesses();
+        // Overwrite the instruction after it's been executed
+        computer.direct_write(Addr::new_default_range(0x00), &[0; 4]);
+
+        let record = TraceRecord::from_computer(&computer, accesses);
+        assert_eq!(record.opcode, bytes);
+        assert_eq!(record.instruction, "LD A,0x1234:w;");
+        assert_eq!(record.a, 0x1234);
+    }
+
+    #[test]
+    fn test_trace_range() {
+        assert_eq!(
+            "0x100..0x2_00".parse(),
+            Ok(TraceRange {
+                start: 0x100,
+                end: 0x200
+            })
+        );
+        assert_eq!("16..32".parse::<TraceRange>().unwrap().end, 32);
+        assert_eq!(
+            "0x100".parse::<TraceRange>(),
+            Err(String::from(
+                "Range `0x100` is not written as `start..end`."
+            ))
+        );
+        assert_eq!(
+            "0x100..0xG".parse::<TraceRange>(),
+            Err(String::from("Invalid address `0xG` in range `0x100..0xG`."))
+        );
+    }
+}
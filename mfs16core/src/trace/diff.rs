@@ -0,0 +1,305 @@
+//! Finding the first instruction where two traces diverge.
+use std::fmt::Write;
+
+use super::TraceRecord;
+
+/// The fields of a [TraceRecord] which are compared, in the order they're reported.
+pub const TRACE_FIELDS: [&str; 14] = [
+    "cycle",
+    "pc",
+    "opcode",
+    "instruction",
+    "a",
+    "b",
+    "c",
+    "d",
+    "e",
+    "h",
+    "l",
+    "sp",
+    "flags",
+    "accesses",
+];
+
+/// A field which differs between two [TraceRecord]s.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldDifference {
+    /// The name of the field, one of [TRACE_FIELDS].
+    pub field: &'static str,
+    /// The formatted value in the left trace.
+    pub left: String,
+    /// The formatted value in the right trace.
+    pub right: String,
+}
+
+/// The first record where two traces diverge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceDivergence {
+    /// The index of the first record which differs. If one trace is a prefix of the other, this
+    /// is the length of the shorter one.
+    pub index: usize,
+    /// The fields which differ. Empty if one trace ended before the other. The accesses of the
+    /// two records also differ if a write found a different value in memory before it.
+    pub differences: Vec<FieldDifference>,
+}
+impl TraceDivergence {
+    /// Find the first record where the given traces differ, ignoring the given fields. Returns
+    /// [None] if the traces are the same. Memory is only compared through the accesses each
+    /// instruction made, so differences in memory which is never accessed aren't found.
+    pub fn find(left: &[TraceRecord], right: &[TraceRecord], ignore: &[&str]) -> Option<Self> {
+        for (index, (l, r)) in left.iter().zip(right).enumerate() {
+            if l == r {
+                continue;
+            }
+            let differences: Vec<FieldDifference> = l
+                .fields()
+                .into_iter()
+                .zip(r.fields())
+                .filter(|((field, l), (_, r))| l != r && !ignore.contains(field))
+                .map(|((field, left), (_, right))| FieldDifference { field, left, right })
+                .collect();
+            if !differences.is_empty() {
+                return Some(Self { index, differences });
+            }
+        }
+
+        (left.len() != right.len()).then(|| Self {
+            index: left.len().min(right.len()),
+            differences: Vec::new(),
+        })
+    }
+
+    /// Describe this divergence, showing up to `context` records before and after it. Records
+    /// which are the same in both traces are marked with `=`, and the left and right records are
+    /// marked with `<` and `>`.
+    pub fn report(&self, left: &[TraceRecord], right: &[TraceRecord], context: usize) -> String {
+        let mut report = String::new();
+        // Writing to a String can't fail
+        let _ = self.write_report(&mut report, left, right, context);
+        report
+    }
+
+    fn write_report(
+        &self,
+        f: &mut String,
+        left: &[TraceRecord],
+        right: &[TraceRecord],
+        context: usize,
+    ) -> std::fmt::Result {
+        match (left.get(self.index), right.get(self.index)) {
+            (Some(l), Some(r)) => writeln!(
+                f,
+                "Traces diverge at record {} (left cycle {}, right cycle {}).",
+                self.index, l.cycle, r.cycle
+            )?,
+            (Some(_), None) => writeln!(f, "Right trace ends after {} records.", self.index)?,
+            _ => writeln!(f, "Left trace ends after {} records.", self.index)?,
+        }
+        writeln!(f)?;
+
+        for record in &left[self.index.saturating_sub(context)..self.index] {
+            writeln!(f, "= {}", record.summary())?;
+        }
+        for offset in 0..=context {
+            let l = left.get(self.index + offset);
+            let r = right.get(self.index + offset);
+            if l.is_none() && r.is_none() {
+                break;
+            }
+            if let Some(l) = l {
+                writeln!(f, "< {}", l.summary())?;
+            }
+            if let Some(r) = r {
+                writeln!(f, "> {}", r.summary())?;
+            }
+        }
+
+        if !self.differences.is_empty() {
+            writeln!(f)?;
+            let width = self
+                .differences
+                .iter()
+                .map(|difference| difference.field.len())
+                .max()
+                .unwrap_or(0);
+            for FieldDifference { field, left, right } in &self.differences {
+                writeln!(f, "{field:>width$}: < {left}")?;
+                writeln!(f, "{:>width$}  > {right}", "")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl TraceRecord {
+    /// Format each of the [TRACE_FIELDS] of this record.
+    fn fields(&self) -> [(&'static str, String); TRACE_FIELDS.len()] {
+        let values = [
+            self.cycle.to_string(),
+            format!("{:#010X}", self.pc),
+            self.format_opcode(),
+            self.instruction.clone(),
+            format!("{:#06X}", self.a),
+            format!("{:#06X}", self.b),
+            format!("{:#06X}", self.c),
+            format!("{:#06X}", self.d),
+            format!("{:#06X}", self.e),
+            format!("{:#06X}", self.h),
+            format!("{:#06X}", self.l),
+            format!("{:#010X}", self.sp),
+            self.flags.clone(),
+            self.format_accesses(true),
+        ];
+        let mut values = values.into_iter();
+        TRACE_FIELDS.map(|field| (field, values.next().unwrap_or_default()))
+    }
+
+    /// Summarise this record on one line.
+    fn summary(&self) -> String {
+        format!(
+            "{:>10} {:#010X} {:<24} A:{:04X} B:{:04X} C:{:04X} D:{:04X} E:{:04X} H:{:04X} \
+             L:{:04X} SP:{:08X} {}",
+            self.cycle,
+            self.pc,
+            self.instruction,
+            self.a,
+            self.b,
+            self.c,
+            self.d,
+            self.e,
+            self.h,
+            self.l,
+            self.sp,
+            self.flags
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{AccessKind, MemAccess};
+
+    /// Make a trace of `n` INC A instructions.
+    fn trace(n: u16) -> Vec<TraceRecord> {
+        (0..n)
+            .map(|i| TraceRecord {
+                cycle: 2 * (i as u128 + 1),
+                pc: 2 * i as u32,
+                opcode: vec![0x30, 0x1D],
+                instruction: String::from("INC A;"),
+                a: i + 1,
+                b: 0,
+                c: 0,
+                d: 0,
+                e: 0,
+                h: 0,
+                l: 0,
+                sp: 0x800000,
+                flags: String::from("zcopn"),
+                accesses: Vec::new(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_find() {
+        let left = trace(8);
+        assert_eq!(TraceDivergence::find(&left, &left, &[]), None);
+
+        let mut right = left.clone();
+        right[5].a = 0xFF;
+        right[5].flags = String::from("Zcopn");
+        right[5].cycle += 1;
+        assert_eq!(
+            TraceDivergence::find(&left, &right, &["cycle"]),
+            Some(TraceDivergence {
+                index: 5,
+                differences: vec![
+                    FieldDifference {
+                        field: "a",
+                        left: String::from("0x0006"),
+                        right: String::from("0x00FF"),
+                    },
+                    FieldDifference {
+                        field: "flags",
+                        left: String::from("zcopn"),
+                        right: String::from("Zcopn"),
+                    },
+                ],
+            })
+        );
+        assert_eq!(
+            TraceDivergence::find(&left, &right, &["cycle", "a", "flags"]),
+            None
+        );
+
+        // Writes of the same value over different memory diverge too
+        let mut left = trace(8);
+        let mut right = left.clone();
+        left[2].accesses = vec![MemAccess {
+            kind: AccessKind::Write,
+            address: 0x100,
+            width: 2,
+            value: 0x1234,
+            old_value: Some(0x0000),
+        }];
+        right[2].accesses = vec![MemAccess {
+            old_value: Some(0xFFFF),
+            ..left[2].accesses[0]
+        }];
+        assert_eq!(
+            TraceDivergence::find(&left, &right, &[]),
+            Some(TraceDivergence {
+                index: 2,
+                differences: vec![FieldDifference {
+                    field: "accesses",
+                    left: String::from("W:0x00000100=0x0000->0x1234"),
+                    right: String::from("W:0x00000100=0xFFFF->0x1234"),
+                }],
+            })
+        );
+
+        assert_eq!(
+            TraceDivergence::find(&left, &left[..3], &[]),
+            Some(TraceDivergence {
+                index: 3,
+                differences: Vec::new(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_report() {
+        let left = trace(8);
+        let mut right = left.clone();
+        right[5].a = 0xFF;
+        let divergence = TraceDivergence::find(&left, &right, &[]).unwrap();
+        assert_eq!(
+            divergence.report(&left, &right, 1),
+            "Traces diverge at record 5 (left cycle 12, right cycle 12).
+
+=         10 0x00000008 INC A;                   A:0005 B:0000 C:0000 D:0000 E:0000 H:0000 L:0000 SP:00800000 zcopn
+<         12 0x0000000A INC A;                   A:0006 B:0000 C:0000 D:0000 E:0000 H:0000 L:0000 SP:00800000 zcopn
+>         12 0x0000000A INC A;                   A:00FF B:0000 C:0000 D:0000 E:0000 H:0000 L:0000 SP:00800000 zcopn
+<         14 0x0000000C INC A;                   A:0007 B:0000 C:0000 D:0000 E:0000 H:0000 L:0000 SP:00800000 zcopn
+>         14 0x0000000C INC A;                   A:0007 B:0000 C:0000 D:0000 E:0000 H:0000 L:0000 SP:00800000 zcopn
+
+a: < 0x0006
+   > 0x00FF
+"
+        );
+
+        let divergence = TraceDivergence::find(&left, &left[..7], &[]).unwrap();
+        assert_eq!(
+            divergence.report(&left, &left[..7], 1),
+            "Right trace ends after 7 records.
+
+=         14 0x0000000C INC A;                   A:0007 B:0000 C:0000 D:0000 E:0000 H:0000 L:0000 SP:00800000 zcopn
+<         16 0x0000000E INC A;                   A:0008 B:0000 C:0000 D:0000 E:0000 H:0000 L:0000 SP:00800000 zcopn
+"
+        );
+    }
+}
@@ -0,0 +1,376 @@
+//! Recording of the frames presented by the GPU to a video file.
+use std::{
+    borrow::Cow,
+    fs::File,
+    io::{self, BufWriter, Write},
+};
+
+use camino::Utf8Path;
+
+use crate::{
+    computer::{Computer, CLOCK_FREQ},
+    gpu::FRAME_CYCLES,
+};
+
+/// GIF frame delays are given in hundredths of a second, but most viewers slow down frames which
+/// are shorter than this.
+const GIF_MIN_DELAY: u128 = 2;
+
+/// The file formats which frames can be recorded to.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum VideoFormat {
+    /// An animated GIF which loops forever.
+    Gif,
+    /// An uncompressed YUV4MPEG2 video, readable by most video tools.
+    Y4m,
+    /// A sequence of binary PPM images, one after another in the same file.
+    Ppm,
+}
+impl VideoFormat {
+    /// Get the [VideoFormat] matching the extension of the given path, returning [None] if no
+    /// format matches.
+    pub fn from_path(path: &Utf8Path) -> Option<Self> {
+        match path.extension()?.to_ascii_lowercase().as_str() {
+            "gif" => Some(Self::Gif),
+            "y4m" => Some(Self::Y4m),
+            "ppm" => Some(Self::Ppm),
+            _ => None,
+        }
+    }
+}
+
+/// Records the frames presented by the GPU. Every frame is displayed with the palette it was
+/// presented with, and scaled to the size of the first frame recorded.
+///
+/// Frames are timed by the cycle on which they were presented, so frames which are presented late
+/// are shown for longer. The last frame is only written once the recording is finished.
+pub struct VideoRecorder<W: Write> {
+    format: VideoFormat,
+    output: Output<W>,
+    /// The size of every recorded frame, set by the first frame.
+    size: Option<(usize, usize)>,
+    /// The frame waiting to be written until it's known how long it's displayed for.
+    pending: Option<PendingFrame>,
+}
+impl VideoRecorder<BufWriter<File>> {
+    /// Create a new [VideoRecorder] writing to the file at the given path. The format is chosen
+    /// by the extension of the path.
+    pub fn create(path: &Utf8Path) -> io::Result<Self> {
+        let format = VideoFormat::from_path(path).ok_or(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("\"{path}\" does not end with .gif, .y4m or .ppm."),
+        ))?;
+        Ok(Self::new(BufWriter::new(File::create(path)?), format))
+    }
+}
+impl<W: Write> VideoRecorder<W> {
+    /// Create a new [VideoRecorder] writing the given format to the given writer.
+    pub fn new(writer: W, format: VideoFormat) -> Self {
+        Self {
+            format,
+            output: Output::Raw(writer),
+            size: None,
+            pending: None,
+        }
+    }
+
+    /// Record the frame most recently presented by the GPU of the given [Computer].
+    pub fn record(&mut self, computer: &Computer) -> io::Result<()> {
+        let gpu = &computer.mmu.gpu;
+        let (width, height) = gpu.frame_size();
+        self.record_frame(
+            gpu.frame_buffer(),
+            width,
+            height,
+            gpu.palette(),
+            computer.cycles,
+        )
+    }
+
+    /// Record a frame, made of one palette index per pixel, which was presented on the given
+    /// cycle.
+    pub fn record_frame(
+        &mut self,
+        pixels: &[u8],
+        width: usize,
+        height: usize,
+        palette: &[u32],
+        cycles: u128,
+    ) -> io::Result<()> {
+        let (out_width, out_height) = match self.size {
+            Some(size) => size,
+            None => {
+                self.start(width, height)?;
+                (width, height)
+            }
+        };
+
+        // Scale the frame to the size of the recording
+        let pixels = (0..(out_width * out_height))
+            .map(|i| {
+                let x = (i % out_width) * width / out_width;
+                let y = (i / out_width) * height / out_height;
+                pixels[(y * width) + x]
+            })
+            .collect();
+        let frame = PendingFrame {
+            pixels,
+            palette: palette.to_vec(),
+            time: self.time(cycles),
+        };
+
+        match self.pending.take() {
+            // A later frame shown at the same time replaces the earlier one
+            Some(pending) if pending.time >= frame.time => {}
+            Some(pending) => {
+                let duration = frame.time - pending.time;
+                self.write_frame(&pending, duration)?;
+            }
+            None => {}
+        }
+        self.pending = Some(frame);
+        Ok(())
+    }
+
+    /// Write the last frame and finish the recording.
+    pub fn finish(mut self) -> io::Result<()> {
+        if let Some(pending) = self.pending.take() {
+            self.write_frame(&pending, 1)?;
+        }
+        let mut writer = match self.output {
+            Output::Raw(writer) => writer,
+            Output::Gif(encoder) => encoder.into_inner()?,
+            Output::Empty => return Ok(()),
+        };
+        writer.flush()
+    }
+
+    /// Start the recording with the size of the first frame.
+    fn start(&mut self, width: usize, height: usize) -> io::Result<()> {
+        self.size = Some((width, height));
+        match (
+            self.format,
+            std::mem::replace(&mut self.output, Output::Empty),
+        ) {
+            (VideoFormat::Gif, Output::Raw(writer)) => {
+                let mut encoder = gif::Encoder::new(writer, to_u16(width)?, to_u16(height)?, &[])
+                    .map_err(io::Error::other)?;
+                encoder
+                    .set_repeat(gif::Repeat::Infinite)
+                    .map_err(io::Error::other)?;
+                self.output = Output::Gif(encoder);
+                Ok(())
+            }
+            (VideoFormat::Y4m, Output::Raw(mut writer)) => {
+                // The colours are stored at full range instead of the usual 16-235
+                writeln!(
+                    writer,
+                    "YUV4MPEG2 W{width} H{height} F{CLOCK_FREQ}:{FRAME_CYCLES} Ip A1:1 C444 \
+                    XCOLORRANGE=FULL"
+                )?;
+                self.output = Output::Raw(writer);
+                Ok(())
+            }
+            (_, output) => {
+                self.output = output;
+                Ok(())
+            }
+        }
+    }
+
+    /// Get the time at which a frame presented on the given cycle is shown. For GIFs, this is
+    /// counted in the shortest frame delay. For the other formats, this is counted in frames.
+    fn time(&self, cycles: u128) -> u128 {
+        match self.format {
+            VideoFormat::Gif => (cycles * 100) / (CLOCK_FREQ as u128 * GIF_MIN_DELAY),
+            VideoFormat::Y4m | VideoFormat::Ppm => cycles / FRAME_CYCLES as u128,
+        }
+    }
+
+    /// Write a frame which is shown for the given duration, counted the same way as
+    /// [VideoRecorder::time].
+    fn write_frame(&mut self, frame: &PendingFrame, duration: u128) -> io::Result<()> {
+        let (width, height) = self.size.unwrap_or_default();
+        let rgb = || {
+            frame
+                .pixels
+                .iter()
+                .map(|&index| frame.palette[index as usize])
+        };
+
+        match &mut self.output {
+            Output::Gif(encoder) => {
+                let delay = u16::try_from(duration * GIF_MIN_DELAY).unwrap_or(u16::MAX);
+                let palette = frame
+                    .palette
+                    .iter()
+                    .flat_map(|&colour| [(colour >> 16) as u8, (colour >> 8) as u8, colour as u8])
+                    .collect();
+                let gif_frame = gif::Frame {
+                    width: to_u16(width)?,
+                    height: to_u16(height)?,
+                    delay,
+                    palette: Some(palette),
+                    buffer: Cow::Borrowed(&frame.pixels),
+                    ..gif::Frame::default()
+                };
+                encoder.write_frame(&gif_frame).map_err(io::Error::other)
+            }
+            Output::Raw(writer) => {
+                let image = match self.format {
+                    VideoFormat::Y4m => {
+                        // Each plane of the image is stored one after the other
+                        let mut image = b"FRAME\n".to_vec();
+                        image.extend(rgb().map(|colour| yuv(colour).0));
+                        image.extend(rgb().map(|colour| yuv(colour).1));
+                        image.extend(rgb().map(|colour| yuv(colour).2));
+                        image
+                    }
+                    _ => {
+                        let mut image = format!("P6\n{width} {height}\n255\n").into_bytes();
+                        image.extend(rgb().flat_map(|colour| {
+                            [(colour >> 16) as u8, (colour >> 8) as u8, colour as u8]
+                        }));
+                        image
+                    }
+                };
+                for _ in 0..duration {
+                    writer.write_all(&image)?;
+                }
+                Ok(())
+            }
+            Output::Empty => Ok(()),
+        }
+    }
+}
+
+/// Where the recording is written.
+enum Output<W: Write> {
+    /// Written directly.
+    Raw(W),
+    /// Written by a GIF encoder, which is created along with the first frame.
+    Gif(gif::Encoder<W>),
+    /// Only used while switching between the other outputs.
+    Empty,
+}
+
+/// A frame which is waiting to be written.
+struct PendingFrame {
+    /// One palette index per pixel, already scaled to the size of the recording.
+    pixels: Vec<u8>,
+    /// The colours of the palette, stored as `0x00RRGGBB`.
+    palette: Vec<u32>,
+    /// The time at which the frame is shown. See [VideoRecorder::time].
+    time: u128,
+}
+
+/// Convert a frame dimension to the size used by GIFs.
+fn to_u16(val: usize) -> io::Result<u16> {
+    u16::try_from(val).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Frame dimension {val} is too large."),
+        )
+    })
+}
+
+/// Convert a colour stored as `0x00RRGGBB` to full-range BT.601 YCbCr.
+fn yuv(colour: u32) -> (u8, u8, u8) {
+    let r = ((colour >> 16) & 0xFF) as f32;
+    let g = ((colour >> 8) & 0xFF) as f32;
+    let b = (colour & 0xFF) as f32;
+    let y = (0.299 * r) + (0.587 * g) + (0.114 * b);
+    let cb = 128.0 - (0.168736 * r) - (0.331264 * g) + (0.5 * b);
+    let cr = 128.0 + (0.5 * r) - (0.418688 * g) - (0.081312 * b);
+    (
+        y.round().clamp(0.0, 255.0) as u8,
+        cb.round().clamp(0.0, 255.0) as u8,
+        cr.round().clamp(0.0, 255.0) as u8,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const PALETTE: [u32; 2] = [0x000000, 0xFF8000];
+
+    #[test]
+    fn test_ppm() {
+        let mut output = Vec::new();
+        let mut recorder = VideoRecorder::new(&mut output, VideoFormat::Ppm);
+        recorder
+            .record_frame(&[0, 1], 2, 1, &PALETTE, FRAME_CYCLES as u128)
+            .unwrap();
+        // Half the size, so it's scaled up to the size of the first frame
+        recorder
+            .record_frame(&[1], 1, 1, &PALETTE, 3 * FRAME_CYCLES as u128)
+            .unwrap();
+        recorder.finish().unwrap();
+
+        let first = b"P6\n2 1\n255\n\x00\x00\x00\xFF\x80\x00";
+        let second = b"P6\n2 1\n255\n\xFF\x80\x00\xFF\x80\x00";
+        // The first frame is shown for two frames, since the second frame is late
+        assert_eq!(output, [&first[..], first, second].concat());
+    }
+
+    #[test]
+    fn test_y4m() {
+        let mut output = Vec::new();
+        let mut recorder = VideoRecorder::new(&mut output, VideoFormat::Y4m);
+        recorder.record_frame(&[0, 1], 2, 1, &PALETTE, 0).unwrap();
+        // Replaces the first frame, since it's shown at the same time
+        recorder.record_frame(&[1, 0], 2, 1, &PALETTE, 1).unwrap();
+        recorder.finish().unwrap();
+
+        let header =
+            format!("YUV4MPEG2 W2 H1 F{CLOCK_FREQ}:{FRAME_CYCLES} Ip A1:1 C444 XCOLORRANGE=FULL\n");
+        let frame = b"FRAME\n\x97\x00\x2B\x80\xCA\x80";
+        assert_eq!(output, [header.as_bytes(), frame].concat());
+    }
+
+    #[test]
+    fn test_gif() {
+        let mut output = Vec::new();
+        let mut recorder = VideoRecorder::new(&mut output, VideoFormat::Gif);
+        for i in 0..4 {
+            recorder
+                .record_frame(&[i % 2, 1], 2, 1, &PALETTE, i as u128 * CLOCK_FREQ as u128)
+                .unwrap();
+        }
+        recorder.finish().unwrap();
+
+        let mut decoder = gif::DecodeOptions::new();
+        decoder.set_color_output(gif::ColorOutput::Indexed);
+        let mut decoder = decoder.read_info(output.as_slice()).unwrap();
+        let mut frames = Vec::new();
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            frames.push((frame.buffer.to_vec(), frame.delay));
+        }
+        assert_eq!(
+            frames,
+            [
+                (vec![0, 1], 100),
+                (vec![1, 1], 100),
+                (vec![0, 1], 100),
+                (vec![1, 1], 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(
+            VideoFormat::from_path(Utf8Path::new("pong.GIF")),
+            Some(VideoFormat::Gif)
+        );
+        assert_eq!(
+            VideoFormat::from_path(Utf8Path::new("out/frames.ppm")),
+            Some(VideoFormat::Ppm)
+        );
+        assert_eq!(VideoFormat::from_path(Utf8Path::new("pong.mp4")), None);
+        assert_eq!(VideoFormat::from_path(Utf8Path::new("pong")), None);
+    }
+}
@@ -1,10 +1,9 @@
 use camino::Utf8PathBuf;
 use clap::Parser;
+use mfs16core::TraceRange;
 
 use crate::utils;
 
-const DEFAULT_FPS: f32 = 60.0;
-
 /// The CLI parser.
 #[derive(Parser, Debug)]
 #[command(name = "mfs16")]
@@ -31,7 +30,39 @@ pub struct Cli {
     #[clap(short = 'k', long = "keyboard")]
     pub kb_debug: bool,
 
-    /// Set the desired FPS.
-    #[clap(default_value_t = DEFAULT_FPS, short = 'f', long = "fps")]
-    pub fps: f32,
+    /// Type out the contents of this text file once the program starts.
+    #[clap(short = 't', long = "type")]
+    pub type_file: Option<Utf8PathBuf>,
+
+    /// Record every input given to the computer to this file.
+    #[clap(short = 'r', long = "record")]
+    pub record: Option<Utf8PathBuf>,
+
+    /// Record every frame to this file. The format is chosen by the extension: .gif, .y4m or .ppm.
+    #[clap(short = 'v', long = "video")]
+    pub video: Option<Utf8PathBuf>,
+
+    /// Write a record of every executed instruction to this file. The format is chosen by the
+    /// extension: .jsonl or .csv.
+    #[clap(long = "trace")]
+    pub trace: Option<Utf8PathBuf>,
+
+    /// Only trace the instructions in this range of addresses, e.g. `0x100..0x200`. Can be given
+    /// more than once.
+    #[clap(long = "trace-range", value_name = "START..END", requires = "trace")]
+    pub trace_range: Vec<TraceRange>,
+
+    /// Replay the inputs recorded in this file. Live input is ignored until the replay is done.
+    #[clap(short = 'p', long = "replay")]
+    pub replay: Option<Utf8PathBuf>,
+
+    /// Label addresses in the debug log using this symbol map. Defaults to `<bin>.sym` if it
+    /// exists.
+    #[clap(short = 's', long = "symbols")]
+    pub symbols: Option<Utf8PathBuf>,
+
+    /// Show source lines in the debug log using this line table. Defaults to `<bin>.lines` if it
+    /// exists.
+    #[clap(short = 'l', long = "lines")]
+    pub lines: Option<Utf8PathBuf>,
 }
@@ -3,11 +3,13 @@ use std::default::Default;
 use camino::{Utf8Path, Utf8PathBuf};
 use color_eyre::eyre;
 use config::{self, Config};
+use mfs16core::{KbLayout, DEFAULT_KEYSTROKE_CYCLES};
 use sdl2::keyboard::Scancode;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    debug::{BreakCriteria, MemRange},
+    controllers::GamepadBindings,
+    debug::{BreakCriteria, Breakpoint, MemRange},
     palette::HexPalette,
     scancodes,
     utils::expand_path,
@@ -37,17 +39,28 @@ pub struct PathSettings {
     pub data_path: Option<Utf8PathBuf>,
 }
 
+/// The text input settings.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TextInputSettings {
+    pub layout: String,
+    pub keystroke_cycles: u32,
+}
+
 /// The key bindings.
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct KeyBindings {
     #[serde(with = "scancodes")]
     pub exit: Scancode,
+    #[serde(with = "scancodes")]
+    pub paste: Scancode,
 }
 
 /// The debugger settings.
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct DebuggerSettings {
     pub break_criteria: BreakCriteria,
+    #[serde(default)]
+    pub breakpoints: Vec<Breakpoint>,
     pub mem_ranges: Vec<MemRange>,
     pub history_size: usize,
     pub cycles_after_break: usize,
@@ -59,6 +72,8 @@ pub struct UserConfig {
     pub palette_settings: PaletteSettings,
     pub path_settings: PathSettings,
     pub key_bindings: KeyBindings,
+    pub text_input_settings: TextInputSettings,
+    pub gamepad_bindings: GamepadBindings,
     pub debugger_settings: DebuggerSettings,
 }
 impl UserConfig {
@@ -102,6 +117,16 @@ impl UserConfig {
         &self.key_bindings.exit
     }
 
+    /// Directly access the "Paste" scancode.
+    pub fn paste_scancode(&self) -> &Scancode {
+        &self.key_bindings.paste
+    }
+
+    /// Get the keyboard layout of the config.
+    pub fn kb_layout(&self) -> Option<KbLayout> {
+        KbLayout::from_name(&self.text_input_settings.layout)
+    }
+
     /// Expand any files paths in the config.
     fn expand_file_paths(mut self) -> eyre::Result<Self> {
         if let Some(data_path) = &self.path_settings.data_path {
@@ -134,7 +159,13 @@ impl Default for UserConfig {
             path_settings: PathSettings { data_path: None },
             key_bindings: KeyBindings {
                 exit: Scancode::Escape,
+                paste: Scancode::F10,
+            },
+            text_input_settings: TextInputSettings {
+                layout: KbLayout::default().to_string(),
+                keystroke_cycles: DEFAULT_KEYSTROKE_CYCLES,
             },
+            gamepad_bindings: GamepadBindings::default(),
             debugger_settings: DebuggerSettings {
                 break_criteria: BreakCriteria {
                     pc_list: Vec::new(),
@@ -144,7 +175,9 @@ impl Default for UserConfig {
                     instr_list: Vec::new(),
                     reg_upper_bounds: Vec::new(),
                     reg_lower_bounds: Vec::new(),
+                    watchpoints: Vec::new(),
                 },
+                breakpoints: Vec::new(),
                 mem_ranges: Vec::new(),
                 history_size: 16,
                 cycles_after_break: 16,
@@ -0,0 +1,157 @@
+use mfs16core::{GamepadAxis, GamepadButton};
+use sdl2::controller::{Axis, Button};
+use serde::{Deserialize, Serialize};
+
+/// The mapping of SDL2 game controller buttons and axes to the MFS-16 gamepad.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct GamepadBindings {
+    #[serde(with = "button")]
+    pub up: Button,
+    #[serde(with = "button")]
+    pub down: Button,
+    #[serde(with = "button")]
+    pub left: Button,
+    #[serde(with = "button")]
+    pub right: Button,
+    #[serde(with = "button")]
+    pub a: Button,
+    #[serde(with = "button")]
+    pub b: Button,
+    #[serde(with = "button")]
+    pub x: Button,
+    #[serde(with = "button")]
+    pub y: Button,
+    #[serde(with = "button")]
+    pub l: Button,
+    #[serde(with = "button")]
+    pub r: Button,
+    #[serde(with = "button")]
+    pub start: Button,
+    #[serde(with = "button")]
+    pub select: Button,
+    #[serde(with = "axis")]
+    pub left_x: Axis,
+    #[serde(with = "axis")]
+    pub left_y: Axis,
+    #[serde(with = "axis")]
+    pub right_x: Axis,
+    #[serde(with = "axis")]
+    pub right_y: Axis,
+    #[serde(with = "axis")]
+    pub left_trigger: Axis,
+    #[serde(with = "axis")]
+    pub right_trigger: Axis,
+}
+impl GamepadBindings {
+    /// Get every SDL2 [Button] along with the [GamepadButton] bound to it.
+    pub fn buttons(&self) -> [(Button, GamepadButton); 12] {
+        [
+            (self.up, GamepadButton::Up),
+            (self.down, GamepadButton::Down),
+            (self.left, GamepadButton::Left),
+            (self.right, GamepadButton::Right),
+            (self.a, GamepadButton::A),
+            (self.b, GamepadButton::B),
+            (self.x, GamepadButton::X),
+            (self.y, GamepadButton::Y),
+            (self.l, GamepadButton::L),
+            (self.r, GamepadButton::R),
+            (self.start, GamepadButton::Start),
+            (self.select, GamepadButton::Select),
+        ]
+    }
+
+    /// Get every SDL2 [Axis] along with the [GamepadAxis] bound to it.
+    pub fn axes(&self) -> [(Axis, GamepadAxis); 6] {
+        [
+            (self.left_x, GamepadAxis::LeftX),
+            (self.left_y, GamepadAxis::LeftY),
+            (self.right_x, GamepadAxis::RightX),
+            (self.right_y, GamepadAxis::RightY),
+            (self.left_trigger, GamepadAxis::LeftTrigger),
+            (self.right_trigger, GamepadAxis::RightTrigger),
+        ]
+    }
+
+    /// Get the [GamepadButton] bound to the given SDL2 [Button], if any.
+    pub fn gamepad_button(&self, sdl_button: Button) -> Option<GamepadButton> {
+        self.buttons()
+            .into_iter()
+            .find(|(bound, _)| bound == &sdl_button)
+            .map(|(_, button)| button)
+    }
+
+    /// Get the [GamepadAxis] bound to the given SDL2 [Axis], if any.
+    pub fn gamepad_axis(&self, sdl_axis: Axis) -> Option<GamepadAxis> {
+        self.axes()
+            .into_iter()
+            .find(|(bound, _)| bound == &sdl_axis)
+            .map(|(_, axis)| axis)
+    }
+}
+impl Default for GamepadBindings {
+    fn default() -> Self {
+        Self {
+            up: Button::DPadUp,
+            down: Button::DPadDown,
+            left: Button::DPadLeft,
+            right: Button::DPadRight,
+            a: Button::A,
+            b: Button::B,
+            x: Button::X,
+            y: Button::Y,
+            l: Button::LeftShoulder,
+            r: Button::RightShoulder,
+            start: Button::Start,
+            select: Button::Back,
+            left_x: Axis::LeftX,
+            left_y: Axis::LeftY,
+            right_x: Axis::RightX,
+            right_y: Axis::RightY,
+            left_trigger: Axis::TriggerLeft,
+            right_trigger: Axis::TriggerRight,
+        }
+    }
+}
+
+/// (De)serialize an SDL2 game controller [Button] by its mapping string name.
+pub mod button {
+    use sdl2::controller::Button;
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(button: &Button, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&button.string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Button, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Button::from_string(&name).ok_or(de::Error::custom(format!(
+            "Game controller button \"{}\" is unsupported by SDL2.",
+            name
+        )))
+    }
+}
+
+/// (De)serialize an SDL2 game controller [Axis] by its mapping string name.
+pub mod axis {
+    use sdl2::controller::Axis;
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(axis: &Axis, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&axis.string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Axis, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Axis::from_string(&name).ok_or(de::Error::custom(format!(
+            "Game controller axis \"{}\" is unsupported by SDL2.",
+            name
+        )))
+    }
+}
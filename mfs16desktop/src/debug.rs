@@ -1,8 +1,11 @@
 use camino::Utf8Path;
-use color_eyre::eyre;
-use mfs16core::{Computer, Instruction, Reg16};
+use color_eyre::eyre::{self, eyre};
+use mfs16core::{
+    BreakCondition, Computer, Cpu, Instruction, LineTable, MemAccess, Reg16, SymbolMap, Watchpoint,
+    NUM_PALETTE_COLOURS,
+};
 use serde::{Deserialize, Serialize};
-use std::{collections::VecDeque, fmt::Display};
+use std::{collections::VecDeque, fmt::Display, sync::Arc};
 use std::{fs::OpenOptions, io::Write};
 
 /// The number of bytes at and after program counter to store in the history.
@@ -13,6 +16,8 @@ pub const PC_BYTES_SIZE: usize = 16;
 pub struct Debugger {
     /// The criteria for breaking.
     pub criteria: BreakCriteria,
+    /// The breakpoints, any of which can break independently of the criteria.
+    breakpoints: Vec<ConditionalBreakpoint>,
     /// The different states of the computer over the last [HISTORY_SIZE] cycles.
     pub history: VecDeque<ComputerState>,
     /// The different states of the computer after the break.
@@ -25,27 +30,48 @@ pub struct Debugger {
     history_size: usize,
     /// The number of cycles to store after the breakpoint.
     cycles_after_break: usize,
+    /// The symbols and source lines used to label the program counter.
+    debug_symbols: Arc<DebugSymbols>,
 }
 impl Debugger {
-    /// Create a new [Debugger] with the given [BreakCriteria] and [MemRange]s.
+    /// Create a new [Debugger] with the given [BreakCriteria], [ConditionalBreakpoint]s and
+    /// [MemRange]s.
     pub fn new(
         criteria: BreakCriteria,
+        breakpoints: Vec<ConditionalBreakpoint>,
         mem_ranges: Vec<MemRange>,
         cpu_only: bool,
         history_size: usize,
         cycles_after_break: usize,
+        debug_symbols: Arc<DebugSymbols>,
     ) -> Self {
         Self {
             criteria,
+            breakpoints,
             history: VecDeque::with_capacity(history_size),
             after_break_history: VecDeque::with_capacity(cycles_after_break),
             mem_ranges,
             cpu_only,
             history_size,
             cycles_after_break,
+            debug_symbols,
         }
     }
 
+    /// Check whether the given [Computer] should break after an instruction which made the given
+    /// memory accesses.
+    pub fn should_break(&mut self, computer: &mut Computer, accesses: &[MemAccess]) -> bool {
+        let mut should_break = self.criteria.is_satisfied(computer, accesses);
+        // Check every breakpoint so that they all count their hits
+        for breakpoint in &mut self.breakpoints {
+            if breakpoint.check(computer) {
+                println!("Hit breakpoint `{}`.", breakpoint.condition);
+                should_break = true;
+            }
+        }
+        should_break
+    }
+
     /// Add the given [Computer]'s current state to history after the breakpoint was reached.
     /// Returns false if should stop.
     pub fn add_state_after_breakpoint(&mut self, computer: &mut Computer) -> bool {
@@ -57,6 +83,7 @@ impl Debugger {
                 computer,
                 &self.mem_ranges,
                 self.cpu_only,
+                Arc::clone(&self.debug_symbols),
             ));
         true
     }
@@ -70,6 +97,7 @@ impl Debugger {
             computer,
             &self.mem_ranges,
             self.cpu_only,
+            Arc::clone(&self.debug_symbols),
         ));
     }
 
@@ -104,6 +132,85 @@ impl Display for Debugger {
     }
 }
 
+/// The symbol map and line table of the running program, used to label addresses in the debug
+/// log.
+#[derive(Debug, Clone, Default)]
+pub struct DebugSymbols {
+    /// The labels and variables of the program.
+    pub symbols: Option<SymbolMap>,
+    /// The source line of every byte of the program.
+    pub lines: Option<LineTable>,
+}
+impl DebugSymbols {
+    /// Format the given [Cpu] with its program counter relative to the nearest label, followed by
+    /// the source line of the current instruction.
+    fn format_cpu(&self, cpu: &Cpu) -> String {
+        let mut formatted = match &self.symbols {
+            Some(symbols) => cpu.to_string_with_symbols(symbols),
+            None => cpu.to_string(),
+        };
+        if let Some(source_line) = self
+            .lines
+            .as_ref()
+            .and_then(|lines| lines.lookup(cpu.pc.address()))
+        {
+            formatted.push_str(&format!("|{source_line}"));
+        }
+        formatted
+    }
+}
+
+/// A breakpoint which breaks when its condition is satisfied, written in the language of
+/// [BreakCondition].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Breakpoint {
+    /// The condition, e.g. `pc == label(main_loop) && [0x800010].w > 5 || flag(Z)`.
+    pub condition: String,
+    /// Break the nth time the condition is satisfied. Must be at least 1.
+    #[serde(default = "Breakpoint::default_hit_count")]
+    pub hit_count: u64,
+}
+impl Breakpoint {
+    fn default_hit_count() -> u64 {
+        1
+    }
+}
+
+/// A [Breakpoint] with a parsed condition, which counts how many times it has been hit.
+#[derive(Debug, Clone)]
+pub struct ConditionalBreakpoint {
+    condition: BreakCondition,
+    hit_count: u64,
+    hits: u64,
+}
+impl ConditionalBreakpoint {
+    /// Parse the condition of the given [Breakpoint], looking up labels in the given [SymbolMap].
+    pub fn parse(breakpoint: &Breakpoint, symbols: Option<&SymbolMap>) -> eyre::Result<Self> {
+        let condition = BreakCondition::parse(&breakpoint.condition, symbols)
+            .map_err(|e| eyre!("Invalid breakpoint `{}`: {e}", breakpoint.condition))?;
+        if breakpoint.hit_count == 0 {
+            return Err(eyre!(
+                "Invalid breakpoint `{}`: hit_count must be at least 1.",
+                breakpoint.condition
+            ));
+        }
+        Ok(Self {
+            condition,
+            hit_count: breakpoint.hit_count,
+            hits: 0,
+        })
+    }
+
+    /// Count a hit if the condition is satisfied, returning true iff this is the hit to break on.
+    fn check(&mut self, computer: &Computer) -> bool {
+        if !self.condition.is_satisfied(computer) {
+            return false;
+        }
+        self.hits += 1;
+        self.hits == self.hit_count
+    }
+}
+
 /// A register-value pair.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RegValPair {
@@ -128,16 +235,21 @@ pub struct BreakCriteria {
     pub reg_upper_bounds: Vec<RegValPair>,
     // If any register is less than its corresponding value, break.
     pub reg_lower_bounds: Vec<RegValPair>,
+    // If any of these ranges of memory is accessed, break.
+    #[serde(default)]
+    pub watchpoints: Vec<Watchpoint>,
 }
 impl BreakCriteria {
-    /// Check to see whether the given [Computer]'s state satisfies the break criteria.
-    pub fn is_satisfied(&self, computer: &Computer) -> bool {
+    /// Check to see whether the given [Computer]'s state and the memory accesses of its last
+    /// instruction satisfy the break criteria.
+    pub fn is_satisfied(&self, computer: &Computer, accesses: &[MemAccess]) -> bool {
         if self.pc_list.is_empty()
             && self.instr_list.is_empty()
             && self.pc_lower_bound.is_none()
             && self.pc_upper_bound.is_none()
             && self.reg_lower_bounds.is_empty()
             && self.reg_upper_bounds.is_empty()
+            && self.watchpoints.is_empty()
             && !self.ei
         {
             return false;
@@ -149,6 +261,7 @@ impl BreakCriteria {
             && self.reg_bounds_satisfied(computer, true)
             && self.reg_bounds_satisfied(computer, false)
             && self.ei_satisfied(computer)
+            && self.watchpoints_satisfied(accesses)
     }
 
     fn ei_satisfied(&self, computer: &Computer) -> bool {
@@ -210,6 +323,15 @@ impl BreakCriteria {
         false
     }
 
+    fn watchpoints_satisfied(&self, accesses: &[MemAccess]) -> bool {
+        if self.watchpoints.is_empty() {
+            return true;
+        }
+        self.watchpoints
+            .iter()
+            .any(|wp| accesses.iter().any(|access| wp.is_triggered_by(access)))
+    }
+
     fn instr_satisfied(&self, computer: &Computer) -> bool {
         if self.instr_list.is_empty() {
             return true;
@@ -234,10 +356,11 @@ pub struct MemRange {
 impl MemRange {
     /// Get this range of memory from the given [Computer].
     fn grab(self, computer: &mut Computer) -> (Self, Vec<u8>) {
-        let mut result = Vec::with_capacity((self.end - self.start) as usize);
-        for address in self.start..self.end {
-            result.push(computer.mmu.read_byte(address));
-        }
+        let result = computer.mmu.without_access_log(|mmu| {
+            (self.start..self.end)
+                .map(|address| mmu.read_byte(address))
+                .collect()
+        });
         (self, result)
     }
 }
@@ -257,14 +380,23 @@ pub struct ComputerState {
     cpu_state: mfs16core::Cpu,
     /// The bytes at and after the PC.
     pc_bytes: Option<[u8; PC_BYTES_SIZE]>,
+    /// The GPU palette colours.
+    palette: Option<[u32; NUM_PALETTE_COLOURS]>,
     /// User-defined ranges of memory.
     memory_ranges: Option<Vec<(MemRange, Vec<u8>)>>,
     /// Whether only the CPU string should be printed or not.
     cpu_only: bool,
+    /// The symbols and source lines used to label the program counter.
+    debug_symbols: Arc<DebugSymbols>,
 }
 impl ComputerState {
     /// Create a new [ComputerState] from a given [Computer] and [MemRange]s.
-    fn from_computer(computer: &mut Computer, mem_ranges: &[MemRange], cpu_only: bool) -> Self {
+    fn from_computer(
+        computer: &mut Computer,
+        mem_ranges: &[MemRange],
+        cpu_only: bool,
+        debug_symbols: Arc<DebugSymbols>,
+    ) -> Self {
         if cpu_only {
             Self {
                 num_cycles: None,
@@ -273,8 +405,10 @@ impl ComputerState {
                 ie_register: None,
                 cpu_state: computer.cpu.clone(),
                 pc_bytes: None,
+                palette: None,
                 memory_ranges: None,
                 cpu_only,
+                debug_symbols,
             }
         } else {
             Self {
@@ -284,8 +418,10 @@ impl ComputerState {
                 ie_register: Some(computer.mmu.ie_register),
                 cpu_state: computer.cpu.clone(),
                 pc_bytes: Some(Self::read_pc_bytes(computer)),
+                palette: Some(*computer.mmu.gpu.palette()),
                 memory_ranges: Some(mem_ranges.iter().map(|mr| mr.grab(computer)).collect()),
                 cpu_only,
+                debug_symbols,
             }
         }
     }
@@ -293,19 +429,22 @@ impl ComputerState {
     /// Read [PC_BYTES_SIZE] bytes from a given [Computer], starting at the [Computer]'s program
     /// counter.
     fn read_pc_bytes(computer: &mut Computer) -> [u8; PC_BYTES_SIZE] {
+        let pc = computer.cpu.pc.address();
         let mut result = [0_u8; PC_BYTES_SIZE];
-        for (index, item) in result.iter_mut().enumerate().take(PC_BYTES_SIZE) {
-            *item = computer
-                .mmu
-                .read_byte(computer.cpu.pc.address() + (index as u32));
-        }
+        computer.mmu.without_access_log(|mmu| {
+            for (index, item) in result.iter_mut().enumerate().take(PC_BYTES_SIZE) {
+                *item = mmu.read_byte(pc + (index as u32));
+            }
+        });
         result
     }
 }
 impl Display for ComputerState {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let formatted_cpu_state = self.debug_symbols.format_cpu(&self.cpu_state);
+
         if self.cpu_only {
-            return write!(f, "{}", self.cpu_state);
+            return write!(f, "{}", formatted_cpu_state);
         }
 
         let formatted_pc_bytes = if let Some(pc_bytes) = self.pc_bytes {
@@ -328,6 +467,16 @@ impl Display for ComputerState {
             ""
         };
 
+        let formatted_palette = if let Some(palette) = self.palette {
+            palette
+                .into_iter()
+                .map(|colour| format!("{:#08X}", colour))
+                .collect::<Vec<String>>()
+                .join(",")
+        } else {
+            String::new()
+        };
+
         let formatted_memory_ranges = if let Some(memory_ranges) = &self.memory_ranges {
             memory_ranges
                 .iter()
@@ -359,13 +508,16 @@ impl Display for ComputerState {
 
 INTERRUPTS={}; IREG:{:#010b} IEREG:{:#010b}
 
+\tPALETTE: [{}]
+
 {}",
             self.num_cycles.unwrap_or(0),
             formatted_pc_bytes,
-            self.cpu_state,
+            formatted_cpu_state,
             formatted_interrupts_enabled,
             self.interrupt_register.unwrap_or(0),
             self.ie_register.unwrap_or(0),
+            formatted_palette,
             formatted_memory_ranges,
         )
     }
@@ -1,4 +1,7 @@
 use std::{
+    collections::VecDeque,
+    fs,
+    str::FromStr,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
@@ -10,20 +13,25 @@ use std::{
 use camino::{Utf8Path, Utf8PathBuf};
 use color_eyre::eyre::{self, eyre};
 use crossbeam::channel;
-use mfs16core::{Computer, Interrupt, CLOCK_FREQ, DISPLAY_HEIGHT, DISPLAY_WIDTH};
+use mfs16core::{
+    Computer, GamepadAxis, GamepadButton, InputLog, InputReplay, KbCode, KbLayout, KeystrokeScript,
+    LineTable, SymbolMap, Tracer, VideoRecorder, CLOCK_FREQ, DISPLAY_HEIGHT, DISPLAY_WIDTH,
+    FRAME_CYCLES, NUM_PALETTE_COLOURS,
+};
 use sdl2::{
+    controller::GameController,
     event::Event,
     pixels::PixelFormatEnum,
     rect::Rect,
-    render::{Canvas, Texture, TextureAccess},
-    video::Window,
+    render::{Canvas, Texture, TextureAccess, TextureCreator},
+    video::{Window, WindowContext},
 };
 
 use crate::{
     arg_parser::Cli,
     config::UserConfig,
-    debug::Debugger,
-    palette::{HexPalette, Rgb24Palette},
+    debug::{ConditionalBreakpoint, DebugSymbols, Debugger},
+    palette::U24Colour,
 };
 
 // TODO add to config
@@ -33,8 +41,6 @@ const WINDOW_WIDTH: u32 = (DISPLAY_WIDTH as u32) * SCALE;
 const WINDOW_HEIGHT: u32 = (DISPLAY_HEIGHT as u32) * SCALE;
 
 const BYTES_PER_RGB24_PIXEL: usize = 3;
-const PIXELS_PER_VRAM_INDEX: usize = 2;
-const BYTES_PER_VRAM_INDEX: usize = BYTES_PER_RGB24_PIXEL * PIXELS_PER_VRAM_INDEX;
 
 const DEBUG_LOG_NAME: &str = "debug.log";
 
@@ -45,9 +51,7 @@ pub fn run_emulator(
     config: &UserConfig,
     data_dir: &Utf8Path,
 ) -> eyre::Result<()> {
-    let s_per_frame = 1.0 / args.fps;
-    let cycles_per_frame: u32 = (s_per_frame * (CLOCK_FREQ as f32)) as u32;
-    let frame_duration = Duration::from_secs_f32(s_per_frame);
+    let frame_duration = Duration::from_secs_f64(FRAME_CYCLES as f64 / CLOCK_FREQ as f64);
     let emu_frame_duration = frame_duration;
 
     let debug = args.debug;
@@ -63,8 +67,42 @@ pub fn run_emulator(
     let (vram_sender, vram_receiver) = channel::bounded(2);
     // Channel to signal frame updates to the emulation thread
     let (frame_sender, frame_receiver) = channel::bounded(2);
-    // Channel to send keyboard updates to the emulation thread
-    let (kb_sender, kb_receiver) = channel::unbounded();
+    // Channel to send keyboard & gamepad updates to the emulation thread
+    let (input_sender, input_receiver) = channel::unbounded();
+    let keystroke_cycles = config.text_input_settings.keystroke_cycles;
+
+    // Type out the given file once the program starts
+    if let Some(path) = &args.type_file {
+        let _ = input_sender.send(InputEvent::Type(fs::read_to_string(path)?));
+    }
+
+    // Load the input log to replay, if any
+    let mut input_replay = match &args.replay {
+        Some(path) => Some(InputReplay::new(
+            fs::read_to_string(path)?
+                .parse::<InputLog>()
+                .map_err(|e| eyre!("Failed to load input log \"{path}\": {e}"))?,
+        )),
+        None => None,
+    };
+
+    // Load the symbols and source lines used to label addresses in the debug log, if any
+    let debug_symbols = Arc::new(DebugSymbols {
+        symbols: load_debug_file(args.symbols.as_deref(), SymbolMap::path_for(&args.bin))?,
+        lines: load_debug_file(args.lines.as_deref(), LineTable::path_for(&args.bin))?,
+    });
+    let breakpoints = config
+        .debugger_settings
+        .breakpoints
+        .iter()
+        .map(|bp| ConditionalBreakpoint::parse(bp, debug_symbols.symbols.as_ref()))
+        .collect::<eyre::Result<Vec<_>>>()?;
+
+    // Trace every executed instruction, if asked to
+    let mut tracer = match &args.trace {
+        Some(path) => Some(Tracer::create(path, args.trace_range.clone())?),
+        None => None,
+    };
 
     // Atomic flag to signal program quit
     let should_quit = Arc::new(AtomicBool::new(false));
@@ -78,14 +116,24 @@ pub fn run_emulator(
         // Set up debugger
         let mut debugger = Debugger::new(
             break_criteria,
+            breakpoints,
             mem_ranges,
             cpu_debug,
             history_size,
             after_break_size,
+            debug_symbols,
         );
+        let watching = (debug || cpu_debug) && !debugger.criteria.watchpoints.is_empty();
+        computer.mmu.log_accesses(watching || tracer.is_some());
 
         let mut hit_breakpoint = false;
 
+        // Pasted text which is currently being typed out
+        let mut keystroke_script: Option<KeystrokeScript> = None;
+        // The typed characters of the pasted text which are yet to be given as host text input,
+        // as the host layout doesn't translate key presses into printable characters
+        let mut pasted_text: VecDeque<char> = VecDeque::new();
+
         while !emu_should_quit.load(Ordering::SeqCst) {
             // Check if stopped
             if computer.cpu.is_stopped {
@@ -113,15 +161,80 @@ pub fn run_emulator(
             let cycles_start = Instant::now();
 
             // Perform the CPU cycles for this frame
-            for _ in 0..cycles_per_frame {
+            for _ in 0..FRAME_CYCLES {
                 computer.cycle();
 
-                // Check for input
-                match kb_receiver.try_recv() {
-                    // Key down
-                    Ok(KeyEvent::KeyDown(code)) => computer.key_down(code),
-                    Ok(KeyEvent::KeyUp(code)) => computer.key_up(code),
-                    _ => {}
+                // Replay recorded input. Live input is ignored until the replay is done.
+                let live_input = input_receiver.try_recv();
+                if let Some(replay) = &mut input_replay {
+                    replay.apply(&mut computer);
+                    if replay.is_done() {
+                        input_replay = None;
+                    }
+                } else {
+                    // Check for input
+                    match live_input {
+                        // Key down
+                        Ok(InputEvent::KeyDown(code)) => computer.key_down(code),
+                        Ok(InputEvent::KeyUp(code)) => computer.key_up(code),
+                        Ok(InputEvent::Text(text)) => computer.text_input(&text),
+                        Ok(InputEvent::ButtonDown(button)) => computer.button_down(button),
+                        Ok(InputEvent::ButtonUp(button)) => computer.button_up(button),
+                        Ok(InputEvent::AxisMotion(axis, value)) => {
+                            computer.axis_motion(axis, value)
+                        }
+                        Ok(InputEvent::Type(text)) => {
+                            let layout = computer.mmu.kb_layout;
+                            if layout == KbLayout::Host {
+                                pasted_text.extend(text.replace("\r\n", "\n").chars().filter(
+                                    |&ch| KeystrokeScript::key_for_char(ch, layout).is_some(),
+                                ));
+                            }
+                            let script = keystroke_script.get_or_insert_with(|| {
+                                KeystrokeScript::new(layout, keystroke_cycles)
+                            });
+                            script.push_text(&text);
+                            if debug && !script.skipped().is_empty() {
+                                println!("Skipped untypeable characters: {:?}", script.skipped());
+                            }
+                        }
+                        _ => {}
+                    }
+
+                    // Type out any pasted text
+                    if let Some(script) = &mut keystroke_script {
+                        // Give each character as text input as its key is pressed
+                        match script.cycle(&mut computer) {
+                            Some(keystroke)
+                                if keystroke.pressed && keystroke.code != KbCode::LShift =>
+                            {
+                                if let Some(ch) = pasted_text.pop_front() {
+                                    computer.text_input(ch.encode_utf8(&mut [0; 4]));
+                                }
+                            }
+                            _ => {}
+                        }
+                        if script.is_done() {
+                            keystroke_script = None;
+                        }
+                    }
+                }
+
+                let accesses = if computer.cpu.instr_is_done() {
+                    computer.mmu.take_accesses()
+                } else {
+                    Vec::new()
+                };
+
+                // Trace the instruction if it's done
+                if let Some(tracer) = &mut tracer {
+                    if computer.cpu.instr_is_done() {
+                        if let Err(e) = tracer.record(&mut computer, accesses.clone()) {
+                            emu_should_quit.store(true, Ordering::SeqCst);
+                            eprintln!("{}", eyre!("Failed to write trace: {e}"));
+                            break;
+                        }
+                    }
                 }
 
                 // Do debugging stuff if the instruction is done
@@ -133,7 +246,7 @@ pub fn run_emulator(
                         }
                     } else {
                         debugger.add_state(&mut computer);
-                        if debugger.criteria.is_satisfied(&computer) {
+                        if debugger.should_break(&mut computer, &accesses) {
                             hit_breakpoint = true;
                         }
                     }
@@ -142,36 +255,42 @@ pub fn run_emulator(
                         println!("{}", computer.cpu)
                     }
                 }
-            }
-
-            // Send the new VRAM state.
-            // If manual frame updates are enabled, try consuming the frame update flag. In this
-            // case, only send a VRAM update if the flag was set.
-            if !computer.mmu.gpu.is_man_frame_enabled()
-                || computer.mmu.gpu.consume_frame_update_flag()
-            {
-                // Set the frame interrupt
-                computer.mmu.set_interrupt(Interrupt::Frame);
 
-                if let Err(e) = vram_sender.send(computer.mmu.gpu.vram.to_vec()) {
-                    emu_should_quit.store(true, Ordering::SeqCst);
-                    eprintln!("{}", eyre!("{e}"));
-                    break;
+                // Send the new frame, along with the palette to display it with, whenever the GPU
+                // presents one
+                if computer.mmu.gpu.consume_frame_ready() {
+                    let (width, height) = computer.mmu.gpu.frame_size();
+                    let frame = Frame {
+                        pixels: computer.mmu.gpu.frame_buffer().to_vec(),
+                        width,
+                        height,
+                        palette: *computer.mmu.gpu.palette(),
+                        cycles: computer.cycles,
+                    };
+                    if let Err(e) = vram_sender.send(frame) {
+                        emu_should_quit.store(true, Ordering::SeqCst);
+                        eprintln!("{}", eyre!("{e}"));
+                        break;
+                    }
                 }
             }
 
+            if emu_should_quit.load(Ordering::SeqCst) {
+                break;
+            }
+
             if (debug || cpu_debug) && (cycles_start.elapsed() >= emu_frame_duration) {
                 println!(
                     "Time limit for {} cycles: {:?}\nActual time: {:?}",
-                    cycles_per_frame,
+                    FRAME_CYCLES,
                     emu_frame_duration,
                     cycles_start.elapsed()
                 );
             }
         }
 
-        // Execution done, send debug results (if any)
-        debugger
+        // Execution done, send debug results (if any) and recorded input (if any)
+        (debugger, computer.input_log, tracer)
     });
 
     // Set up sdl2
@@ -190,32 +309,31 @@ pub fn run_emulator(
         Err(e) => return Err(eyre!(e)),
     };
 
+    let controller_subsystem = match sdl_context.game_controller() {
+        Ok(cs) => cs,
+        Err(e) => return Err(eyre!(e)),
+    };
+    // Opened game controllers must be kept alive in order to receive their events
+    let mut controllers: Vec<GameController> = Vec::new();
+
     let window = video_subsystem
         .window("MFS-16", WINDOW_WIDTH, WINDOW_HEIGHT)
         .position_centered()
         .build()?;
     let mut sdl_canvas = window.into_canvas().build()?;
 
+    // Receive typed text as well as raw key presses
+    video_subsystem.text_input().start();
+
     let texture_creator = sdl_canvas.texture_creator();
-    let mut texture = texture_creator.create_texture(
-        PixelFormatEnum::RGB24,
-        TextureAccess::Streaming,
-        DISPLAY_WIDTH.try_into()?,
-        DISPLAY_HEIGHT.try_into()?,
-    )?;
-
-    // Get colour palette
-    let palette = Rgb24Palette::from_hex_palette(match config.palette() {
-        Some(p) => p,
-        None => {
-            // Print warning if palette in config doesn't match anything
-            println!(
-                "Warning: No preset palette matches given config value \"{}\".",
-                config.palette_settings.preset_palette
-            );
-            HexPalette::default()
-        }
-    });
+    let mut texture = create_texture(&texture_creator, DISPLAY_WIDTH, DISPLAY_HEIGHT)?;
+    let mut texture_size = (DISPLAY_WIDTH, DISPLAY_HEIGHT);
+
+    // Record the frames to a video file, if requested
+    let mut video_recorder = match &args.video {
+        Some(path) => Some(VideoRecorder::create(path)?),
+        None => None,
+    };
 
     // Create pixel array
     let mut pixels = vec![0_u8; DISPLAY_WIDTH * DISPLAY_HEIGHT * BYTES_PER_RGB24_PIXEL];
@@ -248,11 +366,17 @@ pub fn run_emulator(
                 Event::KeyUp {
                     scancode: Some(sc), ..
                 } => {
+                    if &sc == config.paste_scancode() {
+                        continue;
+                    }
+
                     // Send keyboard input to MFS-16
-                    let _ = kb_sender.send(KeyEvent::KeyUp(sc as i32 as u16));
+                    let _ = input_sender.send(InputEvent::KeyUp(sc as i32 as u16));
                 }
                 Event::KeyDown {
-                    scancode: Some(sc), ..
+                    scancode: Some(sc),
+                    repeat,
+                    ..
                 } => {
                     if &sc == config.exit_scancode() {
                         let _ = frame_sender.send(None);
@@ -262,16 +386,100 @@ pub fn run_emulator(
                         break;
                     }
 
+                    if &sc == config.paste_scancode() {
+                        if repeat {
+                            continue;
+                        }
+                        // Type out the host clipboard
+                        match video_subsystem.clipboard().clipboard_text() {
+                            Ok(text) => {
+                                let _ = input_sender.send(InputEvent::Type(text));
+                            }
+                            Err(e) => eprintln!("Warning: Failed to read clipboard: {e}"),
+                        }
+                        continue;
+                    }
+
                     // Send keyboard input to MFS-16
-                    let _ = kb_sender.send(KeyEvent::KeyDown(sc as i32 as u16));
+                    let _ = input_sender.send(InputEvent::KeyDown(sc as i32 as u16));
+                }
+                Event::TextInput { text, .. } => {
+                    // Send typed text to MFS-16
+                    let _ = input_sender.send(InputEvent::Text(text));
+                }
+                Event::ControllerDeviceAdded { which, .. } => {
+                    match controller_subsystem.open(which) {
+                        Ok(controller) => {
+                            if args.debug {
+                                println!("Game controller connected: {}", controller.name());
+                            }
+                            controllers.push(controller);
+                        }
+                        Err(e) => eprintln!("Warning: Failed to open game controller: {e}"),
+                    }
+                }
+                Event::ControllerDeviceRemoved { which, .. } => {
+                    controllers.retain(|controller| controller.instance_id() != which);
+                    // Release the removed controller's buttons and axes, leaving any held by the
+                    // remaining controllers
+                    let bindings = &config.gamepad_bindings;
+                    for (button, gp_button) in bindings.buttons() {
+                        if !controllers
+                            .iter()
+                            .any(|controller| controller.button(button))
+                        {
+                            let _ = input_sender.send(InputEvent::ButtonUp(gp_button));
+                        }
+                    }
+                    for (axis, gp_axis) in bindings.axes() {
+                        let value = controllers
+                            .iter()
+                            .map(|controller| controller.axis(axis))
+                            .find(|&value| value != 0)
+                            .unwrap_or(0);
+                        let _ = input_sender.send(InputEvent::AxisMotion(gp_axis, value));
+                    }
+                }
+                Event::ControllerButtonDown { button, .. } => {
+                    // Send gamepad input to MFS-16
+                    if let Some(gp_button) = config.gamepad_bindings.gamepad_button(button) {
+                        let _ = input_sender.send(InputEvent::ButtonDown(gp_button));
+                    }
+                }
+                Event::ControllerButtonUp { button, .. } => {
+                    // Send gamepad input to MFS-16
+                    if let Some(gp_button) = config.gamepad_bindings.gamepad_button(button) {
+                        let _ = input_sender.send(InputEvent::ButtonUp(gp_button));
+                    }
+                }
+                Event::ControllerAxisMotion { axis, value, .. } => {
+                    // Send gamepad input to MFS-16
+                    if let Some(gp_axis) = config.gamepad_bindings.gamepad_axis(axis) {
+                        let _ = input_sender.send(InputEvent::AxisMotion(gp_axis, value));
+                    }
                 }
                 _ => {}
             }
         }
 
         // Render the current frame
-        if let Ok(vram) = vram_receiver.try_recv() {
-            render_graphics(&mut sdl_canvas, &mut pixels, &mut texture, &palette, vram);
+        if let Ok(frame) = vram_receiver.try_recv() {
+            // The frame size changes with the video mode
+            if texture_size != (frame.width, frame.height) {
+                texture = create_texture(&texture_creator, frame.width, frame.height)?;
+                texture_size = (frame.width, frame.height);
+                pixels.resize(frame.width * frame.height * BYTES_PER_RGB24_PIXEL, 0);
+            }
+            if let Some(recorder) = &mut video_recorder {
+                recorder.record_frame(
+                    &frame.pixels,
+                    frame.width,
+                    frame.height,
+                    &frame.palette,
+                    frame.cycles,
+                )?;
+            }
+            render_graphics(&mut sdl_canvas, &mut pixels, &mut texture, frame);
             fps += 1;
         }
 
@@ -290,49 +498,59 @@ pub fn run_emulator(
     should_quit.store(true, Ordering::SeqCst);
     drop(frame_sender);
     match emu_thread.join() {
-        Ok(debugger) => {
+        Ok((debugger, input_log, tracer)) => {
             if args.debug || args.cpu_debug {
                 let mut debug_log_path = Utf8PathBuf::from(data_dir);
                 debug_log_path.push(DEBUG_LOG_NAME);
                 debugger.write_to_file(debug_log_path)?;
             }
+            if let (Some(path), Some(input_log)) = (&args.record, input_log) {
+                fs::write(path, input_log.to_string())?;
+            }
+            if let Some(tracer) = tracer {
+                tracer.finish()?;
+            }
         }
         Err(_) => return Err(eyre!("Failed to join emulation thread,")),
     }
 
+    if let Some(recorder) = video_recorder {
+        recorder.finish()?;
+    }
+
     Ok(())
 }
 
+/// Create a streaming RGB24 texture of the given size.
+fn create_texture(
+    texture_creator: &TextureCreator<WindowContext>,
+    width: usize,
+    height: usize,
+) -> eyre::Result<Texture<'_>> {
+    Ok(texture_creator.create_texture(
+        PixelFormatEnum::RGB24,
+        TextureAccess::Streaming,
+        width.try_into()?,
+        height.try_into()?,
+    )?)
+}
+
 fn render_graphics(
     sdl_canvas: &mut Canvas<Window>,
     pixels: &mut [u8],
     texture: &mut Texture,
-    palette: &Rgb24Palette,
-    vram: Vec<u8>,
+    frame: Frame,
 ) {
     // Create destination rectangle that is the size of the window
     let dest_rect = Rect::new(0, 0, WINDOW_WIDTH, WINDOW_HEIGHT);
 
-    // Fill the pixel array from VRAM, 6 bytes at a time
-    for (vram_offset, vram_byte) in vram
-        .iter()
-        .enumerate()
-        .take(pixels.len() / BYTES_PER_VRAM_INDEX)
+    // Fill the pixel array from the frame, looking up each pixel's colour in the palette
+    for (rgb24_pixel, &index) in pixels
+        .chunks_exact_mut(BYTES_PER_RGB24_PIXEL)
+        .zip(frame.pixels.iter())
     {
-        let pixels_offset = vram_offset * BYTES_PER_VRAM_INDEX;
-
-        let vram_upper_colour = palette.rgb24_from_nib(*vram_byte, true);
-        let vram_lower_colour = palette.rgb24_from_nib(*vram_byte, false);
-
-        // Set bytes 0, 1, & 2 from upper nibble
-        pixels[pixels_offset] = vram_upper_colour.r;
-        pixels[pixels_offset + 1] = vram_upper_colour.g;
-        pixels[pixels_offset + 2] = vram_upper_colour.b;
-
-        // Set bytes 3, 4, & 5 from lower nibble
-        pixels[pixels_offset + 3] = vram_lower_colour.r;
-        pixels[pixels_offset + 4] = vram_lower_colour.g;
-        pixels[pixels_offset + 5] = vram_lower_colour.b;
+        let colour = <U24Colour>::from(frame.palette[index as usize]);
+        rgb24_pixel.copy_from_slice(&[colour.r, colour.g, colour.b]);
     }
 
     // Update the texture
@@ -346,7 +564,43 @@ fn render_graphics(
     sdl_canvas.present();
 }
 
-enum KeyEvent {
+/// A frame presented by the GPU, along with the palette to display it with.
+struct Frame {
+    /// One palette index per pixel, row by row.
+    pixels: Vec<u8>,
+    /// The width of the frame in pixels.
+    width: usize,
+    /// The height of the frame in pixels.
+    height: usize,
+    /// The colours of the palette, stored as `0x00RRGGBB`.
+    palette: [u32; NUM_PALETTE_COLOURS],
+    /// The cycle on which the frame was presented.
+    cycles: u128,
+}
+
+enum InputEvent {
     KeyUp(u16),
     KeyDown(u16),
+    Text(String),
+    Type(String),
+    ButtonUp(GamepadButton),
+    ButtonDown(GamepadButton),
+    AxisMotion(GamepadAxis, i16),
+}
+
+/// Load the debug file at the given path, or at the default path if none is given and a file
+/// exists there.
+fn load_debug_file<T: FromStr<Err = String>>(
+    path: Option<&Utf8Path>,
+    default_path: Utf8PathBuf,
+) -> eyre::Result<Option<T>> {
+    let path = match path {
+        Some(path) => path.to_owned(),
+        None if default_path.exists() => default_path,
+        None => return Ok(None),
+    };
+    fs::read_to_string(&path)?
+        .parse::<T>()
+        .map(Some)
+        .map_err(|e| eyre!("Failed to load \"{path}\": {e}"))
 }
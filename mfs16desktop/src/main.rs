@@ -3,10 +3,11 @@ use std::{fs::File, io::Read};
 use camino::Utf8Path;
 use clap::Parser;
 use color_eyre::eyre;
-use mfs16core::{Addr, Computer};
+use mfs16core::{Addr, Computer, InputLog};
 
 mod arg_parser;
 mod config;
+mod controllers;
 mod debug;
 mod emulator;
 mod palette;
@@ -50,6 +51,34 @@ fn main() -> eyre::Result<()> {
         computer.kb_debug = true;
     }
 
+    if args.record.is_some() {
+        computer.input_log = Some(InputLog::default());
+    }
+
+    // Set keyboard layout
+    match config.kb_layout() {
+        Some(layout) => computer.set_kb_layout(layout),
+        None => {
+            // Print warning if layout in config doesn't match anything
+            println!(
+                "Warning: No keyboard layout matches given config value \"{}\".",
+                config.text_input_settings.layout
+            );
+        }
+    }
+
+    // Set the power-on colour palette
+    match config.palette() {
+        Some(palette) => computer.mmu.gpu.set_palette(&palette.to_u24_colours()),
+        None => {
+            // Print warning if palette in config doesn't match anything
+            println!(
+                "Warning: No preset palette matches given config value \"{}\".",
+                config.palette_settings.preset_palette
+            );
+        }
+    }
+
     if args.debug {
         dbg!(&config);
     }
@@ -0,0 +1,42 @@
+//! Parse command-line arguments for the disassembler.
+use camino::Utf8PathBuf;
+use clap::Parser;
+
+/// The CLI parser.
+#[derive(Parser, Debug)]
+#[command(name = "mfsd")]
+#[command(author)]
+#[command(about = "Disassembler for MFS-16 machine code.")]
+pub struct Cli {
+    /// The machine code file to disassemble.
+    pub file: Utf8PathBuf,
+
+    /// Additional addresses to start disassembling code from, e.g. `0x1234`. Address 0 and any
+    /// interrupt handlers are always used.
+    #[clap(short, long, value_parser = parse_addr)]
+    pub entry: Vec<u32>,
+
+    /// Name labels after this symbol map. Defaults to `<file>.sym` if it exists.
+    #[clap(short, long)]
+    pub symbols: Option<Utf8PathBuf>,
+
+    /// The replace flag. Set to overwrite any existing files with the output.
+    #[clap(short, long)]
+    pub replace: bool,
+
+    /// Output mfs16asm to this file path. Leave blank to output to stdout.
+    #[clap(short, long)]
+    pub output: Option<Utf8PathBuf>,
+}
+
+fn parse_addr(s: &str) -> Result<u32, String> {
+    let cleaned = s.replace('_', "");
+    match cleaned
+        .strip_prefix("0x")
+        .or_else(|| cleaned.strip_prefix("0X"))
+    {
+        Some(hex) => u32::from_str_radix(hex, 16),
+        None => cleaned.parse(),
+    }
+    .map_err(|e| format!("invalid address `{s}`: {e}"))
+}
@@ -0,0 +1,66 @@
+//! Disassembler for MFS-16 machine code.
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+};
+
+use camino::Utf8Path;
+use clap::Parser;
+use color_eyre::eyre::eyre;
+use mfs16core::{default_entry_points, Disassembly, SymbolMap};
+
+mod arg_parser;
+
+use arg_parser::Cli;
+
+fn main() -> color_eyre::eyre::Result<()> {
+    color_eyre::install()?;
+
+    let args = Cli::parse();
+
+    let machine_code = fs::read(&args.file)?;
+
+    let mut entry_points = default_entry_points(&machine_code);
+    entry_points.extend(args.entry);
+    let symbols = load_symbols(&args.file, args.symbols.as_deref())?;
+    let asm = Disassembly::with_symbols(&machine_code, &entry_points, &symbols).to_string();
+
+    if let Some(output_path) = &args.output {
+        file_output(output_path, &asm, args.replace)?;
+    } else {
+        io::stdout().lock().write_all(asm.as_bytes())?;
+    }
+
+    Ok(())
+}
+
+fn load_symbols(
+    bin_path: &Utf8Path,
+    path: Option<&Utf8Path>,
+) -> color_eyre::eyre::Result<SymbolMap> {
+    let path = match path {
+        Some(path) => path.to_owned(),
+        None => {
+            let default_path = SymbolMap::path_for(bin_path);
+            if !default_path.exists() {
+                return Ok(SymbolMap::default());
+            }
+            default_path
+        }
+    };
+    fs::read_to_string(&path)?
+        .parse::<SymbolMap>()
+        .map_err(|e| eyre!("Failed to load symbol map \"{path}\": {e}"))
+}
+
+fn file_output(path: &Utf8Path, asm: &str, replace: bool) -> color_eyre::eyre::Result<()> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create(replace)
+        .truncate(replace)
+        .create_new(!replace)
+        .open(path)?;
+    file.write_all(asm.as_bytes())?;
+    println!("Disassembled to `{}` successfully!", path);
+    Ok(())
+}
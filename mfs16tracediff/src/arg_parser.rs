@@ -0,0 +1,34 @@
+//! Parse command-line arguments for the trace diff tool.
+use camino::Utf8PathBuf;
+use clap::Parser;
+use mfs16core::TRACE_FIELDS;
+
+/// The CLI parser.
+#[derive(Parser, Debug)]
+#[command(name = "mfs16tracediff")]
+#[command(author)]
+#[command(about = "Find where two MFS-16 instruction traces diverge.")]
+#[command(long_about = "Find where two MFS-16 instruction traces diverge.
+
+Traces only hold the registers after each instruction and the memory accesses it made, so \
+differences in memory are only found once an instruction reads or writes them differently, or \
+finds a different value in memory before writing it. Memory which differs but is never accessed \
+isn't reported.
+
+The desktop app's debug.log can't be diffed, as it only holds a human-readable dump of the last \
+few states before and after a break. Record a trace with --trace instead.")]
+pub struct Cli {
+    /// The first JSON Lines trace.
+    pub left: Utf8PathBuf,
+
+    /// The second JSON Lines trace.
+    pub right: Utf8PathBuf,
+
+    /// The number of instructions to show before and after the divergence.
+    #[clap(short, long, default_value_t = 5)]
+    pub context: usize,
+
+    /// Don't compare this field, e.g. `cycle` to compare builds with different timings.
+    #[clap(short, long, value_parser = clap::builder::PossibleValuesParser::new(TRACE_FIELDS))]
+    pub ignore: Vec<String>,
+}
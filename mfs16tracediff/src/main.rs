@@ -0,0 +1,42 @@
+//! Find the first instruction where two MFS-16 instruction traces diverge.
+use std::{fs, process::ExitCode};
+
+use camino::Utf8Path;
+use clap::Parser;
+use color_eyre::eyre::eyre;
+use mfs16core::{TraceDivergence, TraceFormat, TraceRecord};
+
+mod arg_parser;
+
+use arg_parser::Cli;
+
+fn main() -> color_eyre::eyre::Result<ExitCode> {
+    color_eyre::install()?;
+
+    let args = Cli::parse();
+
+    let left = load_trace(&args.left)?;
+    let right = load_trace(&args.right)?;
+    let ignore: Vec<&str> = args.ignore.iter().map(String::as_str).collect();
+
+    match TraceDivergence::find(&left, &right, &ignore) {
+        Some(divergence) => {
+            print!("{}", divergence.report(&left, &right, args.context));
+            Ok(ExitCode::FAILURE)
+        }
+        None => {
+            println!("Traces are identical ({} records).", left.len());
+            Ok(ExitCode::SUCCESS)
+        }
+    }
+}
+
+fn load_trace(path: &Utf8Path) -> color_eyre::eyre::Result<Vec<TraceRecord>> {
+    if TraceFormat::from_path(path) == Some(TraceFormat::Csv) {
+        return Err(eyre!(
+            "\"{path}\" is a CSV trace. Only JSON Lines traces can be diffed."
+        ));
+    }
+    TraceRecord::from_json_lines(&fs::read_to_string(path)?)
+        .map_err(|e| eyre!("Failed to load trace \"{path}\": {e}"))
+}